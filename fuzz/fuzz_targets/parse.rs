@@ -0,0 +1,21 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use jsx::{ Arena, Syntax, ParseOptions, TransformOptions, };
+
+
+// cargo +nightly fuzz run parse
+fuzz_target!(|data: &[u8]| {
+    if let Ok(source) = std::str::from_utf8(data) {
+        for &syntax in [Syntax::Jsx, Syntax::Tsx].iter() {
+            let arena = Arena::new();
+            let _ = jsx::tokenize(source, ParseOptions { syntax });
+            let (program, _) = jsx::parse_recovering(source, &arena, ParseOptions { syntax });
+            let _ = jsx::to_json(source, &program);
+
+            let options = TransformOptions { syntax, ..TransformOptions::default() };
+            let _ = jsx::transform(source, options);
+        }
+    }
+});
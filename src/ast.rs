@@ -1,10 +1,11 @@
-use crate::lex::Token;
+use crate::entity::decode_entities;
+use crate::list::List;
 
 use std::ops::Deref;
-use std::collections::HashMap;
 
 
-#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Token2Kind {
     Identifier,
     LiteralString,
@@ -13,7 +14,8 @@ pub enum Token2Kind {
     SelfClosingElement,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Token2 {
     pub start: usize,
     pub end: usize,
@@ -22,6 +24,7 @@ pub struct Token2 {
 
 
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[derive(Debug, Eq, Clone)]
 pub struct Loc<T> {
     pub start: usize,
@@ -58,124 +61,1303 @@ impl<T: PartialEq> PartialEq for Loc<T> {
     }
 }
 
+/// A node allocated in the `Arena` of the parse. Nodes are `Copy` and never
+/// change, a rewrite allocates new ones, see `fold::Fold`.
+pub type Node<'ast, T> = &'ast Loc<T>;
+
+/// Nodes in source order, linked in the `Arena` of the parse.
+pub type NodeList<'ast, T> = List<'ast, Node<'ast, T>>;
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum Node {
-    // OpeningFragment,
-    // ClosingFragment,
-    // OpeningElement(OpeningElement),
-    // ClosingElement(ClosingElement),
-    // SelfClosingElement(SelfClosingElement),
-    Element(ElementExpression),
-    Fragment(FragmentExpression),
+
+/// A whole `.jsx` file, parsed as an ECMAScript module.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Program<'ast> {
+    pub body: NodeList<'ast, Statement<'ast>>,
+    /// Every comment in the source, in source order. Comments are not part
+    /// of the nodes, the spans tell where they go.
+    pub comments: List<'ast, Loc<Comment>>,
 }
 
+// `// line` | `/* block */`, the span includes the delimiters.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Comment {
+    Line,
+    Block,
+}
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct MemberExpression {
-    pub members: Vec<Loc<Token>>,
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MemberExpression<'ast> {
+    pub members: List<'ast, Loc<&'ast str>>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct NamespacedName {
-    pub ns: Loc<Token>,
-    pub name: Loc<Token>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct NamespacedName<'ast> {
+    pub ns: Loc<&'ast str>,
+    pub name: Loc<&'ast str>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum ElementName {
-    Identifier(Loc<Token>),
-    NamespacedName(NamespacedName),
-    MemberExpression(MemberExpression),
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ElementName<'ast> {
+    Identifier(Loc<&'ast str>),
+    NamespacedName(NamespacedName<'ast>),
+    MemberExpression(MemberExpression<'ast>),
 }
 
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum NormalAttributeName {
-    Identifier(Loc<Token>),
-    NamespacedName(NamespacedName),
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NormalAttributeName<'ast> {
+    Identifier(Loc<&'ast str>),
+    NamespacedName(NamespacedName<'ast>),
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NormalAttributeInitializer<'ast> {
+    LiteralString(Loc<&'ast str>),
+    AssignmentExpression(Node<'ast, Expression<'ast>>),
+    ElementExpression(Node<'ast, ElementExpression<'ast>>),
+    FragmentExpression(Node<'ast, FragmentExpression<'ast>>),
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum NormalAttributeInitializer {
-    LiteralString(Loc<Token>),
-    AssignmentExpression(Loc<AssignmentExpression>),
-    ElementExpression(Loc<ElementExpression>),
-    FragmentExpression(Loc<FragmentExpression>),
+impl<'ast> NormalAttributeInitializer<'ast> {
+    /// Value of a quoted initializer with its character references decoded,
+    /// JSX strings have no escape sequences.
+    pub fn cooked_string(&self) -> Option<String> {
+        match self {
+            NormalAttributeInitializer::LiteralString(literal) => {
+                let raw = literal.item.get(1..literal.item.len() - 1).unwrap_or("");
+                Some(decode_entities(raw).into_owned())
+            },
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct NormalAttribute {
-    pub name: NormalAttributeName,
-    pub init: Option<NormalAttributeInitializer>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct NormalAttribute<'ast> {
+    pub name: NormalAttributeName<'ast>,
+    pub init: Option<NormalAttributeInitializer<'ast>>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum Attribute {
-    Normal(NormalAttribute),
-    Spread(Loc<Token>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Attribute<'ast> {
+    Normal(NormalAttribute<'ast>),
+    /// `{...props}`, the expression after `...`.
+    Spread(Node<'ast, Expression<'ast>>),
 }
 
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum OpeningOrSelfClosingElement {
-    Opening((ElementName, Vec<Attribute>)),
-    SelfClosing((ElementName, Vec<Attribute>)),
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OpeningOrSelfClosingElement<'ast> {
+    Opening((ElementName<'ast>, Option<Node<'ast, TsTypeArguments<'ast>>>, List<'ast, Attribute<'ast>>)),
+    SelfClosing((ElementName<'ast>, Option<Node<'ast, TsTypeArguments<'ast>>>, List<'ast, Attribute<'ast>>)),
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct OpeningElement {
-    pub name: ElementName,
-    pub attrs: Vec<Attribute>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OpeningElement<'ast> {
+    pub name: ElementName<'ast>,
+    pub attrs: List<'ast, Attribute<'ast>>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct ClosingElement {
-    pub name: ElementName,
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ClosingElement<'ast> {
+    pub name: ElementName<'ast>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct SelfClosingElement {
-    pub name: ElementName,
-    pub attrs: Vec<Attribute>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SelfClosingElement<'ast> {
+    pub name: ElementName<'ast>,
+    pub attrs: List<'ast, Attribute<'ast>>,
 }
 
 // SourceCharacter but not one of {, <, > or }
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[derive(Debug, PartialEq, Clone, Copy)]
-pub struct Text {
-    pub start: usize,
-    pub end: usize,
+pub struct Text<'ast> {
+    pub raw: &'ast str,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub struct ChildExpression {
-    pub start: usize,
-    pub end: usize,
+impl<'ast> Text<'ast> {
+    /// Value of the text as a child, character references decoded and whitespace
+    /// normalized by `cook_jsx_text`. Empty when the text produces no child.
+    #[inline]
+    pub fn cooked(&self) -> String {
+        cook_jsx_text(&decode_entities(self.raw))
+    }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum Child {
-    Text(Loc<Text>),
-    Element(ElementExpression),
-    ChildExpression(AssignmentExpression),
+/// Whitespace of JSX text as React and Babel treat it, tabs become spaces,
+/// every line is trimmed except the outer ends of the first and last line,
+/// lines left empty are dropped and the rest are joined with a space.
+///
+// "\n    ABC\n    " => "ABC"
+// "  a  \n  b  " => "  a b  "
+// "\n  \n" => ""
+// " " => " "
+pub fn cook_jsx_text(raw: &str) -> String {
+    let raw = raw.replace("\r\n", "\n");
+    let lines = raw.split(['\n', '\r']).collect::<Vec<&str>>();
+    // Babel starts at the first line, whitespace on one line is kept as it is.
+    let last_non_empty = lines.iter().rposition(|line| line.contains(|c| c != ' ' && c != '\t')).unwrap_or(0);
+
+    let mut cooked = String::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let line = line.replace('\t', " ");
+        let mut line = &line[..];
+        if idx > 0 {
+            line = line.trim_start_matches(' ');
+        }
+        if idx + 1 < lines.len() {
+            line = line.trim_end_matches(' ');
+        }
+
+        if !line.is_empty() {
+            cooked.push_str(line);
+            if idx != last_non_empty {
+                cooked.push(' ');
+            }
+        }
+    }
+
+    cooked
+}
+
+// { }
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EmptyExpression;
+
+/// Placeholder for source skipped after a syntax error, `Parser::parse_recovering`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ErrorNode;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Child<'ast> {
+    Text(Loc<Text<'ast>>),
+    Element(Node<'ast, ElementExpression<'ast>>),
+    Fragment(Node<'ast, FragmentExpression<'ast>>),
+    ChildExpression(Node<'ast, Expression<'ast>>),
+    /// `{...children}`, the expression after `...`.
+    Spread(Node<'ast, Expression<'ast>>),
+    EmptyExpression(Loc<EmptyExpression>),
+    Error(Loc<ErrorNode>),
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct FragmentExpression {
-    pub children: Vec<Child>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FragmentExpression<'ast> {
+    pub children: List<'ast, Child<'ast>>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct ElementExpression {
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ElementExpression<'ast> {
     pub is_self_closing: bool,
-    pub name: ElementName,
-    pub attrs: Vec<Attribute>,
-    pub children: Option<Vec<Child>>,
+    pub name: ElementName<'ast>,
+    /// `<Select<Option> />`, TSX only.
+    pub type_args: Option<Node<'ast, TsTypeArguments<'ast>>>,
+    pub attrs: List<'ast, Attribute<'ast>>,
+    pub children: Option<List<'ast, Child<'ast>>>,
 }
 
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct AssignmentExpression {
-    pub start: usize,
-    pub end: usize,
+
+
+// ECMAScript
+// https://tc39.es/ecma262/
+//
+// Identifiers carry their name, literals and `this` carry no payload, their
+// text is the source spanned by the surrounding `Loc`.
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Expression<'ast> {
+    This,
+    Super,
+    Identifier(&'ast str),
+    Literal(Literal),
+    Template(TemplateLiteral<'ast>),
+    TaggedTemplate(TaggedTemplateExpression<'ast>),
+    Array(ArrayExpression<'ast>),
+    Object(ObjectExpression<'ast>),
+    Function(Function<'ast>),
+    Class(Class<'ast>),
+    Arrow(ArrowFunctionExpression<'ast>),
+    Parenthesized(Node<'ast, Expression<'ast>>),
+    Unary(UnaryExpression<'ast>),
+    Update(UpdateExpression<'ast>),
+    Binary(BinaryExpression<'ast>),
+    Conditional(ConditionalExpression<'ast>),
+    Assignment(AssignmentExpression<'ast>),
+    Sequence(NodeList<'ast, Expression<'ast>>),
+    Call(CallExpression<'ast>),
+    New(NewExpression<'ast>),
+    StaticMember(StaticMemberExpression<'ast>),
+    ComputedMember(ComputedMemberExpression<'ast>),
+    // ...expr, only valid in array literals and arguments
+    Spread(Node<'ast, Expression<'ast>>),
+    Await(Node<'ast, Expression<'ast>>),
+    Yield(YieldExpression<'ast>),
+    // new.target, import.meta
+    MetaProperty(MetaProperty<'ast>),
+    // import(source)
+    Import(Node<'ast, Expression<'ast>>),
+    Element(Node<'ast, ElementExpression<'ast>>),
+    Fragment(Node<'ast, FragmentExpression<'ast>>),
+    // expr as Type
+    TsAs(TsAsExpression<'ast>),
+    // expr satisfies Type
+    TsSatisfies(TsAsExpression<'ast>),
+    // expr!
+    TsNonNull(Node<'ast, Expression<'ast>>),
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Literal {
+    Null,
+    True,
+    False,
+    Number,
+    BigInt,
+    String,
+    RegExp,
+}
+
+// `abc${ expr }def`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TemplateLiteral<'ast> {
+    /// Raw text between the substitutions, one more than `expressions`.
+    pub quasis: List<'ast, Loc<TemplateElement>>,
+    pub expressions: NodeList<'ast, Expression<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TemplateElement {
+    pub tail: bool,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TaggedTemplateExpression<'ast> {
+    pub tag: Node<'ast, Expression<'ast>>,
+    pub type_args: Option<Node<'ast, TsTypeArguments<'ast>>>,
+    pub quasi: Loc<TemplateLiteral<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ArrayExpression<'ast> {
+    /// `None` is a hole, `[a, , b]`.
+    pub elements: List<'ast, Option<Node<'ast, Expression<'ast>>>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PropertyKey<'ast> {
+    Identifier(Loc<&'ast str>),
+    // #name, class members only
+    PrivateName(Loc<&'ast str>),
+    Literal(Loc<Literal>),
+    Computed(Node<'ast, Expression<'ast>>),
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MethodKind {
+    Constructor,
+    Method,
+    Get,
+    Set,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Property<'ast> {
+    // key: value
+    Init { key: PropertyKey<'ast>, value: Node<'ast, Expression<'ast>> },
+    // key
+    Shorthand(Loc<&'ast str>),
+    // key() {}, get key() {}, set key(v) {}
+    Method { kind: MethodKind, key: PropertyKey<'ast>, value: Loc<Function<'ast>> },
+    // ...expr
+    Spread(Node<'ast, Expression<'ast>>),
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ObjectExpression<'ast> {
+    pub properties: NodeList<'ast, Property<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Function<'ast> {
+    pub id: Option<Loc<&'ast str>>,
+    pub is_async: bool,
+    pub is_generator: bool,
+    pub type_params: Option<Node<'ast, TsTypeParameters<'ast>>>,
+    pub params: NodeList<'ast, Pattern<'ast>>,
+    pub return_type: Option<Node<'ast, TsTypeAnnotation<'ast>>>,
+    pub body: Loc<BlockStatement<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Class<'ast> {
+    /// `abstract class`
+    pub modifiers: List<'ast, Loc<TsModifier>>,
+    pub id: Option<Loc<&'ast str>>,
+    pub type_params: Option<Node<'ast, TsTypeParameters<'ast>>>,
+    pub super_class: Option<Node<'ast, Expression<'ast>>>,
+    pub super_type_args: Option<Node<'ast, TsTypeArguments<'ast>>>,
+    /// `implements A, B`, spanning from the keyword.
+    pub implements: Option<Loc<NodeList<'ast, TsType<'ast>>>>,
+    pub body: NodeList<'ast, ClassMember<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ClassMember<'ast> {
+    // key() {}, static get key() {}
+    Method {
+        modifiers: List<'ast, Loc<TsModifier>>,
+        is_static: bool,
+        kind: MethodKind,
+        key: PropertyKey<'ast>,
+        /// `key?() {}`
+        optional: Option<Loc<&'ast str>>,
+        value: Loc<Function<'ast>>,
+    },
+    // key = value;
+    Property {
+        modifiers: List<'ast, Loc<TsModifier>>,
+        is_static: bool,
+        key: PropertyKey<'ast>,
+        annotation: Option<Loc<TsTypeAnnotation<'ast>>>,
+        value: Option<Node<'ast, Expression<'ast>>>,
+    },
+    // static { }
+    StaticBlock(BlockStatement<'ast>),
+    // Overloads and abstract methods, `key(): Type;`
+    TsMethodSignature {
+        modifiers: List<'ast, Loc<TsModifier>>,
+        is_static: bool,
+        kind: MethodKind,
+        key: PropertyKey<'ast>,
+        optional: Option<Loc<&'ast str>>,
+        value: Loc<TsDeclareFunction<'ast>>,
+    },
+    // [key: string]: Type;
+    TsIndexSignature(TsIndexSignature<'ast>),
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ArrowBody<'ast> {
+    Expression(Node<'ast, Expression<'ast>>),
+    Block(Loc<BlockStatement<'ast>>),
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ArrowFunctionExpression<'ast> {
+    pub is_async: bool,
+    pub type_params: Option<Node<'ast, TsTypeParameters<'ast>>>,
+    pub params: NodeList<'ast, Pattern<'ast>>,
+    pub return_type: Option<Node<'ast, TsTypeAnnotation<'ast>>>,
+    pub body: ArrowBody<'ast>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UnaryOperator {
+    Minus,       // -
+    Plus,        // +
+    Not,         // !
+    BitNot,      // ~
+    Typeof,      // typeof
+    Void,        // void
+    Delete,      // delete
+}
+
+impl UnaryOperator {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            UnaryOperator::Minus => "-",
+            UnaryOperator::Plus => "+",
+            UnaryOperator::Not => "!",
+            UnaryOperator::BitNot => "~",
+            UnaryOperator::Typeof => "typeof",
+            UnaryOperator::Void => "void",
+            UnaryOperator::Delete => "delete",
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct UnaryExpression<'ast> {
+    pub operator: UnaryOperator,
+    pub argument: Node<'ast, Expression<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UpdateOperator {
+    Increment,   // ++
+    Decrement,   // --
+}
+
+impl UpdateOperator {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            UpdateOperator::Increment => "++",
+            UpdateOperator::Decrement => "--",
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct UpdateExpression<'ast> {
+    pub operator: UpdateOperator,
+    pub prefix: bool,
+    pub argument: Node<'ast, Expression<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BinaryOperator {
+    Add,                 // +
+    Sub,                 // -
+    Mul,                 // *
+    Div,                 // /
+    Rem,                 // %
+    Exp,                 // **
+    Less,                // <
+    Greater,             // >
+    LessEqual,           // <=
+    GreaterEqual,        // >=
+    Equal,               // ==
+    NotEqual,            // !=
+    StrictEqual,         // ===
+    StrictNotEqual,      // !==
+    ShiftLeft,           // <<
+    ShiftRight,          // >>
+    UnsignedShiftRight,  // >>>
+    BitAnd,              // &
+    BitOr,               // |
+    BitXor,              // ^
+    In,                  // in
+    Instanceof,          // instanceof
+    And,                 // &&
+    Or,                  // ||
+    Nullish,             // ??
+}
+
+impl BinaryOperator {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Sub => "-",
+            BinaryOperator::Mul => "*",
+            BinaryOperator::Div => "/",
+            BinaryOperator::Rem => "%",
+            BinaryOperator::Exp => "**",
+            BinaryOperator::Less => "<",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::LessEqual => "<=",
+            BinaryOperator::GreaterEqual => ">=",
+            BinaryOperator::Equal => "==",
+            BinaryOperator::NotEqual => "!=",
+            BinaryOperator::StrictEqual => "===",
+            BinaryOperator::StrictNotEqual => "!==",
+            BinaryOperator::ShiftLeft => "<<",
+            BinaryOperator::ShiftRight => ">>",
+            BinaryOperator::UnsignedShiftRight => ">>>",
+            BinaryOperator::BitAnd => "&",
+            BinaryOperator::BitOr => "|",
+            BinaryOperator::BitXor => "^",
+            BinaryOperator::In => "in",
+            BinaryOperator::Instanceof => "instanceof",
+            BinaryOperator::And => "&&",
+            BinaryOperator::Or => "||",
+            BinaryOperator::Nullish => "??",
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct BinaryExpression<'ast> {
+    pub operator: BinaryOperator,
+    pub left: Node<'ast, Expression<'ast>>,
+    pub right: Node<'ast, Expression<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ConditionalExpression<'ast> {
+    pub test: Node<'ast, Expression<'ast>>,
+    pub consequent: Node<'ast, Expression<'ast>>,
+    pub alternate: Node<'ast, Expression<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AssignmentOperator {
+    Assign,                  // =
+    AddAssign,               // +=
+    SubAssign,               // -=
+    MulAssign,               // *=
+    DivAssign,               // /=
+    RemAssign,               // %=
+    ExpAssign,               // **=
+    ShiftLeftAssign,         // <<=
+    ShiftRightAssign,        // >>=
+    UnsignedShiftRightAssign,// >>>=
+    BitAndAssign,            // &=
+    BitOrAssign,             // |=
+    BitXorAssign,            // ^=
+    AndAssign,               // &&=
+    OrAssign,                // ||=
+    NullishAssign,           // ??=
+}
+
+impl AssignmentOperator {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            AssignmentOperator::Assign => "=",
+            AssignmentOperator::AddAssign => "+=",
+            AssignmentOperator::SubAssign => "-=",
+            AssignmentOperator::MulAssign => "*=",
+            AssignmentOperator::DivAssign => "/=",
+            AssignmentOperator::RemAssign => "%=",
+            AssignmentOperator::ExpAssign => "**=",
+            AssignmentOperator::ShiftLeftAssign => "<<=",
+            AssignmentOperator::ShiftRightAssign => ">>=",
+            AssignmentOperator::UnsignedShiftRightAssign => ">>>=",
+            AssignmentOperator::BitAndAssign => "&=",
+            AssignmentOperator::BitOrAssign => "|=",
+            AssignmentOperator::BitXorAssign => "^=",
+            AssignmentOperator::AndAssign => "&&=",
+            AssignmentOperator::OrAssign => "||=",
+            AssignmentOperator::NullishAssign => "??=",
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AssignmentExpression<'ast> {
+    pub operator: AssignmentOperator,
+    pub left: Node<'ast, Pattern<'ast>>,
+    pub right: Node<'ast, Expression<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CallExpression<'ast> {
+    pub callee: Node<'ast, Expression<'ast>>,
+    pub type_args: Option<Node<'ast, TsTypeArguments<'ast>>>,
+    pub arguments: NodeList<'ast, Expression<'ast>>,
+    /// `callee?.()`
+    pub optional: bool,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct NewExpression<'ast> {
+    pub callee: Node<'ast, Expression<'ast>>,
+    pub type_args: Option<Node<'ast, TsTypeArguments<'ast>>>,
+    pub arguments: NodeList<'ast, Expression<'ast>>,
+}
+
+// object.property
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StaticMemberExpression<'ast> {
+    pub object: Node<'ast, Expression<'ast>>,
+    pub property: Loc<&'ast str>,
+    /// `object?.property`
+    pub optional: bool,
+}
+
+// object[property]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComputedMemberExpression<'ast> {
+    pub object: Node<'ast, Expression<'ast>>,
+    pub property: Node<'ast, Expression<'ast>>,
+    /// `object?.[property]`
+    pub optional: bool,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MetaProperty<'ast> {
+    pub meta: Loc<&'ast str>,
+    pub property: Loc<&'ast str>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct YieldExpression<'ast> {
+    pub delegate: bool,
+    pub argument: Option<Node<'ast, Expression<'ast>>>,
+}
+
+
+// Binding and assignment targets.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Pattern<'ast> {
+    Identifier(&'ast str),
+    Object(ObjectPattern<'ast>),
+    Array(ArrayPattern<'ast>),
+    // target = default
+    Assignment(AssignmentPattern<'ast>),
+    // ...target
+    Rest(Node<'ast, Pattern<'ast>>),
+    // Member expressions, only valid as assignment targets.
+    Expression(Node<'ast, Expression<'ast>>),
+    // target?: Type, target!: Type
+    Typed(TypedPattern<'ast>),
+    // private readonly target, a constructor parameter that is also a property
+    TsParameterProperty(TsParameterProperty<'ast>),
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PatternProperty<'ast> {
+    // key: target
+    Property { key: PropertyKey<'ast>, value: Node<'ast, Pattern<'ast>> },
+    // name, name = default
+    Shorthand { name: Loc<&'ast str>, init: Option<Node<'ast, Expression<'ast>>> },
+    // ...target
+    Rest(Node<'ast, Pattern<'ast>>),
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ObjectPattern<'ast> {
+    pub properties: NodeList<'ast, PatternProperty<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ArrayPattern<'ast> {
+    pub elements: List<'ast, Option<Node<'ast, Pattern<'ast>>>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TypedPattern<'ast> {
+    pub pattern: Node<'ast, Pattern<'ast>>,
+    pub annotation: Loc<TsTypeAnnotation<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsParameterProperty<'ast> {
+    pub modifiers: List<'ast, Loc<TsModifier>>,
+    pub parameter: Node<'ast, Pattern<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AssignmentPattern<'ast> {
+    pub left: Node<'ast, Pattern<'ast>>,
+    pub right: Node<'ast, Expression<'ast>>,
+}
+
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Statement<'ast> {
+    Empty,
+    Debugger,
+    Expression(Node<'ast, Expression<'ast>>),
+    Block(BlockStatement<'ast>),
+    Variable(VariableDeclaration<'ast>),
+    Function(Function<'ast>),
+    Class(Class<'ast>),
+    Return(Option<Node<'ast, Expression<'ast>>>),
+    If(IfStatement<'ast>),
+    Switch(SwitchStatement<'ast>),
+    While(WhileStatement<'ast>),
+    DoWhile(DoWhileStatement<'ast>),
+    For(ForStatement<'ast>),
+    ForIn(ForInStatement<'ast>),
+    ForOf(ForOfStatement<'ast>),
+    // break label;
+    Break(Option<Loc<&'ast str>>),
+    // continue label;
+    Continue(Option<Loc<&'ast str>>),
+    Throw(Node<'ast, Expression<'ast>>),
+    Try(TryStatement<'ast>),
+    Labeled(LabeledStatement<'ast>),
+    With(WithStatement<'ast>),
+    Import(ImportDeclaration<'ast>),
+    ExportNamed(ExportNamedDeclaration<'ast>),
+    ExportDefault(ExportDefaultDeclaration<'ast>),
+    ExportAll(ExportAllDeclaration<'ast>),
+    TsInterface(TsInterfaceDeclaration<'ast>),
+    TsTypeAlias(TsTypeAliasDeclaration<'ast>),
+    TsEnum(TsEnumDeclaration<'ast>),
+    TsModule(TsModuleDeclaration<'ast>),
+    // Overloads, `function f(a: string): void;`
+    TsDeclareFunction(TsDeclareFunction<'ast>),
+    // declare const a: string;
+    TsDeclare(Node<'ast, Statement<'ast>>),
+    /// Source skipped after a syntax error, `Parser::parse_recovering`.
+    Error,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct BlockStatement<'ast> {
+    pub body: NodeList<'ast, Statement<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum VariableKind {
+    Var,
+    Let,
+    Const,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct VariableDeclarator<'ast> {
+    pub id: Node<'ast, Pattern<'ast>>,
+    pub init: Option<Node<'ast, Expression<'ast>>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct VariableDeclaration<'ast> {
+    pub kind: VariableKind,
+    pub declarations: NodeList<'ast, VariableDeclarator<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct IfStatement<'ast> {
+    pub test: Node<'ast, Expression<'ast>>,
+    pub consequent: Node<'ast, Statement<'ast>>,
+    pub alternate: Option<Node<'ast, Statement<'ast>>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SwitchStatement<'ast> {
+    pub discriminant: Node<'ast, Expression<'ast>>,
+    pub cases: NodeList<'ast, SwitchCase<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SwitchCase<'ast> {
+    /// `None` is `default:`.
+    pub test: Option<Node<'ast, Expression<'ast>>>,
+    pub consequent: NodeList<'ast, Statement<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct WhileStatement<'ast> {
+    pub test: Node<'ast, Expression<'ast>>,
+    pub body: Node<'ast, Statement<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DoWhileStatement<'ast> {
+    pub body: Node<'ast, Statement<'ast>>,
+    pub test: Node<'ast, Expression<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ForInit<'ast> {
+    Variable(Loc<VariableDeclaration<'ast>>),
+    Expression(Node<'ast, Expression<'ast>>),
+}
+
+// for (init; test; update) body
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ForStatement<'ast> {
+    pub init: Option<ForInit<'ast>>,
+    pub test: Option<Node<'ast, Expression<'ast>>>,
+    pub update: Option<Node<'ast, Expression<'ast>>>,
+    pub body: Node<'ast, Statement<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ForHead<'ast> {
+    Variable(Loc<VariableDeclaration<'ast>>),
+    Pattern(Node<'ast, Pattern<'ast>>),
+}
+
+// for (left in right) body
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ForInStatement<'ast> {
+    pub left: ForHead<'ast>,
+    pub right: Node<'ast, Expression<'ast>>,
+    pub body: Node<'ast, Statement<'ast>>,
+}
+
+// for (left of right) body
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ForOfStatement<'ast> {
+    /// `for await (...)`
+    pub is_await: bool,
+    pub left: ForHead<'ast>,
+    pub right: Node<'ast, Expression<'ast>>,
+    pub body: Node<'ast, Statement<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TryStatement<'ast> {
+    pub block: Loc<BlockStatement<'ast>>,
+    pub handler: Option<Loc<CatchClause<'ast>>>,
+    pub finalizer: Option<Loc<BlockStatement<'ast>>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CatchClause<'ast> {
+    /// `None` is `catch { }`.
+    pub param: Option<Node<'ast, Pattern<'ast>>>,
+    pub body: Loc<BlockStatement<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LabeledStatement<'ast> {
+    pub label: Loc<&'ast str>,
+    pub body: Node<'ast, Statement<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct WithStatement<'ast> {
+    pub object: Node<'ast, Expression<'ast>>,
+    pub body: Node<'ast, Statement<'ast>>,
+}
+
+
+// Modules
+//
+// Exported and imported names are the text of an `Identifier` or a `LiteralString`,
+// quotes included.
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ImportSpecifier<'ast> {
+    // import name from "source"
+    Default(Loc<&'ast str>),
+    // import * as name from "source"
+    Namespace(Loc<&'ast str>),
+    // import { imported as local } from "source"
+    Named { is_type: bool, imported: Loc<&'ast str>, local: Loc<&'ast str> },
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ImportDeclaration<'ast> {
+    /// `import type { A } from "source"`
+    pub is_type: bool,
+    pub specifiers: NodeList<'ast, ImportSpecifier<'ast>>,
+    pub source: Loc<Literal>,
+}
+
+// export { local as exported }
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ExportSpecifier<'ast> {
+    /// `export { type A }`
+    pub is_type: bool,
+    pub local: Loc<&'ast str>,
+    pub exported: Loc<&'ast str>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ExportNamedDeclaration<'ast> {
+    /// `export type { A }`
+    pub is_type: bool,
+    /// `export const a = 1;`, `export function f() {}`
+    pub declaration: Option<Node<'ast, Statement<'ast>>>,
+    pub specifiers: NodeList<'ast, ExportSpecifier<'ast>>,
+    /// `export { a } from "source";`
+    pub source: Option<Loc<Literal>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ExportDefaultDeclaration<'ast> {
+    Function(Function<'ast>),
+    Class(Class<'ast>),
+    Expression(Node<'ast, Expression<'ast>>),
+    TsDeclareFunction(TsDeclareFunction<'ast>),
+    TsInterface(TsInterfaceDeclaration<'ast>),
+}
+
+// export * as exported from "source"
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ExportAllDeclaration<'ast> {
+    /// `export type * from "source"`
+    pub is_type: bool,
+    pub exported: Option<Loc<&'ast str>>,
+    pub source: Loc<Literal>,
+}
+
+
+// TypeScript
+// https://github.com/microsoft/TypeScript/blob/main/doc/spec-ARCHIVED.md
+//
+// Types are only parsed in TSX mode and never reach the output, keyword
+// types like `string` carry no payload, their text is the source.
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TsModifier {
+    Public,
+    Private,
+    Protected,
+    Readonly,
+    Abstract,
+    Override,
+    Declare,
+}
+
+/// `?`, `!` and `: Type` following a binding or a property name, the span
+/// starts at the first of them.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsTypeAnnotation<'ast> {
+    pub optional: bool,
+    pub definite: bool,
+    pub ty: Option<Node<'ast, TsType<'ast>>>,
+}
+
+// <T extends Constraint = Default>
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsTypeParameters<'ast> {
+    pub params: NodeList<'ast, TsTypeParameter<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsTypeParameter<'ast> {
+    /// `const`, `in` and `out`.
+    pub modifiers: List<'ast, Loc<&'ast str>>,
+    pub name: Loc<&'ast str>,
+    pub constraint: Option<Loc<TsType<'ast>>>,
+    pub default: Option<Loc<TsType<'ast>>>,
+}
+
+// <A, B>
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsTypeArguments<'ast> {
+    pub params: NodeList<'ast, TsType<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TsType<'ast> {
+    // any, unknown, string, number, boolean, void, never, this ...
+    Keyword,
+    Reference(TsTypeReference<'ast>),
+    // "a", 1, true, null
+    Literal(Literal),
+    Template(TsTemplateLiteralType<'ast>),
+    // Type[]
+    Array(Node<'ast, TsType<'ast>>),
+    // [A, b?: B, ...C]
+    Tuple(NodeList<'ast, TsTupleElement<'ast>>),
+    Union(NodeList<'ast, TsType<'ast>>),
+    Intersection(NodeList<'ast, TsType<'ast>>),
+    // (a: A) => B, new (a: A) => B
+    Function(TsFunctionType<'ast>),
+    // { a: A; b(): B }
+    TypeLiteral(NodeList<'ast, TsTypeMember<'ast>>),
+    // { [K in keyof T]?: T[K] }
+    Mapped(TsMappedType<'ast>),
+    Parenthesized(Node<'ast, TsType<'ast>>),
+    // keyof T, unique symbol, readonly T[]
+    Operator(TsTypeOperator<'ast>),
+    // typeof a.b
+    Query(TsTypeQuery<'ast>),
+    // import("source").Name
+    Import(TsImportType<'ast>),
+    // T[K]
+    IndexedAccess(TsIndexedAccessType<'ast>),
+    // T extends U ? X : Y
+    Conditional(TsConditionalType<'ast>),
+    // infer U extends Constraint
+    Infer(TsInferType<'ast>),
+    // value is Type, asserts value
+    Predicate(TsTypePredicate<'ast>),
+}
+
+// Name.Sub<Args>
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsTypeReference<'ast> {
+    pub name: List<'ast, Loc<&'ast str>>,
+    pub type_args: Option<Loc<TsTypeArguments<'ast>>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsTemplateLiteralType<'ast> {
+    pub quasis: List<'ast, Loc<TemplateElement>>,
+    pub types: NodeList<'ast, TsType<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsTupleElement<'ast> {
+    pub label: Option<Loc<&'ast str>>,
+    pub optional: bool,
+    pub rest: bool,
+    pub ty: Loc<TsType<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsFunctionType<'ast> {
+    pub is_constructor: bool,
+    pub type_params: Option<Node<'ast, TsTypeParameters<'ast>>>,
+    pub params: NodeList<'ast, Pattern<'ast>>,
+    pub return_type: Node<'ast, TsType<'ast>>,
+}
+
+// Members of interfaces and type literals.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TsTypeMember<'ast> {
+    // readonly key?: Type
+    Property { readonly: bool, key: PropertyKey<'ast>, optional: bool, ty: Option<Loc<TsType<'ast>>> },
+    // key?<T>(a: A): B, get key(): A
+    Method { kind: MethodKind, key: PropertyKey<'ast>, optional: bool, signature: TsSignature<'ast> },
+    // (a: A): B
+    Call(TsSignature<'ast>),
+    // new (a: A): B
+    Construct(TsSignature<'ast>),
+    // [key: string]: Type
+    Index(TsIndexSignature<'ast>),
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsSignature<'ast> {
+    pub type_params: Option<Node<'ast, TsTypeParameters<'ast>>>,
+    pub params: NodeList<'ast, Pattern<'ast>>,
+    pub return_type: Option<Loc<TsType<'ast>>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsIndexSignature<'ast> {
+    pub modifiers: List<'ast, Loc<TsModifier>>,
+    pub is_static: bool,
+    pub params: NodeList<'ast, Pattern<'ast>>,
+    pub ty: Loc<TsType<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TsMappedModifier {
+    Add,
+    Remove,
+    Present,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsMappedType<'ast> {
+    pub readonly: Option<TsMappedModifier>,
+    pub name: Loc<&'ast str>,
+    pub constraint: Node<'ast, TsType<'ast>>,
+    /// `[K in T as NewKey]`
+    pub name_type: Option<Node<'ast, TsType<'ast>>>,
+    pub optional: Option<TsMappedModifier>,
+    pub ty: Option<Node<'ast, TsType<'ast>>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TsTypeOperatorKind {
+    Keyof,
+    Unique,
+    Readonly,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsTypeOperator<'ast> {
+    pub operator: TsTypeOperatorKind,
+    pub ty: Node<'ast, TsType<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsTypeQuery<'ast> {
+    pub name: List<'ast, Loc<&'ast str>>,
+    pub type_args: Option<Loc<TsTypeArguments<'ast>>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsImportType<'ast> {
+    pub argument: Loc<Literal>,
+    pub qualifier: List<'ast, Loc<&'ast str>>,
+    pub type_args: Option<Loc<TsTypeArguments<'ast>>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsIndexedAccessType<'ast> {
+    pub object: Node<'ast, TsType<'ast>>,
+    pub index: Node<'ast, TsType<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsConditionalType<'ast> {
+    pub check: Node<'ast, TsType<'ast>>,
+    pub extends: Node<'ast, TsType<'ast>>,
+    pub true_type: Node<'ast, TsType<'ast>>,
+    pub false_type: Node<'ast, TsType<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsInferType<'ast> {
+    pub name: Loc<&'ast str>,
+    pub constraint: Option<Node<'ast, TsType<'ast>>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsTypePredicate<'ast> {
+    pub asserts: bool,
+    pub name: Loc<&'ast str>,
+    pub ty: Option<Node<'ast, TsType<'ast>>>,
+}
+
+// expr as Type, expr satisfies Type
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsAsExpression<'ast> {
+    pub expression: Node<'ast, Expression<'ast>>,
+    pub ty: Node<'ast, TsType<'ast>>,
+}
+
+// function f(a: A): B;
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsDeclareFunction<'ast> {
+    pub id: Option<Loc<&'ast str>>,
+    pub is_async: bool,
+    pub is_generator: bool,
+    pub type_params: Option<Node<'ast, TsTypeParameters<'ast>>>,
+    pub params: NodeList<'ast, Pattern<'ast>>,
+    pub return_type: Option<Node<'ast, TsTypeAnnotation<'ast>>>,
+}
+
+// interface Name<T> extends A, B { members }
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsInterfaceDeclaration<'ast> {
+    pub id: Loc<&'ast str>,
+    pub type_params: Option<Node<'ast, TsTypeParameters<'ast>>>,
+    pub extends: NodeList<'ast, TsType<'ast>>,
+    pub body: NodeList<'ast, TsTypeMember<'ast>>,
+}
+
+// type Name<T> = Type;
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsTypeAliasDeclaration<'ast> {
+    pub id: Loc<&'ast str>,
+    pub type_params: Option<Node<'ast, TsTypeParameters<'ast>>>,
+    pub ty: Loc<TsType<'ast>>,
+}
+
+// declare enum Name { A = 1, B }
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsEnumDeclaration<'ast> {
+    pub is_const: bool,
+    pub id: Loc<&'ast str>,
+    pub members: NodeList<'ast, TsEnumMember<'ast>>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsEnumMember<'ast> {
+    /// The text of an `Identifier` or a `LiteralString`, quotes included.
+    pub id: Loc<&'ast str>,
+    pub init: Option<Node<'ast, Expression<'ast>>>,
+}
+
+// declare module "name" { }, declare namespace A.B { }, declare global { }
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TsModuleDeclaration<'ast> {
+    /// The names of `A.B`, or one `Identifier` or `LiteralString`, quotes included.
+    pub id: List<'ast, Loc<&'ast str>>,
+    pub body: Option<NodeList<'ast, Statement<'ast>>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cook_jsx_text;
+
+    #[test]
+    fn cook_jsx_text_trims_lines() {
+        assert_eq!(cook_jsx_text("\n    ABC\n    "), "ABC");
+        assert_eq!(cook_jsx_text("  a  \n  b  "), "  a b  ");
+        assert_eq!(cook_jsx_text("a\n\n\tb\r\nc"), "a b c");
+        assert_eq!(cook_jsx_text("\n  \n"), "");
+    }
+
+    #[test]
+    fn cook_jsx_text_keeps_space_between_expressions() {
+        // <b>{first} {last}</b>
+        assert_eq!(cook_jsx_text(" "), " ");
+        assert_eq!(cook_jsx_text("\t "), "  ");
+    }
 }
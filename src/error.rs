@@ -1,8 +1,136 @@
+use std::fmt;
 
+use crate::source_map::LineIndex;
 
-#[derive(Debug, Clone, Copy)]
+
+/// Control flow of the lexer and the parser, `EndOfProgram` is how the lexer
+/// reports running out of source. Callers get a `ParseError`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Error {
     EndOfProgram,
     UnexpectedEndOfProgram,
     UnexpectedToken,
 }
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::EndOfProgram => f.write_str("end of input"),
+            Error::UnexpectedEndOfProgram => f.write_str("unexpected end of input"),
+            Error::UnexpectedToken => f.write_str("unexpected token"),
+        }
+    }
+}
+
+impl std::error::Error for Error { }
+
+
+/// A token the lexer cannot read, `@` or a string that runs to the end of the source.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LexError {
+    pub kind: Error,
+    /// Byte offsets of the unreadable source.
+    pub start: usize,
+    pub end: usize,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at {}", self.kind, self.start)
+    }
+}
+
+impl std::error::Error for LexError { }
+
+
+/// A syntax error located in the source.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    pub kind: Error,
+    /// Byte offsets of the offending token.
+    pub start: usize,
+    pub end: usize,
+    /// 1-based line and column of `start`, the column counts UTF-16 code units
+    /// like every column of the crate, see `LineIndex`.
+    pub line: usize,
+    pub column: usize,
+    /// Source text of the offending token, empty at the end of the source.
+    pub found: String,
+    /// What would have been accepted instead, "`)`", "identifier".
+    pub expected: Vec<String>,
+    pub message: String,
+}
+
+impl ParseError {
+    /// An error spanning `start..end` of `code`, the message defaults to the
+    /// found and expected tokens.
+    pub fn new(code: &str, kind: Error, (start, end): (usize, usize), expected: Vec<String>, message: Option<String>) -> Self {
+        let start = floor_char_boundary(code, start);
+        let mut end = floor_char_boundary(code, end.max(start));
+        // The lexer stops before a char it cannot read, `@`.
+        if start == end && kind == Error::UnexpectedToken {
+            end += code[start..].chars().next().map_or(0, char::len_utf8);
+        }
+        let found = code[start..end].to_string();
+
+        let (line, column) = LineIndex::new(code).line_column(start);
+
+        let message = message.unwrap_or_else(|| {
+            let mut message = match kind {
+                Error::UnexpectedToken if !found.is_empty() => format!("unexpected `{}`", found),
+                _ => "unexpected end of input".to_string(),
+            };
+            if !expected.is_empty() {
+                message.push_str(", expected ");
+                message.push_str(&expected.join(" or "));
+            }
+            message
+        });
+
+        ParseError { kind, start, end, line: line + 1, column: column + 1, found, expected, message }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError { }
+
+
+/// Every syntax error found in a source, in source order.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Diagnostics {
+    pub errors: Vec<ParseError>,
+}
+
+impl From<ParseError> for Diagnostics {
+    fn from(error: ParseError) -> Self {
+        Diagnostics { errors: vec![error] }
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, error) in self.errors.iter().enumerate() {
+            if idx > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostics { }
+
+#[inline]
+fn floor_char_boundary(code: &str, index: usize) -> usize {
+    let mut index = index.min(code.len());
+    while !code.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
@@ -0,0 +1,2078 @@
+use std::fmt::Write;
+
+use crate::list::List;
+
+use crate::lex::is_line_terminator;
+use crate::entity::decode_entities;
+use crate::source_map::{ LineIndex, write_json_string, };
+use crate::ast::{
+    Loc, NodeList, Program, Comment,
+
+    ElementExpression, FragmentExpression, ElementName,
+    Attribute, NormalAttributeName, NormalAttributeInitializer, Child,
+
+    Expression, Literal, TemplateLiteral, TemplateElement, Property, PropertyKey, MethodKind,
+    Function, Class, ClassMember, ArrowBody,
+    BinaryOperator,
+    Pattern, PatternProperty,
+    Statement, BlockStatement, VariableDeclaration, VariableKind, ForInit, ForHead,
+    ImportSpecifier, ExportDefaultDeclaration,
+
+    TsModifier, TsTypeAnnotation, TsTypeParameters, TsTypeArguments, TsType, TsTypeMember,
+    TsSignature, TsIndexSignature, TsMappedModifier, TsTypeOperatorKind,
+    TsDeclareFunction, TsInterfaceDeclaration, TsModuleDeclaration,
+};
+
+
+// ESTree shaped like @babel/parser output, with its JSX and TypeScript nodes.
+// https://github.com/babel/babel/blob/main/packages/babel-parser/ast/spec.md
+//
+// Offsets and columns count UTF-16 code units like JavaScript strings do.
+// Nodes the AST keeps no span for, `JSXOpeningElement`, `JSXExpressionContainer`
+// and `ClassBody`, are spanned by the tokens around them.
+
+/// The `File` node of `program`, parsed from `code`, as JSON.
+pub fn to_json(code: &str, program: &Program) -> String {
+    let mut writer = Writer::new(code);
+    writer.file(program);
+    writer.output
+}
+
+struct Writer<'a> {
+    code: &'a str,
+    lines: LineIndex,
+    output: String,
+    /// Start of the parentheses around the next node, Babel drops them and
+    /// marks the node `extra.parenthesized`.
+    paren_start: Option<usize>,
+    /// Fields of the next node, `declare: true`.
+    pending: Vec<(&'static str, String)>,
+    /// `extra` of every open node.
+    extras: Vec<Vec<(&'static str, String)>>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(code: &'a str) -> Self {
+        Writer {
+            code,
+            lines: LineIndex::new(code),
+            output: String::new(),
+            paren_start: None,
+            pending: Vec::new(),
+            extras: Vec::new(),
+        }
+    }
+
+    // Nodes and fields
+
+    /// UTF-16 offset, 1-based line and column of byte `index`.
+    fn position(&self, index: usize) -> (usize, usize, usize) {
+        let (line, column) = self.lines.line_column(index);
+        (self.lines.utf16_offset(index), line + 1, column)
+    }
+
+    fn open(&mut self, ty: &str, start: usize, end: usize) {
+        let (start_offset, start_line, start_column) = self.position(start);
+        let (end_offset, end_line, end_column) = self.position(end);
+        let _ = write!(
+            self.output,
+            "{{\"type\":\"{}\",\"start\":{},\"end\":{},\"loc\":{{\"start\":{{\"line\":{},\"column\":{},\"index\":{}}},\"end\":{{\"line\":{},\"column\":{},\"index\":{}}}}}",
+            ty, start_offset, end_offset,
+            start_line, start_column, start_offset,
+            end_line, end_column, end_offset,
+        );
+
+        for (key, value) in std::mem::take(&mut self.pending) {
+            self.raw(key, &value);
+        }
+
+        let extra = match self.paren_start.take() {
+            Some(paren_start) => vec![
+                ("parenthesized", "true".to_string()),
+                ("parenStart", self.position(paren_start).0.to_string()),
+            ],
+            None => Vec::new(),
+        };
+        self.extras.push(extra);
+    }
+
+    fn close(&mut self) {
+        let extra = self.extras.pop().unwrap_or_default();
+        if !extra.is_empty() {
+            self.output.push_str(",\"extra\":{");
+            for (idx, (key, value)) in extra.iter().enumerate() {
+                if idx > 0 {
+                    self.output.push(',');
+                }
+                let _ = write!(self.output, "\"{}\":{}", key, value);
+            }
+            self.output.push('}');
+        }
+        self.output.push('}');
+    }
+
+    #[inline]
+    fn key(&mut self, key: &str) {
+        let _ = write!(self.output, ",\"{}\":", key);
+    }
+
+    #[inline]
+    fn raw(&mut self, key: &str, value: &str) {
+        self.key(key);
+        self.output.push_str(value);
+    }
+
+    #[inline]
+    fn string(&mut self, key: &str, value: &str) {
+        self.key(key);
+        write_json_string(&mut self.output, value);
+    }
+
+    #[inline]
+    fn boolean(&mut self, key: &str, value: bool) {
+        self.raw(key, if value { "true" } else { "false" });
+    }
+
+    #[inline]
+    fn null(&mut self, key: &str) {
+        self.raw(key, "null");
+    }
+
+    /// Adds `key` to the `extra` of the open node.
+    fn extra(&mut self, key: &'static str, value: String) {
+        if let Some(extra) = self.extras.last_mut() {
+            extra.push((key, value));
+        }
+    }
+
+    /// `raw` and `rawValue` extras of a literal.
+    fn extra_raw(&mut self, start: usize, end: usize, value: String) {
+        let mut raw = String::new();
+        write_json_string(&mut raw, self.text(start, end));
+        self.extra("rawValue", value);
+        self.extra("raw", raw);
+    }
+
+    fn list<T>(&mut self, key: &str, items: List<T>, mut write: impl FnMut(&mut Self, &T)) {
+        self.key(key);
+        self.output.push('[');
+        for (idx, item) in items.iter().enumerate() {
+            if idx > 0 {
+                self.output.push(',');
+            }
+            write(self, item);
+        }
+        self.output.push(']');
+    }
+
+    /// `list` of nodes, written through their reference.
+    #[inline]
+    fn nodes<T>(&mut self, key: &str, items: NodeList<T>, mut write: impl FnMut(&mut Self, &Loc<T>)) {
+        self.list(key, items, |writer, item| write(writer, item));
+    }
+
+    fn option<T>(&mut self, key: &str, item: Option<&T>, write: impl FnOnce(&mut Self, &T)) {
+        self.key(key);
+        match item {
+            Some(item) => write(self, item),
+            None => self.output.push_str("null"),
+        }
+    }
+
+    // Source text
+
+    #[inline]
+    fn text(&self, start: usize, end: usize) -> &'a str {
+        self.code.get(start..end).unwrap_or("")
+    }
+
+    /// Index of the first char from `index` that is no whitespace or comment.
+    fn skip_trivia(&self, mut index: usize) -> usize {
+        loop {
+            let rest = self.code.get(index..).unwrap_or("");
+            match rest.chars().next() {
+                Some(c) if c.is_whitespace() || c == '\u{feff}' => index += c.len_utf8(),
+                Some('/') if rest.starts_with("//") => {
+                    index += rest.find(is_line_terminator).unwrap_or(rest.len());
+                },
+                Some('/') if rest.starts_with("/*") => {
+                    index += rest[2..].find("*/").map_or(rest.len(), |end| end + 4);
+                },
+                _ => return index,
+            }
+        }
+    }
+
+    /// End of `text` following `index`, `index` when something else follows.
+    fn after(&self, index: usize, text: &str) -> usize {
+        let start = self.skip_trivia(index);
+        if self.code.get(start..).unwrap_or("").starts_with(text) {
+            start + text.len()
+        } else {
+            index
+        }
+    }
+
+    /// Start of the `:` or `=>` before the type starting at `index`.
+    fn annotation_start(&self, index: usize) -> usize {
+        let before = self.text(0, index).trim_end();
+        if before.ends_with("=>") {
+            before.len() - 2
+        } else if before.ends_with(':') {
+            before.len() - 1
+        } else {
+            index
+        }
+    }
+
+    /// Name of an identifier, escapes resolved.
+    fn name(&self, start: usize, end: usize) -> String {
+        let text = self.text(start, end);
+        if text.contains('\\') {
+            cook(text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    // Program
+
+    fn file(&mut self, program: &Program) {
+        let len = self.code.len();
+        self.open("File", 0, len);
+
+        self.key("program");
+        self.open("Program", 0, len);
+        self.string("sourceType", "module");
+        self.null("interpreter");
+        self.nodes("body", program.body, Self::statement);
+        self.raw("directives", "[]");
+        self.close();
+
+        self.list("comments", program.comments, Self::comment);
+        self.close();
+    }
+
+    fn comment(&mut self, comment: &Loc<Comment>) {
+        let text = self.text(comment.start, comment.end);
+        let (ty, value) = match comment.item {
+            Comment::Line => ("CommentLine", text.get(2..).unwrap_or("")),
+            Comment::Block => ("CommentBlock", text.get(2..).map_or("", |text| text.strip_suffix("*/").unwrap_or(text))),
+        };
+
+        self.open(ty, comment.start, comment.end);
+        self.string("value", value);
+        self.close();
+    }
+
+    fn identifier(&mut self, start: usize, end: usize) {
+        let name = self.name(start, end);
+        self.open("Identifier", start, end);
+        self.string("name", &name);
+        self.close();
+    }
+
+    #[inline]
+    fn token_identifier(&mut self, token: &Loc<&str>) {
+        self.identifier(token.start, token.end);
+    }
+
+    /// Module export names and enum members, `a` or `"a"`.
+    fn identifier_or_string(&mut self, token: &Loc<&str>) {
+        if token.starts_with(['"', '\'']) {
+            self.literal(token.start, token.end, Literal::String);
+        } else {
+            self.token_identifier(token);
+        }
+    }
+
+    fn literal(&mut self, start: usize, end: usize, literal: Literal) {
+        let text = self.text(start, end);
+        match literal {
+            Literal::Null => {
+                self.open("NullLiteral", start, end);
+            },
+            Literal::True | Literal::False => {
+                self.open("BooleanLiteral", start, end);
+                self.boolean("value", literal == Literal::True);
+            },
+            Literal::Number => {
+                let value = number_json(number_value(text));
+                self.open("NumericLiteral", start, end);
+                self.raw("value", &value);
+                self.extra_raw(start, end, value);
+            },
+            Literal::BigInt => {
+                let mut value = String::new();
+                write_json_string(&mut value, &text.trim_end_matches('n').replace('_', ""));
+                self.open("BigIntLiteral", start, end);
+                self.raw("value", &value);
+                self.extra_raw(start, end, value);
+            },
+            Literal::String => {
+                let mut value = String::new();
+                write_json_string(&mut value, &cook(text.get(1..text.len().saturating_sub(1)).unwrap_or("")));
+                self.open("StringLiteral", start, end);
+                self.raw("value", &value);
+                self.extra_raw(start, end, value);
+            },
+            Literal::RegExp => {
+                let body_end = text.rfind('/').unwrap_or(0).max(1);
+                self.open("RegExpLiteral", start, end);
+                self.string("pattern", text.get(1..body_end).unwrap_or(""));
+                self.string("flags", text.get(body_end + 1..).unwrap_or(""));
+                let mut raw = String::new();
+                write_json_string(&mut raw, text);
+                self.extra("raw", raw);
+            },
+        }
+        self.close();
+    }
+
+    // Statements
+
+    fn statement(&mut self, stmt: &Loc<Statement>) {
+        let (start, end) = (stmt.start, stmt.end);
+        match stmt.item {
+            Statement::Empty => self.open("EmptyStatement", start, end),
+            Statement::Debugger => self.open("DebuggerStatement", start, end),
+            Statement::Expression(expr) => {
+                self.open("ExpressionStatement", start, end);
+                self.key("expression");
+                self.expression(expr);
+            },
+            Statement::Block(ref block) => return self.block(start, end, block),
+            Statement::Variable(ref declaration) => return self.variable_declaration(start, end, declaration),
+            Statement::Function(ref function) => return self.function("FunctionDeclaration", start, end, function),
+            Statement::Class(ref class) => return self.class("ClassDeclaration", start, end, class),
+            Statement::Return(ref argument) => {
+                self.open("ReturnStatement", start, end);
+                self.option("argument", *argument, Self::expression);
+            },
+            Statement::If(ref stmt) => {
+                self.open("IfStatement", start, end);
+                self.key("test");
+                self.expression(stmt.test);
+                self.key("consequent");
+                self.statement(stmt.consequent);
+                self.option("alternate", stmt.alternate, Self::statement);
+            },
+            Statement::Switch(ref stmt) => {
+                self.open("SwitchStatement", start, end);
+                self.key("discriminant");
+                self.expression(stmt.discriminant);
+                self.list("cases", stmt.cases, |writer, case| {
+                    writer.open("SwitchCase", case.start, case.end);
+                    writer.option("test", case.test, Self::expression);
+                    writer.nodes("consequent", case.consequent, Self::statement);
+                    writer.close();
+                });
+            },
+            Statement::While(ref stmt) => {
+                self.open("WhileStatement", start, end);
+                self.key("test");
+                self.expression(stmt.test);
+                self.key("body");
+                self.statement(stmt.body);
+            },
+            Statement::DoWhile(ref stmt) => {
+                self.open("DoWhileStatement", start, end);
+                self.key("body");
+                self.statement(stmt.body);
+                self.key("test");
+                self.expression(stmt.test);
+            },
+            Statement::For(ref stmt) => {
+                self.open("ForStatement", start, end);
+                self.option("init", stmt.init.as_ref(), |writer, init| match init {
+                    ForInit::Variable(declaration) => {
+                        writer.variable_declaration(declaration.start, declaration.end, declaration);
+                    },
+                    ForInit::Expression(expr) => writer.expression(expr),
+                });
+                self.option("test", stmt.test, Self::expression);
+                self.option("update", stmt.update, Self::expression);
+                self.key("body");
+                self.statement(stmt.body);
+            },
+            Statement::ForIn(ref stmt) => {
+                self.open("ForInStatement", start, end);
+                self.key("left");
+                self.for_head(&stmt.left);
+                self.key("right");
+                self.expression(stmt.right);
+                self.key("body");
+                self.statement(stmt.body);
+            },
+            Statement::ForOf(ref stmt) => {
+                self.open("ForOfStatement", start, end);
+                self.boolean("await", stmt.is_await);
+                self.key("left");
+                self.for_head(&stmt.left);
+                self.key("right");
+                self.expression(stmt.right);
+                self.key("body");
+                self.statement(stmt.body);
+            },
+            Statement::Break(ref label) => {
+                self.open("BreakStatement", start, end);
+                self.option("label", label.as_ref(), Self::token_identifier);
+            },
+            Statement::Continue(ref label) => {
+                self.open("ContinueStatement", start, end);
+                self.option("label", label.as_ref(), Self::token_identifier);
+            },
+            Statement::Throw(argument) => {
+                self.open("ThrowStatement", start, end);
+                self.key("argument");
+                self.expression(argument);
+            },
+            Statement::Try(ref stmt) => {
+                self.open("TryStatement", start, end);
+                self.key("block");
+                self.block(stmt.block.start, stmt.block.end, &stmt.block);
+                self.option("handler", stmt.handler.as_ref(), |writer, handler| {
+                    writer.open("CatchClause", handler.start, handler.end);
+                    writer.option("param", handler.param, Self::pattern);
+                    writer.key("body");
+                    writer.block(handler.body.start, handler.body.end, &handler.body);
+                    writer.close();
+                });
+                self.option("finalizer", stmt.finalizer.as_ref(), |writer, block| {
+                    writer.block(block.start, block.end, block);
+                });
+            },
+            Statement::Labeled(ref stmt) => {
+                self.open("LabeledStatement", start, end);
+                self.key("label");
+                self.token_identifier(&stmt.label);
+                self.key("body");
+                self.statement(stmt.body);
+            },
+            Statement::With(ref stmt) => {
+                self.open("WithStatement", start, end);
+                self.key("object");
+                self.expression(stmt.object);
+                self.key("body");
+                self.statement(stmt.body);
+            },
+            Statement::Import(ref declaration) => {
+                self.open("ImportDeclaration", start, end);
+                self.string("importKind", if declaration.is_type { "type" } else { "value" });
+                self.list("specifiers", declaration.specifiers, |writer, specifier| {
+                    match specifier.item {
+                        ImportSpecifier::Default(ref local) => {
+                            writer.open("ImportDefaultSpecifier", specifier.start, specifier.end);
+                            writer.key("local");
+                            writer.token_identifier(local);
+                        },
+                        ImportSpecifier::Namespace(ref local) => {
+                            writer.open("ImportNamespaceSpecifier", specifier.start, specifier.end);
+                            writer.key("local");
+                            writer.token_identifier(local);
+                        },
+                        ImportSpecifier::Named { is_type, ref imported, ref local } => {
+                            writer.open("ImportSpecifier", specifier.start, specifier.end);
+                            writer.string("importKind", if is_type { "type" } else { "value" });
+                            writer.key("imported");
+                            writer.identifier_or_string(imported);
+                            writer.key("local");
+                            writer.token_identifier(local);
+                        },
+                    }
+                    writer.close();
+                });
+                self.key("source");
+                self.literal(declaration.source.start, declaration.source.end, Literal::String);
+            },
+            Statement::ExportNamed(ref declaration) => {
+                self.open("ExportNamedDeclaration", start, end);
+                self.string("exportKind", if declaration.is_type { "type" } else { "value" });
+                self.option("declaration", declaration.declaration, Self::statement);
+                self.list("specifiers", declaration.specifiers, |writer, specifier| {
+                    writer.open("ExportSpecifier", specifier.start, specifier.end);
+                    writer.string("exportKind", if specifier.is_type { "type" } else { "value" });
+                    writer.key("local");
+                    writer.identifier_or_string(&specifier.local);
+                    writer.key("exported");
+                    writer.identifier_or_string(&specifier.exported);
+                    writer.close();
+                });
+                self.option("source", declaration.source.as_ref(), |writer, source| {
+                    writer.literal(source.start, source.end, Literal::String);
+                });
+            },
+            Statement::ExportDefault(ref declaration) => {
+                self.open("ExportDefaultDeclaration", start, end);
+                self.key("declaration");
+                // export default function () {}
+                let declaration_start = self.skip_trivia(self.after(self.after(start, "export"), "default"));
+                match *declaration {
+                    ExportDefaultDeclaration::Function(ref function) => {
+                        self.function("FunctionDeclaration", declaration_start, end, function);
+                    },
+                    ExportDefaultDeclaration::Class(ref class) => {
+                        self.class("ClassDeclaration", declaration_start, end, class);
+                    },
+                    ExportDefaultDeclaration::Expression(expr) => self.expression(expr),
+                    ExportDefaultDeclaration::TsDeclareFunction(ref function) => {
+                        self.ts_declare_function(declaration_start, end, function);
+                    },
+                    ExportDefaultDeclaration::TsInterface(ref interface) => {
+                        self.ts_interface(declaration_start, end, interface);
+                    },
+                }
+            },
+            Statement::ExportAll(ref declaration) => {
+                match declaration.exported {
+                    // export * as name from "source"
+                    Some(ref exported) => {
+                        self.open("ExportNamedDeclaration", start, end);
+                        self.null("declaration");
+                        let star = self.text(start, end).find('*').map_or(start, |index| start + index);
+                        self.key("specifiers");
+                        self.output.push('[');
+                        self.open("ExportNamespaceSpecifier", star, exported.end);
+                        self.key("exported");
+                        self.identifier_or_string(exported);
+                        self.close();
+                        self.output.push(']');
+                    },
+                    None => self.open("ExportAllDeclaration", start, end),
+                }
+                self.string("exportKind", if declaration.is_type { "type" } else { "value" });
+                self.key("source");
+                self.literal(declaration.source.start, declaration.source.end, Literal::String);
+            },
+            Statement::TsInterface(ref interface) => return self.ts_interface(start, end, interface),
+            Statement::TsTypeAlias(ref alias) => {
+                self.open("TSTypeAliasDeclaration", start, end);
+                self.key("id");
+                self.token_identifier(&alias.id);
+                self.option("typeParameters", alias.type_params, Self::ts_type_parameters);
+                self.key("typeAnnotation");
+                self.ts_type(&alias.ty);
+            },
+            Statement::TsEnum(ref declaration) => {
+                self.open("TSEnumDeclaration", start, end);
+                self.boolean("const", declaration.is_const);
+                self.key("id");
+                self.token_identifier(&declaration.id);
+                self.list("members", declaration.members, |writer, member| {
+                    writer.open("TSEnumMember", member.start, member.end);
+                    writer.key("id");
+                    writer.identifier_or_string(&member.id);
+                    if let Some(init) = member.init {
+                        writer.key("initializer");
+                        writer.expression(init);
+                    }
+                    writer.close();
+                });
+            },
+            Statement::TsModule(ref module) => return self.ts_module(start, end, module),
+            Statement::TsDeclareFunction(ref function) => return self.ts_declare_function(start, end, function),
+            Statement::TsDeclare(declaration) => {
+                self.pending.push(("declare", "true".to_string()));
+                return self.statement(declaration);
+            },
+            Statement::Error => self.open("Error", start, end),
+        }
+        self.close();
+    }
+
+    fn block(&mut self, start: usize, end: usize, block: &BlockStatement) {
+        self.open("BlockStatement", start, end);
+        self.nodes("body", block.body, Self::statement);
+        self.raw("directives", "[]");
+        self.close();
+    }
+
+    fn variable_declaration(&mut self, start: usize, end: usize, declaration: &VariableDeclaration) {
+        self.open("VariableDeclaration", start, end);
+        self.string("kind", match declaration.kind {
+            VariableKind::Var => "var",
+            VariableKind::Let => "let",
+            VariableKind::Const => "const",
+        });
+        self.list("declarations", declaration.declarations, |writer, declarator| {
+            writer.open("VariableDeclarator", declarator.start, declarator.end);
+            writer.key("id");
+            writer.pattern(declarator.id);
+            writer.option("init", declarator.init, Self::expression);
+            writer.close();
+        });
+        self.close();
+    }
+
+    fn for_head(&mut self, head: &ForHead) {
+        match head {
+            ForHead::Variable(declaration) => {
+                self.variable_declaration(declaration.start, declaration.end, declaration);
+            },
+            ForHead::Pattern(pattern) => self.pattern(pattern),
+        }
+    }
+
+    // Functions and classes
+
+    fn function(&mut self, ty: &str, start: usize, end: usize, function: &Function) {
+        self.open(ty, start, end);
+        self.option("id", function.id.as_ref(), Self::token_identifier);
+        self.function_fields(function);
+        self.close();
+    }
+
+    /// Everything but the `id`, class and object methods have none.
+    fn function_fields(&mut self, function: &Function) {
+        self.boolean("generator", function.is_generator);
+        self.boolean("async", function.is_async);
+        if let Some(type_params) = function.type_params {
+            self.key("typeParameters");
+            self.ts_type_parameters(type_params);
+        }
+        self.nodes("params", function.params, Self::pattern);
+        if let Some(return_type) = function.return_type {
+            self.return_type(return_type);
+        }
+        self.key("body");
+        self.block(function.body.start, function.body.end, &function.body);
+    }
+
+    fn class(&mut self, ty: &str, start: usize, end: usize, class: &Class) {
+        self.open(ty, start, end);
+        self.option("id", class.id.as_ref(), Self::token_identifier);
+        self.option("superClass", class.super_class, Self::expression);
+        if let Some(type_params) = class.type_params {
+            self.key("typeParameters");
+            self.ts_type_parameters(type_params);
+        }
+        if let Some(type_args) = class.super_type_args {
+            self.key("superTypeParameters");
+            self.ts_type_arguments(type_args);
+        }
+        if let Some(ref implements) = class.implements {
+            self.nodes("implements", implements.item, Self::ts_expression_with_type_arguments);
+        }
+        if class.modifiers.iter().any(|modifier| modifier.item == TsModifier::Abstract) {
+            self.boolean("abstract", true);
+        }
+
+        // class A extends B {
+        let header_end = [
+            class.id.as_ref().map(|id| id.end),
+            class.type_params.as_ref().map(|params| params.end),
+            class.super_class.as_ref().map(|super_class| super_class.end),
+            class.super_type_args.as_ref().map(|args| args.end),
+            class.implements.as_ref().map(|implements| implements.end),
+        ].iter().flatten().max().cloned();
+        let header_end = header_end.unwrap_or_else(|| {
+            self.text(start, end).find("class").map_or(start, |index| start + index + 5)
+        });
+        let body_start = self.skip_trivia(header_end);
+
+        self.key("body");
+        self.open("ClassBody", body_start, end);
+        self.nodes("body", class.body, Self::class_member);
+        self.close();
+
+        self.close();
+    }
+
+    fn class_member(&mut self, member: &Loc<ClassMember>) {
+        let (start, end) = (member.start, member.end);
+        match member.item {
+            ClassMember::Method { ref modifiers, is_static, kind, ref key, ref optional, ref value } => {
+                let ty = if matches!(key, PropertyKey::PrivateName(_)) { "ClassPrivateMethod" } else { "ClassMethod" };
+                self.open(ty, start, end);
+                self.string("kind", method_kind(kind));
+                self.property_key(key);
+                self.boolean("static", is_static);
+                self.ts_modifiers(modifiers);
+                if optional.is_some() {
+                    self.boolean("optional", true);
+                }
+                self.null("id");
+                self.function_fields(value);
+            },
+            ClassMember::Property { ref modifiers, is_static, ref key, ref annotation, ref value } => {
+                let ty = if matches!(key, PropertyKey::PrivateName(_)) { "ClassPrivateProperty" } else { "ClassProperty" };
+                self.open(ty, start, end);
+                self.property_key(key);
+                self.boolean("static", is_static);
+                self.ts_modifiers(modifiers);
+                if let Some(ref annotation) = annotation {
+                    self.type_annotation_fields(annotation);
+                }
+                self.option("value", *value, Self::expression);
+            },
+            ClassMember::StaticBlock(ref block) => {
+                self.open("StaticBlock", start, end);
+                self.nodes("body", block.body, Self::statement);
+            },
+            ClassMember::TsMethodSignature { ref modifiers, is_static, kind, ref key, ref optional, ref value } => {
+                self.open("TSDeclareMethod", start, end);
+                self.string("kind", method_kind(kind));
+                self.property_key(key);
+                self.boolean("static", is_static);
+                self.ts_modifiers(modifiers);
+                if optional.is_some() {
+                    self.boolean("optional", true);
+                }
+                self.null("id");
+                self.boolean("generator", value.is_generator);
+                self.boolean("async", value.is_async);
+                if let Some(type_params) = value.type_params {
+                    self.key("typeParameters");
+                    self.ts_type_parameters(type_params);
+                }
+                self.nodes("params", value.params, Self::pattern);
+                if let Some(return_type) = value.return_type {
+                    self.return_type(return_type);
+                }
+            },
+            ClassMember::TsIndexSignature(ref signature) => return self.ts_index_signature(start, end, signature),
+        }
+        self.close();
+    }
+
+    /// `key` and `computed` of a property or a member.
+    fn property_key(&mut self, key: &PropertyKey) {
+        self.key("key");
+        match key {
+            PropertyKey::Identifier(token) => self.token_identifier(token),
+            PropertyKey::PrivateName(token) => {
+                self.open("PrivateName", token.start, token.end);
+                self.key("id");
+                self.identifier(token.start + 1, token.end);
+                self.close();
+            },
+            PropertyKey::Literal(literal) => self.literal(literal.start, literal.end, literal.item),
+            PropertyKey::Computed(expr) => self.expression(expr),
+        }
+        self.boolean("computed", matches!(key, PropertyKey::Computed(_)));
+    }
+
+    // Expressions
+
+    fn expression(&mut self, expr: &Loc<Expression>) {
+        let (start, end) = (expr.start, expr.end);
+        match expr.item {
+            Expression::This => self.open("ThisExpression", start, end),
+            Expression::Super => self.open("Super", start, end),
+            Expression::Identifier(_) => return self.identifier(start, end),
+            Expression::Literal(literal) => return self.literal(start, end, literal),
+            Expression::Template(ref template) => return self.template(start, end, template),
+            Expression::TaggedTemplate(ref tagged) => {
+                self.open("TaggedTemplateExpression", start, end);
+                self.key("tag");
+                self.expression(tagged.tag);
+                if let Some(type_args) = tagged.type_args {
+                    self.key("typeParameters");
+                    self.ts_type_arguments(type_args);
+                }
+                self.key("quasi");
+                self.template(tagged.quasi.start, tagged.quasi.end, &tagged.quasi);
+            },
+            Expression::Array(ref array) => {
+                self.open("ArrayExpression", start, end);
+                self.list("elements", array.elements, |writer, element| match element {
+                    Some(element) => writer.expression(element),
+                    None => writer.output.push_str("null"),
+                });
+            },
+            Expression::Object(ref object) => {
+                self.open("ObjectExpression", start, end);
+                self.nodes("properties", object.properties, Self::property);
+            },
+            Expression::Function(ref function) => return self.function("FunctionExpression", start, end, function),
+            Expression::Class(ref class) => return self.class("ClassExpression", start, end, class),
+            Expression::Arrow(ref arrow) => {
+                self.open("ArrowFunctionExpression", start, end);
+                self.null("id");
+                self.boolean("generator", false);
+                self.boolean("async", arrow.is_async);
+                if let Some(type_params) = arrow.type_params {
+                    self.key("typeParameters");
+                    self.ts_type_parameters(type_params);
+                }
+                self.nodes("params", arrow.params, Self::pattern);
+                if let Some(return_type) = arrow.return_type {
+                    self.return_type(return_type);
+                }
+                self.boolean("expression", matches!(arrow.body, ArrowBody::Expression(_)));
+                self.key("body");
+                match arrow.body {
+                    ArrowBody::Expression(expr) => self.expression(expr),
+                    ArrowBody::Block(ref block) => self.block(block.start, block.end, block),
+                }
+            },
+            Expression::Parenthesized(inner) => {
+                if self.paren_start.is_none() {
+                    self.paren_start = Some(start);
+                }
+                return self.expression(inner);
+            },
+            Expression::Unary(ref unary) => {
+                self.open("UnaryExpression", start, end);
+                self.string("operator", unary.operator.as_str());
+                self.boolean("prefix", true);
+                self.key("argument");
+                self.expression(unary.argument);
+            },
+            Expression::Update(ref update) => {
+                self.open("UpdateExpression", start, end);
+                self.string("operator", update.operator.as_str());
+                self.boolean("prefix", update.prefix);
+                self.key("argument");
+                self.expression(update.argument);
+            },
+            Expression::Binary(ref binary) => {
+                let is_logical = matches!(binary.operator, BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Nullish);
+                self.open(if is_logical { "LogicalExpression" } else { "BinaryExpression" }, start, end);
+                self.key("left");
+                self.expression(binary.left);
+                self.string("operator", binary.operator.as_str());
+                self.key("right");
+                self.expression(binary.right);
+            },
+            Expression::Conditional(ref conditional) => {
+                self.open("ConditionalExpression", start, end);
+                self.key("test");
+                self.expression(conditional.test);
+                self.key("consequent");
+                self.expression(conditional.consequent);
+                self.key("alternate");
+                self.expression(conditional.alternate);
+            },
+            Expression::Assignment(ref assignment) => {
+                self.open("AssignmentExpression", start, end);
+                self.string("operator", assignment.operator.as_str());
+                self.key("left");
+                self.pattern(assignment.left);
+                self.key("right");
+                self.expression(assignment.right);
+            },
+            Expression::Sequence(expressions) => {
+                self.open("SequenceExpression", start, end);
+                self.nodes("expressions", expressions, Self::expression);
+            },
+            Expression::Call(ref call) => {
+                let is_optional = is_optional_chain(&expr.item);
+                self.open(if is_optional { "OptionalCallExpression" } else { "CallExpression" }, start, end);
+                self.key("callee");
+                self.expression(call.callee);
+                if let Some(type_args) = call.type_args {
+                    self.key("typeParameters");
+                    self.ts_type_arguments(type_args);
+                }
+                self.nodes("arguments", call.arguments, Self::expression);
+                if is_optional {
+                    self.boolean("optional", call.optional);
+                }
+            },
+            Expression::New(ref new) => {
+                self.open("NewExpression", start, end);
+                self.key("callee");
+                self.expression(new.callee);
+                if let Some(type_args) = new.type_args {
+                    self.key("typeParameters");
+                    self.ts_type_arguments(type_args);
+                }
+                self.nodes("arguments", new.arguments, Self::expression);
+            },
+            Expression::StaticMember(ref member) => {
+                let is_optional = is_optional_chain(&expr.item);
+                self.open(if is_optional { "OptionalMemberExpression" } else { "MemberExpression" }, start, end);
+                self.key("object");
+                self.expression(member.object);
+                self.key("property");
+                if member.property.starts_with('#') {
+                    self.open("PrivateName", member.property.start, member.property.end);
+                    self.key("id");
+                    self.identifier(member.property.start + 1, member.property.end);
+                    self.close();
+                } else {
+                    self.token_identifier(&member.property);
+                }
+                self.boolean("computed", false);
+                if is_optional {
+                    self.boolean("optional", member.optional);
+                }
+            },
+            Expression::ComputedMember(ref member) => {
+                let is_optional = is_optional_chain(&expr.item);
+                self.open(if is_optional { "OptionalMemberExpression" } else { "MemberExpression" }, start, end);
+                self.key("object");
+                self.expression(member.object);
+                self.key("property");
+                self.expression(member.property);
+                self.boolean("computed", true);
+                if is_optional {
+                    self.boolean("optional", member.optional);
+                }
+            },
+            Expression::Spread(argument) => {
+                self.open("SpreadElement", start, end);
+                self.key("argument");
+                self.expression(argument);
+            },
+            Expression::Await(argument) => {
+                self.open("AwaitExpression", start, end);
+                self.key("argument");
+                self.expression(argument);
+            },
+            Expression::Yield(ref expr) => {
+                self.open("YieldExpression", start, end);
+                self.boolean("delegate", expr.delegate);
+                self.option("argument", expr.argument, Self::expression);
+            },
+            Expression::MetaProperty(ref meta) => {
+                self.open("MetaProperty", start, end);
+                self.key("meta");
+                self.token_identifier(&meta.meta);
+                self.key("property");
+                self.token_identifier(&meta.property);
+            },
+            // import(source)
+            Expression::Import(source) => {
+                self.open("CallExpression", start, end);
+                self.key("callee");
+                self.open("Import", start, start + "import".len());
+                self.close();
+                self.key("arguments");
+                self.output.push('[');
+                self.expression(source);
+                self.output.push(']');
+            },
+            Expression::Element(elem) => return self.jsx_element(start, end, elem),
+            Expression::Fragment(fragment) => return self.jsx_fragment(start, end, fragment),
+            Expression::TsAs(ref as_expr) | Expression::TsSatisfies(ref as_expr) => {
+                let is_as = matches!(expr.item, Expression::TsAs(_));
+                self.open(if is_as { "TSAsExpression" } else { "TSSatisfiesExpression" }, start, end);
+                self.key("expression");
+                self.expression(as_expr.expression);
+                self.key("typeAnnotation");
+                self.ts_type(as_expr.ty);
+            },
+            Expression::TsNonNull(inner) => {
+                self.open("TSNonNullExpression", start, end);
+                self.key("expression");
+                self.expression(inner);
+            },
+        }
+        self.close();
+    }
+
+    fn property(&mut self, property: &Loc<Property>) {
+        let (start, end) = (property.start, property.end);
+        match property.item {
+            Property::Init { ref key, value } => {
+                self.open("ObjectProperty", start, end);
+                self.boolean("method", false);
+                self.property_key(key);
+                self.boolean("shorthand", false);
+                self.key("value");
+                self.expression(value);
+            },
+            Property::Shorthand(ref name) => {
+                self.open("ObjectProperty", start, end);
+                self.boolean("method", false);
+                self.key("key");
+                self.token_identifier(name);
+                self.boolean("computed", false);
+                self.boolean("shorthand", true);
+                self.key("value");
+                self.token_identifier(name);
+            },
+            Property::Method { kind, ref key, ref value } => {
+                self.open("ObjectMethod", start, end);
+                self.string("kind", method_kind(kind));
+                self.boolean("method", kind == MethodKind::Method);
+                self.property_key(key);
+                self.null("id");
+                self.function_fields(value);
+            },
+            Property::Spread(argument) => {
+                self.open("SpreadElement", start, end);
+                self.key("argument");
+                self.expression(argument);
+            },
+        }
+        self.close();
+    }
+
+    fn template(&mut self, start: usize, end: usize, template: &TemplateLiteral) {
+        self.open("TemplateLiteral", start, end);
+        self.nodes("expressions", template.expressions, Self::expression);
+        self.list("quasis", template.quasis, Self::template_element);
+        self.close();
+    }
+
+    fn template_element(&mut self, quasi: &Loc<TemplateElement>) {
+        let raw = self.text(quasi.start, quasi.end);
+        self.open("TemplateElement", quasi.start, quasi.end);
+        self.key("value");
+        self.output.push_str("{\"raw\":");
+        write_json_string(&mut self.output, &raw.replace("\r\n", "\n").replace('\r', "\n"));
+        self.output.push_str(",\"cooked\":");
+        write_json_string(&mut self.output, &cook(raw));
+        self.output.push('}');
+        self.boolean("tail", quasi.tail);
+        self.close();
+    }
+
+    // Patterns
+
+    #[inline]
+    fn pattern(&mut self, pattern: &Loc<Pattern>) {
+        self.typed_pattern(pattern, None);
+    }
+
+    /// `pattern`, with the `typeAnnotation` of a `TypedPattern` around it.
+    fn typed_pattern(&mut self, pattern: &Loc<Pattern>, annotation: Option<&Loc<TsTypeAnnotation>>) {
+        let start = pattern.start;
+        let end = annotation.map_or(pattern.end, |annotation| annotation.end.max(pattern.end));
+        match pattern.item {
+            Pattern::Typed(ref typed) => return self.typed_pattern(typed.pattern, Some(&typed.annotation)),
+            Pattern::Expression(expr) if annotation.is_none() => return self.expression(expr),
+            // this: Window
+            Pattern::Identifier(_) | Pattern::Expression(_) => {
+                let name = self.name(pattern.start, pattern.end);
+                self.open("Identifier", start, end);
+                self.string("name", &name);
+            },
+            Pattern::Object(ref object) => {
+                self.open("ObjectPattern", start, end);
+                self.nodes("properties", object.properties, Self::pattern_property);
+            },
+            Pattern::Array(ref array) => {
+                self.open("ArrayPattern", start, end);
+                self.list("elements", array.elements, |writer, element| match element {
+                    Some(element) => writer.pattern(element),
+                    None => writer.output.push_str("null"),
+                });
+            },
+            Pattern::Assignment(ref assignment) => {
+                self.open("AssignmentPattern", start, end);
+                self.key("left");
+                self.pattern(assignment.left);
+                self.key("right");
+                self.expression(assignment.right);
+            },
+            Pattern::Rest(argument) => {
+                self.open("RestElement", start, end);
+                self.key("argument");
+                self.pattern(argument);
+            },
+            Pattern::TsParameterProperty(ref property) => {
+                self.open("TSParameterProperty", start, end);
+                self.ts_modifiers(&property.modifiers);
+                self.key("parameter");
+                self.pattern(property.parameter);
+            },
+        }
+        if let Some(annotation) = annotation {
+            self.type_annotation_fields(annotation);
+        }
+        self.close();
+    }
+
+    fn pattern_property(&mut self, property: &Loc<PatternProperty>) {
+        let (start, end) = (property.start, property.end);
+        match property.item {
+            PatternProperty::Property { ref key, value } => {
+                self.open("ObjectProperty", start, end);
+                self.boolean("method", false);
+                self.property_key(key);
+                self.boolean("shorthand", false);
+                self.key("value");
+                self.pattern(value);
+            },
+            // name, name = default
+            PatternProperty::Shorthand { ref name, ref init } => {
+                self.open("ObjectProperty", start, end);
+                self.boolean("method", false);
+                self.key("key");
+                self.token_identifier(name);
+                self.boolean("computed", false);
+                self.boolean("shorthand", true);
+                self.key("value");
+                match init {
+                    Some(init) => {
+                        self.open("AssignmentPattern", start, end);
+                        self.key("left");
+                        self.token_identifier(name);
+                        self.key("right");
+                        self.expression(init);
+                        self.close();
+                    },
+                    None => self.token_identifier(name),
+                }
+            },
+            PatternProperty::Rest(argument) => {
+                self.open("RestElement", start, end);
+                self.key("argument");
+                self.pattern(argument);
+            },
+        }
+        self.close();
+    }
+
+    // JSX
+
+    fn jsx_element(&mut self, start: usize, end: usize, elem: &ElementExpression) {
+        // The attributes end the opening element.
+        let mut cursor = elem.type_args.as_ref().map_or(element_name_end(&elem.name), |args| args.end);
+        let mut attr_spans = Vec::new();
+        for attr in elem.attrs.iter() {
+            let span = self.jsx_attribute_span(attr, cursor);
+            attr_spans.push(span);
+            cursor = span.1;
+        }
+        let opening_end = if elem.is_self_closing { end } else { self.after(cursor, ">") };
+
+        self.open("JSXElement", start, end);
+
+        self.key("openingElement");
+        self.open("JSXOpeningElement", start, opening_end);
+        self.key("name");
+        self.jsx_element_name(&elem.name, 0);
+        if let Some(type_args) = elem.type_args {
+            self.key("typeParameters");
+            self.ts_type_arguments(type_args);
+        }
+        self.key("attributes");
+        self.output.push('[');
+        for (idx, (attr, span)) in elem.attrs.iter().zip(attr_spans).enumerate() {
+            if idx > 0 {
+                self.output.push(',');
+            }
+            self.jsx_attribute(attr, span);
+        }
+        self.output.push(']');
+        self.boolean("selfClosing", elem.is_self_closing);
+        self.close();
+
+        let children = elem.children.unwrap_or_else(List::empty);
+        let cursor = self.jsx_children(children, opening_end);
+
+        self.key("closingElement");
+        if !elem.is_self_closing && self.text(cursor, end).starts_with("</") {
+            let name_start = self.skip_trivia(cursor + 2);
+            let shift = name_start.saturating_sub(element_name_start(&elem.name));
+            self.open("JSXClosingElement", cursor, end);
+            self.key("name");
+            self.jsx_element_name(&elem.name, shift);
+            self.close();
+        } else {
+            self.output.push_str("null");
+        }
+
+        self.close();
+    }
+
+    fn jsx_fragment(&mut self, start: usize, end: usize, fragment: &FragmentExpression) {
+        let opening_end = self.after(start + 1, ">");
+
+        self.open("JSXFragment", start, end);
+        self.key("openingFragment");
+        self.open("JSXOpeningFragment", start, opening_end);
+        self.close();
+
+        let cursor = self.jsx_children(fragment.children, opening_end);
+
+        self.key("closingFragment");
+        self.open("JSXClosingFragment", cursor, end);
+        self.close();
+        self.close();
+    }
+
+    /// Writes the `children` starting at `cursor`, returns where they end.
+    fn jsx_children(&mut self, children: List<Child>, mut cursor: usize) -> usize {
+        self.key("children");
+        self.output.push('[');
+        for (idx, child) in children.iter().enumerate() {
+            if idx > 0 {
+                self.output.push(',');
+            }
+            cursor = self.jsx_child(child, cursor);
+        }
+        self.output.push(']');
+
+        cursor
+    }
+
+    fn jsx_child(&mut self, child: &Child, cursor: usize) -> usize {
+        match child {
+            Child::Text(text) => {
+                let raw = self.text(text.start, text.end);
+                let mut value = String::new();
+                write_json_string(&mut value, &decode_entities(raw));
+                self.open("JSXText", text.start, text.end);
+                self.raw("value", &value);
+                self.extra_raw(text.start, text.end, value);
+                self.close();
+                text.end
+            },
+            Child::Element(elem) => {
+                self.jsx_element(elem.start, elem.end, elem);
+                elem.end
+            },
+            Child::Fragment(fragment) => {
+                self.jsx_fragment(fragment.start, fragment.end, fragment);
+                fragment.end
+            },
+            Child::ChildExpression(expr) | Child::Spread(expr) => {
+                // { expr }, { ...expr }
+                let start = self.text(cursor, expr.start).rfind('{').map_or(cursor, |index| cursor + index);
+                let end = self.after(expr.end, "}");
+                self.open(if matches!(child, Child::Spread(_)) { "JSXSpreadChild" } else { "JSXExpressionContainer" }, start, end);
+                self.key("expression");
+                self.expression(expr);
+                self.close();
+                end
+            },
+            Child::EmptyExpression(empty) => {
+                // The span is inside the braces.
+                let (start, end) = (empty.start.saturating_sub(1), empty.end + 1);
+                self.open("JSXExpressionContainer", start, end);
+                self.key("expression");
+                self.open("JSXEmptyExpression", empty.start, empty.end);
+                self.close();
+                self.close();
+                end
+            },
+            Child::Error(error) => {
+                self.open("Error", error.start, error.end);
+                self.close();
+                error.end
+            },
+        }
+    }
+
+    /// Span of `attr`, the previous attribute or the name ends at `cursor`.
+    fn jsx_attribute_span(&self, attr: &Attribute, cursor: usize) -> (usize, usize) {
+        match attr {
+            Attribute::Spread(expr) => {
+                let start = self.text(cursor, expr.start).rfind('{').map_or(cursor, |index| cursor + index);
+                (start, self.after(expr.end, "}"))
+            },
+            Attribute::Normal(normal) => {
+                let (start, name_end) = attribute_name_span(&normal.name);
+                let end = match normal.init {
+                    None => name_end,
+                    Some(NormalAttributeInitializer::LiteralString(ref token)) => token.end,
+                    Some(NormalAttributeInitializer::AssignmentExpression(expr)) => self.after(expr.end, "}"),
+                    Some(NormalAttributeInitializer::ElementExpression(elem)) => elem.end,
+                    Some(NormalAttributeInitializer::FragmentExpression(fragment)) => fragment.end,
+                };
+                (start, end)
+            },
+        }
+    }
+
+    fn jsx_attribute(&mut self, attr: &Attribute, (start, end): (usize, usize)) {
+        match attr {
+            Attribute::Spread(expr) => {
+                self.open("JSXSpreadAttribute", start, end);
+                self.key("argument");
+                self.expression(expr);
+            },
+            Attribute::Normal(normal) => {
+                self.open("JSXAttribute", start, end);
+                self.key("name");
+                match normal.name {
+                    NormalAttributeName::Identifier(ref token) => self.jsx_identifier(token, 0),
+                    NormalAttributeName::NamespacedName(ref name) => {
+                        self.open("JSXNamespacedName", name.ns.start, name.name.end);
+                        self.key("namespace");
+                        self.jsx_identifier(&name.ns, 0);
+                        self.key("name");
+                        self.jsx_identifier(&name.name, 0);
+                        self.close();
+                    },
+                }
+
+                self.key("value");
+                match normal.init {
+                    None => self.output.push_str("null"),
+                    Some(ref init @ NormalAttributeInitializer::LiteralString(ref token)) => {
+                        let mut value = String::new();
+                        write_json_string(&mut value, &init.cooked_string().unwrap_or_default());
+                        self.open("StringLiteral", token.start, token.end);
+                        self.raw("value", &value);
+                        self.extra_raw(token.start, token.end, value);
+                        self.close();
+                    },
+                    Some(NormalAttributeInitializer::AssignmentExpression(expr)) => {
+                        let name_end = attribute_name_span(&normal.name).1;
+                        let container_start = self.skip_trivia(self.after(name_end, "="));
+                        self.open("JSXExpressionContainer", container_start, end);
+                        self.key("expression");
+                        self.expression(expr);
+                        self.close();
+                    },
+                    Some(NormalAttributeInitializer::ElementExpression(elem)) => {
+                        self.jsx_element(elem.start, elem.end, elem);
+                    },
+                    Some(NormalAttributeInitializer::FragmentExpression(fragment)) => {
+                        self.jsx_fragment(fragment.start, fragment.end, fragment);
+                    },
+                }
+            },
+        }
+        self.close();
+    }
+
+    /// The name of an opening element, of the closing element `shift` bytes later.
+    fn jsx_element_name(&mut self, name: &ElementName, shift: usize) {
+        match name {
+            ElementName::Identifier(token) => self.jsx_identifier(token, shift),
+            ElementName::NamespacedName(name) => {
+                self.open("JSXNamespacedName", name.ns.start + shift, name.name.end + shift);
+                self.key("namespace");
+                self.jsx_identifier(&name.ns, shift);
+                self.key("name");
+                self.jsx_identifier(&name.name, shift);
+                self.close();
+            },
+            ElementName::MemberExpression(member) => {
+                let members: Vec<_> = member.members.iter().copied().collect();
+                self.jsx_member_expression(&members, shift);
+            },
+        }
+    }
+
+    // a.b.c is ((a.b).c)
+    fn jsx_member_expression(&mut self, members: &[Loc<&str>], shift: usize) {
+        match members.split_last() {
+            Some((property, object)) if !object.is_empty() => {
+                self.open("JSXMemberExpression", object[0].start + shift, property.end + shift);
+                self.key("object");
+                self.jsx_member_expression(object, shift);
+                self.key("property");
+                self.jsx_identifier(property, shift);
+                self.close();
+            },
+            Some((name, _)) => self.jsx_identifier(name, shift),
+            None => self.output.push_str("null"),
+        }
+    }
+
+    fn jsx_identifier(&mut self, token: &Loc<&str>, shift: usize) {
+        let name = self.text(token.start, token.end);
+        self.open("JSXIdentifier", token.start + shift, token.end + shift);
+        self.string("name", name);
+        self.close();
+    }
+
+    // TypeScript
+
+    fn ts_modifiers(&mut self, modifiers: &List<Loc<TsModifier>>) {
+        for modifier in modifiers {
+            match modifier.item {
+                TsModifier::Public => self.string("accessibility", "public"),
+                TsModifier::Private => self.string("accessibility", "private"),
+                TsModifier::Protected => self.string("accessibility", "protected"),
+                TsModifier::Readonly => self.boolean("readonly", true),
+                TsModifier::Abstract => self.boolean("abstract", true),
+                TsModifier::Override => self.boolean("override", true),
+                TsModifier::Declare => self.boolean("declare", true),
+            }
+        }
+    }
+
+    /// `optional`, `definite` and `typeAnnotation` of a binding or a property.
+    fn type_annotation_fields(&mut self, annotation: &Loc<TsTypeAnnotation>) {
+        if annotation.optional {
+            self.boolean("optional", true);
+        }
+        if annotation.definite {
+            self.boolean("definite", true);
+        }
+        if let Some(ty) = annotation.ty {
+            self.key("typeAnnotation");
+            self.ts_type_annotation(ty);
+        }
+    }
+
+    fn return_type(&mut self, return_type: &Loc<TsTypeAnnotation>) {
+        if let Some(ty) = return_type.ty {
+            self.key("returnType");
+            self.ts_type_annotation(ty);
+        }
+    }
+
+    /// `TSTypeAnnotation` spanning `: Type`.
+    fn ts_type_annotation(&mut self, ty: &Loc<TsType>) {
+        let start = self.annotation_start(ty.start);
+        self.open("TSTypeAnnotation", start, ty.end);
+        self.key("typeAnnotation");
+        self.ts_type(ty);
+        self.close();
+    }
+
+    fn ts_type_parameters(&mut self, params: &Loc<TsTypeParameters>) {
+        self.open("TSTypeParameterDeclaration", params.start, params.end);
+        self.list("params", params.params, |writer, param| {
+            writer.open("TSTypeParameter", param.start, param.end);
+            let name = writer.text(param.name.start, param.name.end);
+            writer.string("name", name);
+            for modifier in param.modifiers.iter() {
+                let modifier = writer.text(modifier.start, modifier.end);
+                if let "in" | "out" | "const" = modifier {
+                    writer.boolean(modifier, true);
+                }
+            }
+            if let Some(ref constraint) = param.constraint {
+                writer.key("constraint");
+                writer.ts_type(constraint);
+            }
+            if let Some(ref default) = param.default {
+                writer.key("default");
+                writer.ts_type(default);
+            }
+            writer.close();
+        });
+        self.close();
+    }
+
+    fn ts_type_arguments(&mut self, args: &Loc<TsTypeArguments>) {
+        self.open("TSTypeParameterInstantiation", args.start, args.end);
+        self.nodes("params", args.params, Self::ts_type);
+        self.close();
+    }
+
+    /// `A.B.C`, an `Identifier` or nested `TSQualifiedName`s.
+    fn ts_entity_name(&mut self, names: List<Loc<&str>>) {
+        let names: Vec<_> = names.iter().copied().collect();
+        self.ts_qualified_name(&names);
+    }
+
+    fn ts_qualified_name(&mut self, names: &[Loc<&str>]) {
+        match names.split_last() {
+            Some((right, left)) if !left.is_empty() => {
+                self.open("TSQualifiedName", left[0].start, right.end);
+                self.key("left");
+                self.ts_qualified_name(left);
+                self.key("right");
+                self.token_identifier(right);
+                self.close();
+            },
+            Some((name, _)) => self.token_identifier(name),
+            None => self.output.push_str("null"),
+        }
+    }
+
+    /// `extends A<T>` of interfaces, `implements A<T>` of classes.
+    fn ts_expression_with_type_arguments(&mut self, ty: &Loc<TsType>) {
+        match ty.item {
+            TsType::Reference(ref reference) => {
+                self.open("TSExpressionWithTypeArguments", ty.start, ty.end);
+                self.key("expression");
+                self.ts_entity_name(reference.name);
+                self.option("typeParameters", reference.type_args.as_ref(), Self::ts_type_arguments);
+                self.close();
+            },
+            _ => self.ts_type(ty),
+        }
+    }
+
+    fn ts_type(&mut self, ty: &Loc<TsType>) {
+        let (start, end) = (ty.start, ty.end);
+        match ty.item {
+            TsType::Keyword => {
+                let keyword = match self.text(start, end) {
+                    "any" => "TSAnyKeyword",
+                    "unknown" => "TSUnknownKeyword",
+                    "string" => "TSStringKeyword",
+                    "number" => "TSNumberKeyword",
+                    "boolean" => "TSBooleanKeyword",
+                    "bigint" => "TSBigIntKeyword",
+                    "symbol" => "TSSymbolKeyword",
+                    "object" => "TSObjectKeyword",
+                    "void" => "TSVoidKeyword",
+                    "undefined" => "TSUndefinedKeyword",
+                    "null" => "TSNullKeyword",
+                    "never" => "TSNeverKeyword",
+                    "intrinsic" => "TSIntrinsicKeyword",
+                    "this" => "TSThisType",
+                    _ => "TSTypeReference",
+                };
+                self.open(keyword, start, end);
+                if keyword == "TSTypeReference" {
+                    self.key("typeName");
+                    self.identifier(start, end);
+                }
+            },
+            TsType::Reference(ref reference) => {
+                self.open("TSTypeReference", start, end);
+                self.key("typeName");
+                self.ts_entity_name(reference.name);
+                if let Some(ref type_args) = reference.type_args {
+                    self.key("typeParameters");
+                    self.ts_type_arguments(type_args);
+                }
+            },
+            TsType::Literal(Literal::Null) => self.open("TSNullKeyword", start, end),
+            TsType::Literal(literal) => {
+                self.open("TSLiteralType", start, end);
+                self.key("literal");
+                // -1
+                if let Some(rest) = self.text(start, end).strip_prefix('-') {
+                    let argument_start = end - rest.trim_start().len();
+                    self.open("UnaryExpression", start, end);
+                    self.string("operator", "-");
+                    self.boolean("prefix", true);
+                    self.key("argument");
+                    self.literal(argument_start, end, literal);
+                    self.close();
+                } else {
+                    self.literal(start, end, literal);
+                }
+            },
+            TsType::Template(ref template) => {
+                self.open("TSLiteralType", start, end);
+                self.key("literal");
+                self.open("TemplateLiteral", start, end);
+                self.nodes("expressions", template.types, Self::ts_type);
+                self.list("quasis", template.quasis, Self::template_element);
+                self.close();
+            },
+            TsType::Array(element) => {
+                self.open("TSArrayType", start, end);
+                self.key("elementType");
+                self.ts_type(element);
+            },
+            TsType::Tuple(elements) => {
+                self.open("TSTupleType", start, end);
+                self.list("elementTypes", elements, |writer, element| {
+                    let (start, end) = (element.start, element.end);
+                    match element.label {
+                        // a?: A, ...rest: A[]
+                        Some(ref label) => {
+                            if element.rest {
+                                writer.open("TSRestType", start, end);
+                                writer.key("typeAnnotation");
+                            }
+                            writer.open("TSNamedTupleMember", label.start, end);
+                            writer.key("label");
+                            writer.token_identifier(label);
+                            writer.boolean("optional", element.optional);
+                            writer.key("elementType");
+                            writer.ts_type(&element.ty);
+                            writer.close();
+                            if element.rest {
+                                writer.close();
+                            }
+                        },
+                        None if element.rest || element.optional => {
+                            writer.open(if element.rest { "TSRestType" } else { "TSOptionalType" }, start, end);
+                            writer.key("typeAnnotation");
+                            writer.ts_type(&element.ty);
+                            writer.close();
+                        },
+                        None => writer.ts_type(&element.ty),
+                    }
+                });
+            },
+            TsType::Union(types) => {
+                self.open("TSUnionType", start, end);
+                self.nodes("types", types, Self::ts_type);
+            },
+            TsType::Intersection(types) => {
+                self.open("TSIntersectionType", start, end);
+                self.nodes("types", types, Self::ts_type);
+            },
+            TsType::Function(ref function) => {
+                self.open(if function.is_constructor { "TSConstructorType" } else { "TSFunctionType" }, start, end);
+                if let Some(type_params) = function.type_params {
+                    self.key("typeParameters");
+                    self.ts_type_parameters(type_params);
+                }
+                self.nodes("parameters", function.params, Self::pattern);
+                self.key("typeAnnotation");
+                self.ts_type_annotation(function.return_type);
+            },
+            TsType::TypeLiteral(members) => {
+                self.open("TSTypeLiteral", start, end);
+                self.nodes("members", members, Self::ts_type_member);
+            },
+            TsType::Mapped(ref mapped) => {
+                self.open("TSMappedType", start, end);
+                if let Some(readonly) = mapped.readonly {
+                    self.raw("readonly", mapped_modifier(readonly));
+                }
+                self.key("typeParameter");
+                self.open("TSTypeParameter", mapped.name.start, mapped.constraint.end);
+                let name = self.text(mapped.name.start, mapped.name.end);
+                self.string("name", name);
+                self.key("constraint");
+                self.ts_type(mapped.constraint);
+                self.close();
+                self.option("nameType", mapped.name_type, Self::ts_type);
+                if let Some(optional) = mapped.optional {
+                    self.raw("optional", mapped_modifier(optional));
+                }
+                if let Some(ty) = mapped.ty {
+                    self.key("typeAnnotation");
+                    self.ts_type(ty);
+                }
+            },
+            TsType::Parenthesized(inner) => {
+                self.open("TSParenthesizedType", start, end);
+                self.key("typeAnnotation");
+                self.ts_type(inner);
+            },
+            TsType::Operator(ref operator) => {
+                self.open("TSTypeOperator", start, end);
+                self.string("operator", match operator.operator {
+                    TsTypeOperatorKind::Keyof => "keyof",
+                    TsTypeOperatorKind::Unique => "unique",
+                    TsTypeOperatorKind::Readonly => "readonly",
+                });
+                self.key("typeAnnotation");
+                self.ts_type(operator.ty);
+            },
+            TsType::Query(ref query) => {
+                self.open("TSTypeQuery", start, end);
+                self.key("exprName");
+                self.ts_entity_name(query.name);
+                if let Some(ref type_args) = query.type_args {
+                    self.key("typeParameters");
+                    self.ts_type_arguments(type_args);
+                }
+            },
+            TsType::Import(ref import) => {
+                self.open("TSImportType", start, end);
+                self.key("argument");
+                self.literal(import.argument.start, import.argument.end, import.argument.item);
+                self.key("qualifier");
+                self.ts_entity_name(import.qualifier);
+                if let Some(ref type_args) = import.type_args {
+                    self.key("typeParameters");
+                    self.ts_type_arguments(type_args);
+                }
+            },
+            TsType::IndexedAccess(ref access) => {
+                self.open("TSIndexedAccessType", start, end);
+                self.key("objectType");
+                self.ts_type(access.object);
+                self.key("indexType");
+                self.ts_type(access.index);
+            },
+            TsType::Conditional(ref conditional) => {
+                self.open("TSConditionalType", start, end);
+                self.key("checkType");
+                self.ts_type(conditional.check);
+                self.key("extendsType");
+                self.ts_type(conditional.extends);
+                self.key("trueType");
+                self.ts_type(conditional.true_type);
+                self.key("falseType");
+                self.ts_type(conditional.false_type);
+            },
+            TsType::Infer(ref infer) => {
+                self.open("TSInferType", start, end);
+                self.key("typeParameter");
+                let param_end = infer.constraint.as_ref().map_or(infer.name.end, |constraint| constraint.end);
+                self.open("TSTypeParameter", infer.name.start, param_end);
+                let name = self.text(infer.name.start, infer.name.end);
+                self.string("name", name);
+                if let Some(constraint) = infer.constraint {
+                    self.key("constraint");
+                    self.ts_type(constraint);
+                }
+                self.close();
+            },
+            TsType::Predicate(ref predicate) => {
+                self.open("TSTypePredicate", start, end);
+                self.key("parameterName");
+                if self.text(predicate.name.start, predicate.name.end) == "this" {
+                    self.open("TSThisType", predicate.name.start, predicate.name.end);
+                    self.close();
+                } else {
+                    self.token_identifier(&predicate.name);
+                }
+                self.option("typeAnnotation", predicate.ty, Self::ts_type_annotation);
+                self.boolean("asserts", predicate.asserts);
+            },
+        }
+        self.close();
+    }
+
+    fn ts_type_member(&mut self, member: &Loc<TsTypeMember>) {
+        let (start, end) = (member.start, member.end);
+        match member.item {
+            TsTypeMember::Property { readonly, ref key, optional, ref ty } => {
+                self.open("TSPropertySignature", start, end);
+                self.property_key(key);
+                if optional {
+                    self.boolean("optional", true);
+                }
+                if readonly {
+                    self.boolean("readonly", true);
+                }
+                if let Some(ref ty) = ty {
+                    self.key("typeAnnotation");
+                    self.ts_type_annotation(ty);
+                }
+            },
+            TsTypeMember::Method { kind, ref key, optional, ref signature } => {
+                self.open("TSMethodSignature", start, end);
+                self.string("kind", method_kind(kind));
+                self.property_key(key);
+                if optional {
+                    self.boolean("optional", true);
+                }
+                self.ts_signature_fields(signature);
+            },
+            TsTypeMember::Call(ref signature) => {
+                self.open("TSCallSignatureDeclaration", start, end);
+                self.ts_signature_fields(signature);
+            },
+            TsTypeMember::Construct(ref signature) => {
+                self.open("TSConstructSignatureDeclaration", start, end);
+                self.ts_signature_fields(signature);
+            },
+            TsTypeMember::Index(ref signature) => return self.ts_index_signature(start, end, signature),
+        }
+        self.close();
+    }
+
+    fn ts_signature_fields(&mut self, signature: &TsSignature) {
+        if let Some(type_params) = signature.type_params {
+            self.key("typeParameters");
+            self.ts_type_parameters(type_params);
+        }
+        self.nodes("parameters", signature.params, Self::pattern);
+        if let Some(ref return_type) = signature.return_type {
+            self.key("typeAnnotation");
+            self.ts_type_annotation(return_type);
+        }
+    }
+
+    fn ts_index_signature(&mut self, start: usize, end: usize, signature: &TsIndexSignature) {
+        self.open("TSIndexSignature", start, end);
+        self.ts_modifiers(&signature.modifiers);
+        if signature.is_static {
+            self.boolean("static", true);
+        }
+        self.nodes("parameters", signature.params, Self::pattern);
+        self.key("typeAnnotation");
+        self.ts_type_annotation(&signature.ty);
+        self.close();
+    }
+
+    fn ts_declare_function(&mut self, start: usize, end: usize, function: &TsDeclareFunction) {
+        self.open("TSDeclareFunction", start, end);
+        self.option("id", function.id.as_ref(), Self::token_identifier);
+        self.boolean("generator", function.is_generator);
+        self.boolean("async", function.is_async);
+        if let Some(type_params) = function.type_params {
+            self.key("typeParameters");
+            self.ts_type_parameters(type_params);
+        }
+        self.nodes("params", function.params, Self::pattern);
+        if let Some(return_type) = function.return_type {
+            self.return_type(return_type);
+        }
+        self.close();
+    }
+
+    fn ts_interface(&mut self, start: usize, end: usize, interface: &TsInterfaceDeclaration) {
+        self.open("TSInterfaceDeclaration", start, end);
+        self.key("id");
+        self.token_identifier(&interface.id);
+        if let Some(type_params) = interface.type_params {
+            self.key("typeParameters");
+            self.ts_type_parameters(type_params);
+        }
+        if !interface.extends.is_empty() {
+            self.nodes("extends", interface.extends, Self::ts_expression_with_type_arguments);
+        }
+
+        let header_end = [
+            Some(interface.id.end),
+            interface.type_params.as_ref().map(|params| params.end),
+            interface.extends.iter().last().map(|ty| ty.end),
+        ].iter().flatten().max().cloned().unwrap_or(start);
+        let body_start = self.skip_trivia(header_end);
+        self.key("body");
+        self.open("TSInterfaceBody", body_start, end);
+        self.nodes("body", interface.body, Self::ts_type_member);
+        self.close();
+
+        self.close();
+    }
+
+    fn ts_module(&mut self, start: usize, end: usize, module: &TsModuleDeclaration) {
+        let kind = match self.text(self.skip_trivia(start), end) {
+            text if text.starts_with("namespace") => "namespace",
+            text if text.starts_with("module") => "module",
+            _ => "global",
+        };
+        self.ts_module_names(start, end, kind, module.id, module.body);
+    }
+
+    // namespace A.B {} is namespace A { namespace B {} }
+    fn ts_module_names(&mut self, start: usize, end: usize, kind: &str, names: List<Loc<&str>>, body: Option<NodeList<Statement>>) {
+        let mut rest = names;
+        let name = match rest.shift_ref() {
+            Some(name) => name,
+            None => return self.output.push_str("null"),
+        };
+
+        self.open("TSModuleDeclaration", start, end);
+        self.string("kind", kind);
+        self.key("id");
+        self.identifier_or_string(name);
+        if let Some(next) = rest.first_element() {
+            self.key("body");
+            self.ts_module_names(next.start, end, kind, rest, body);
+        } else if let Some(body) = body {
+            let body_start = self.skip_trivia(name.end);
+            self.key("body");
+            self.open("TSModuleBlock", body_start, end);
+            self.nodes("body", body, Self::statement);
+            self.close();
+        }
+        self.close();
+    }
+}
+
+
+/// `a?.b.c()` belongs to an optional chain, parentheses end a chain.
+fn is_optional_chain(expr: &Expression) -> bool {
+    match expr {
+        Expression::Call(call) => call.optional || is_optional_chain(&call.callee.item),
+        Expression::StaticMember(member) => member.optional || is_optional_chain(&member.object.item),
+        Expression::ComputedMember(member) => member.optional || is_optional_chain(&member.object.item),
+        Expression::TsNonNull(expr) => is_optional_chain(&expr.item),
+        _ => false,
+    }
+}
+
+fn element_name_start(name: &ElementName) -> usize {
+    match name {
+        ElementName::Identifier(token) => token.start,
+        ElementName::NamespacedName(name) => name.ns.start,
+        ElementName::MemberExpression(member) => member.members.first_element().map_or(0, |token| token.start),
+    }
+}
+
+fn element_name_end(name: &ElementName) -> usize {
+    match name {
+        ElementName::Identifier(token) => token.end,
+        ElementName::NamespacedName(name) => name.name.end,
+        ElementName::MemberExpression(member) => member.members.iter().last().map_or(0, |token| token.end),
+    }
+}
+
+fn attribute_name_span(name: &NormalAttributeName) -> (usize, usize) {
+    match name {
+        NormalAttributeName::Identifier(token) => (token.start, token.end),
+        NormalAttributeName::NamespacedName(name) => (name.ns.start, name.name.end),
+    }
+}
+
+fn method_kind(kind: MethodKind) -> &'static str {
+    match kind {
+        MethodKind::Constructor => "constructor",
+        MethodKind::Method => "method",
+        MethodKind::Get => "get",
+        MethodKind::Set => "set",
+    }
+}
+
+/// `true`, `"+"` or `"-"`.
+fn mapped_modifier(modifier: TsMappedModifier) -> &'static str {
+    match modifier {
+        TsMappedModifier::Present => "true",
+        TsMappedModifier::Add => "\"+\"",
+        TsMappedModifier::Remove => "\"-\"",
+    }
+}
+
+/// Value of a numeric literal, `0x1f`, `0o17`, `1_000`, legacy octal `017`.
+pub(crate) fn number_value(raw: &str) -> f64 {
+    let raw = raw.replace('_', "");
+    let (digits, radix) = match raw.get(..2) {
+        Some("0x") | Some("0X") => (&raw[2..], 16),
+        Some("0o") | Some("0O") => (&raw[2..], 8),
+        Some("0b") | Some("0B") => (&raw[2..], 2),
+        _ if raw.len() > 1 && raw.starts_with('0') && raw.bytes().all(|b| (b'0'..=b'7').contains(&b)) => (&raw[1..], 8),
+        _ => return raw.parse().unwrap_or(f64::NAN),
+    };
+
+    digits.chars().fold(0.0, |value, c| value * radix as f64 + c.to_digit(radix).unwrap_or(0) as f64)
+}
+
+/// JSON has no `Infinity` or `NaN`, `JSON.stringify` writes `null` for them too.
+fn number_json(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+/// Value of the text of a string literal or a template, escapes resolved and
+/// line terminators normalized to `\n`.
+///
+// "a\x41\u{1F600}\
+// b" => "aA😀b"
+fn cook(raw: &str) -> String {
+    let mut cooked = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(index) = rest.find(['\\', '\r']) {
+        cooked.push_str(&rest[..index]);
+        if rest[index..].starts_with('\r') {
+            cooked.push('\n');
+            rest = &rest[index + 1..];
+            rest = rest.strip_prefix('\n').unwrap_or(rest);
+            continue;
+        }
+
+        rest = &rest[index + 1..];
+        let escaped = match rest.chars().next() {
+            Some(escaped) => escaped,
+            None => break,
+        };
+        rest = &rest[escaped.len_utf8()..];
+
+        match escaped {
+            'n' => cooked.push('\n'),
+            't' => cooked.push('\t'),
+            'r' => cooked.push('\r'),
+            'b' => cooked.push('\u{8}'),
+            'f' => cooked.push('\u{c}'),
+            'v' => cooked.push('\u{b}'),
+            // Line continuations
+            '\r' => rest = rest.strip_prefix('\n').unwrap_or(rest),
+            '\n' | '\u{2028}' | '\u{2029}' => {},
+            // \0, legacy octal \101
+            '0'..='7' => {
+                let max_len = if escaped <= '3' { 2 } else { 1 };
+                let len = rest.bytes().take(max_len).take_while(|b| (b'0'..=b'7').contains(b)).count();
+                let code = rest[..len].bytes().fold(escaped as u32 - '0' as u32, |code, b| code * 8 + (b - b'0') as u32);
+                cooked.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                rest = &rest[len..];
+            },
+            'x' => match hex(rest, 2) {
+                Some(code) => {
+                    cooked.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    rest = &rest[2..];
+                },
+                None => cooked.push('x'),
+            },
+            'u' => match unicode_escape(rest) {
+                // 😀
+                Some((high @ 0xd800..=0xdbff, len)) => {
+                    rest = &rest[len..];
+                    match rest.strip_prefix("\\u").and_then(unicode_escape) {
+                        Some((low @ 0xdc00..=0xdfff, len)) => {
+                            let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                            cooked.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                            rest = &rest[2 + len..];
+                        },
+                        _ => cooked.push('\u{fffd}'),
+                    }
+                },
+                Some((code, len)) => {
+                    cooked.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    rest = &rest[len..];
+                },
+                None => cooked.push('u'),
+            },
+            c => cooked.push(c),
+        }
+    }
+    cooked.push_str(rest);
+
+    cooked
+}
+
+/// Code point of the `XXXX` or `{X...}` after `\u`, and its length.
+fn unicode_escape(text: &str) -> Option<(u32, usize)> {
+    match text.strip_prefix('{') {
+        Some(braced) => {
+            let end = braced.find('}')?;
+            let code = u32::from_str_radix(&braced[..end], 16).ok()?;
+            Some((code, end + 2))
+        },
+        None => Some((hex(text, 4)?, 4)),
+    }
+}
+
+#[inline]
+fn hex(text: &str, len: usize) -> Option<u32> {
+    let digits = text.get(..len)?;
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(digits, 16).ok()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use toolshed::Arena;
+
+    use crate::parser::ParseOptions;
+
+    use super::to_json;
+
+    fn json(code: &str) -> String {
+        let arena = Arena::new();
+        let program = crate::parse(code, &arena, ParseOptions::default()).unwrap();
+        to_json(code, &program)
+    }
+
+    #[test]
+    fn babel_shape() {
+        assert_eq!(json("x = 1 + 2n"), concat!(
+            r#"{"type":"File","start":0,"end":10,"loc":{"start":{"line":1,"column":0,"index":0},"end":{"line":1,"column":10,"index":10}},"#,
+            r#""program":{"type":"Program","start":0,"end":10,"loc":{"start":{"line":1,"column":0,"index":0},"end":{"line":1,"column":10,"index":10}},"#,
+            r#""sourceType":"module","interpreter":null,"body":["#,
+            r#"{"type":"ExpressionStatement","start":0,"end":10,"loc":{"start":{"line":1,"column":0,"index":0},"end":{"line":1,"column":10,"index":10}},"#,
+            r#""expression":{"type":"AssignmentExpression","start":0,"end":10,"loc":{"start":{"line":1,"column":0,"index":0},"end":{"line":1,"column":10,"index":10}},"operator":"=","#,
+            r#""left":{"type":"Identifier","start":0,"end":1,"loc":{"start":{"line":1,"column":0,"index":0},"end":{"line":1,"column":1,"index":1}},"name":"x"},"#,
+            r#""right":{"type":"BinaryExpression","start":4,"end":10,"loc":{"start":{"line":1,"column":4,"index":4},"end":{"line":1,"column":10,"index":10}},"#,
+            r#""left":{"type":"NumericLiteral","start":4,"end":5,"loc":{"start":{"line":1,"column":4,"index":4},"end":{"line":1,"column":5,"index":5}},"value":1,"extra":{"rawValue":1,"raw":"1"}},"#,
+            r#""operator":"+","#,
+            r#""right":{"type":"BigIntLiteral","start":8,"end":10,"loc":{"start":{"line":1,"column":8,"index":8},"end":{"line":1,"column":10,"index":10}},"value":"2","extra":{"rawValue":"2","raw":"2n"}}}}}],"#,
+            r#""directives":[]},"comments":[]}"#,
+        ));
+    }
+
+    #[test]
+    fn jsx_nodes() {
+        assert_eq!(json("<a b=\"c\">{d}</a>;"), concat!(
+            r#"{"type":"File","start":0,"end":17,"loc":{"start":{"line":1,"column":0,"index":0},"end":{"line":1,"column":17,"index":17}},"#,
+            r#""program":{"type":"Program","start":0,"end":17,"loc":{"start":{"line":1,"column":0,"index":0},"end":{"line":1,"column":17,"index":17}},"#,
+            r#""sourceType":"module","interpreter":null,"body":["#,
+            r#"{"type":"ExpressionStatement","start":0,"end":17,"loc":{"start":{"line":1,"column":0,"index":0},"end":{"line":1,"column":17,"index":17}},"#,
+            r#""expression":{"type":"JSXElement","start":0,"end":16,"loc":{"start":{"line":1,"column":0,"index":0},"end":{"line":1,"column":16,"index":16}},"#,
+            r#""openingElement":{"type":"JSXOpeningElement","start":0,"end":9,"loc":{"start":{"line":1,"column":0,"index":0},"end":{"line":1,"column":9,"index":9}},"#,
+            r#""name":{"type":"JSXIdentifier","start":1,"end":2,"loc":{"start":{"line":1,"column":1,"index":1},"end":{"line":1,"column":2,"index":2}},"name":"a"},"#,
+            r#""attributes":[{"type":"JSXAttribute","start":3,"end":8,"loc":{"start":{"line":1,"column":3,"index":3},"end":{"line":1,"column":8,"index":8}},"#,
+            r#""name":{"type":"JSXIdentifier","start":3,"end":4,"loc":{"start":{"line":1,"column":3,"index":3},"end":{"line":1,"column":4,"index":4}},"name":"b"},"#,
+            r#""value":{"type":"StringLiteral","start":5,"end":8,"loc":{"start":{"line":1,"column":5,"index":5},"end":{"line":1,"column":8,"index":8}},"value":"c","extra":{"rawValue":"c","raw":"\"c\""}}}],"#,
+            r#""selfClosing":false},"#,
+            r#""children":[{"type":"JSXExpressionContainer","start":9,"end":12,"loc":{"start":{"line":1,"column":9,"index":9},"end":{"line":1,"column":12,"index":12}},"#,
+            r#""expression":{"type":"Identifier","start":10,"end":11,"loc":{"start":{"line":1,"column":10,"index":10},"end":{"line":1,"column":11,"index":11}},"name":"d"}}],"#,
+            r#""closingElement":{"type":"JSXClosingElement","start":12,"end":16,"loc":{"start":{"line":1,"column":12,"index":12},"end":{"line":1,"column":16,"index":16}},"#,
+            r#""name":{"type":"JSXIdentifier","start":14,"end":15,"loc":{"start":{"line":1,"column":14,"index":14},"end":{"line":1,"column":15,"index":15}},"name":"a"}}}}],"#,
+            r#""directives":[]},"comments":[]}"#,
+        ));
+    }
+
+    #[test]
+    fn utf16_offsets() {
+        let json = json("\"\u{1f600}\";\nb");
+        assert!(json.contains(r#"{"type":"Identifier","start":6,"end":7,"loc":{"start":{"line":2,"column":0,"index":6},"end":{"line":2,"column":1,"index":7}},"name":"b"}"#));
+        assert!(json.contains(r#"{"type":"StringLiteral","start":0,"end":4,"#));
+    }
+}
@@ -0,0 +1,581 @@
+use toolshed::Arena;
+use crate::list::List;
+
+use crate::ast::{
+    Loc, Node, NodeList, Program, Statement, BlockStatement, VariableDeclaration, VariableDeclarator, IfStatement,
+    SwitchStatement, SwitchCase, WhileStatement, DoWhileStatement, ForStatement, ForInit, ForHead,
+    ForInStatement, ForOfStatement, TryStatement, CatchClause, LabeledStatement, WithStatement,
+    ExportNamedDeclaration, ExportDefaultDeclaration, TsEnumDeclaration, TsEnumMember,
+    TsModuleDeclaration, TsDeclareFunction, TsIndexSignature, Expression, TemplateLiteral,
+    TaggedTemplateExpression, ArrayExpression, ObjectExpression, ArrowFunctionExpression, ArrowBody,
+    UnaryExpression, UpdateExpression, BinaryExpression, ConditionalExpression,
+    AssignmentExpression, CallExpression, NewExpression, StaticMemberExpression,
+    ComputedMemberExpression, YieldExpression, TsAsExpression, Property, PropertyKey, Pattern,
+    PatternProperty, ObjectPattern, ArrayPattern, AssignmentPattern, TypedPattern, TsParameterProperty,
+    Function, Class, ClassMember, ElementExpression, FragmentExpression, ElementName, Attribute,
+    NormalAttribute, NormalAttributeName, NormalAttributeInitializer, Child,
+};
+
+
+/// Rebuilds the AST from copies of its nodes, allocated in `arena`. Every
+/// method folds the children of its node with the matching `walk_*` function
+/// by default, an override returns the node to put in its place.
+///
+/// Nodes left as they were parsed are copied from the source, a node a fold
+/// changed is printed from the AST without its comments. Literals and the text
+/// of templates carry no payload, so a new one takes the span of the source
+/// text it copies.
+///
+// struct DropTestIds<'ast>(&'ast Arena);
+//
+// impl<'ast> Fold<'ast> for DropTestIds<'ast> {
+//     fn arena(&self) -> &'ast Arena {
+//         self.0
+//     }
+//
+//     fn fold_attribute(&mut self, attr: Attribute<'ast>) -> Vec<Attribute<'ast>> {
+//         if is_test_id(&attr) { vec![] } else { vec![walk_attribute(self, attr)] }
+//     }
+// }
+pub trait Fold<'ast> {
+    /// The arena of the parse, holds the new nodes.
+    fn arena(&self) -> &'ast Arena;
+
+    fn fold_program(&mut self, program: Program<'ast>) -> Program<'ast> {
+        walk_program(self, program)
+    }
+
+    fn fold_statement(&mut self, stmt: Loc<Statement<'ast>>) -> Loc<Statement<'ast>> {
+        walk_statement(self, stmt)
+    }
+
+    fn fold_expression(&mut self, expr: Loc<Expression<'ast>>) -> Loc<Expression<'ast>> {
+        walk_expression(self, expr)
+    }
+
+    fn fold_pattern(&mut self, pattern: Loc<Pattern<'ast>>) -> Loc<Pattern<'ast>> {
+        walk_pattern(self, pattern)
+    }
+
+    fn fold_function(&mut self, function: Function<'ast>) -> Function<'ast> {
+        walk_function(self, function)
+    }
+
+    fn fold_class(&mut self, class: Class<'ast>) -> Class<'ast> {
+        walk_class(self, class)
+    }
+
+    fn fold_class_member(&mut self, member: Loc<ClassMember<'ast>>) -> Loc<ClassMember<'ast>> {
+        walk_class_member(self, member)
+    }
+
+    fn fold_property(&mut self, property: Loc<Property<'ast>>) -> Loc<Property<'ast>> {
+        walk_property(self, property)
+    }
+
+    fn fold_element(&mut self, elem: ElementExpression<'ast>) -> ElementExpression<'ast> {
+        walk_element(self, elem)
+    }
+
+    fn fold_fragment(&mut self, fragment: FragmentExpression<'ast>) -> FragmentExpression<'ast> {
+        walk_fragment(self, fragment)
+    }
+
+    fn fold_element_name(&mut self, name: ElementName<'ast>) -> ElementName<'ast> {
+        name
+    }
+
+    /// The attributes put in place of `attr`, none drops it.
+    fn fold_attribute(&mut self, attr: Attribute<'ast>) -> Vec<Attribute<'ast>> {
+        vec![ walk_attribute(self, attr) ]
+    }
+
+    fn fold_attribute_name(&mut self, name: NormalAttributeName<'ast>) -> NormalAttributeName<'ast> {
+        name
+    }
+
+    fn fold_attribute_value(&mut self, value: NormalAttributeInitializer<'ast>) -> NormalAttributeInitializer<'ast> {
+        walk_attribute_value(self, value)
+    }
+
+    /// The children spliced in place of `child`, none drops it.
+    fn fold_child(&mut self, child: Child<'ast>) -> Vec<Child<'ast>> {
+        vec![ walk_child(self, child) ]
+    }
+}
+
+
+#[inline]
+fn map_item<T, U, F: FnOnce(T) -> U>(loc: Loc<T>, op: F) -> Loc<U> {
+    Loc::new(loc.start, loc.end, op(loc.item))
+}
+
+/// Folds a copy of `node` into a new node, nodes in the arena never change.
+#[inline]
+fn fold_node<'ast, F, T, Op>(f: &mut F, node: &'ast Loc<T>, op: Op) -> &'ast Loc<T>
+    where F: Fold<'ast> + ?Sized, T: Copy, Op: FnOnce(&mut F, Loc<T>) -> Loc<T>
+{
+    let loc = op(f, *node);
+    f.arena().alloc(loc)
+}
+
+/// Folds the items of `list` into a new list.
+fn fold_list<'ast, F, T, Op>(f: &mut F, list: List<'ast, T>, mut op: Op) -> List<'ast, T>
+    where F: Fold<'ast> + ?Sized, T: Copy, Op: FnMut(&mut F, T) -> T
+{
+    let arena = f.arena();
+    List::from_iter(arena, list.iter().map(|item| op(f, *item)))
+}
+
+#[inline]
+fn fold_nodes<'ast, F, T, Op>(f: &mut F, list: NodeList<'ast, T>, mut op: Op) -> NodeList<'ast, T>
+    where F: Fold<'ast> + ?Sized, T: Copy, Op: FnMut(&mut F, Loc<T>) -> Loc<T>
+{
+    fold_list(f, list, |f, node| fold_node(f, node, &mut op))
+}
+
+fn fold_expressions<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, expressions: NodeList<'ast, Expression<'ast>>) -> NodeList<'ast, Expression<'ast>> {
+    fold_nodes(f, expressions, F::fold_expression)
+}
+
+fn fold_statements<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, body: NodeList<'ast, Statement<'ast>>) -> NodeList<'ast, Statement<'ast>> {
+    fold_nodes(f, body, F::fold_statement)
+}
+
+#[inline]
+fn fold_element_node<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, elem: Node<'ast, ElementExpression<'ast>>) -> Node<'ast, ElementExpression<'ast>> {
+    fold_node(f, elem, |f, elem| map_item(elem, |elem| f.fold_element(elem)))
+}
+
+#[inline]
+fn fold_fragment_node<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, fragment: Node<'ast, FragmentExpression<'ast>>) -> Node<'ast, FragmentExpression<'ast>> {
+    fold_node(f, fragment, |f, fragment| map_item(fragment, |fragment| f.fold_fragment(fragment)))
+}
+
+fn fold_children<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, children: List<'ast, Child<'ast>>) -> List<'ast, Child<'ast>> {
+    let arena = f.arena();
+    List::from_iter(arena, children.iter().flat_map(|child| f.fold_child(*child)))
+}
+
+
+pub fn walk_program<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, program: Program<'ast>) -> Program<'ast> {
+    Program {
+        body: fold_statements(f, program.body),
+        ..program
+    }
+}
+
+pub fn walk_statement<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, stmt: Loc<Statement<'ast>>) -> Loc<Statement<'ast>> {
+    map_item(stmt, |item| match item {
+        Statement::Empty => Statement::Empty,
+        Statement::Debugger => Statement::Debugger,
+        Statement::Error => Statement::Error,
+        Statement::Break(label) => Statement::Break(label),
+        Statement::Continue(label) => Statement::Continue(label),
+        Statement::Expression(expr) => Statement::Expression(fold_node(f, expr, F::fold_expression)),
+        Statement::Throw(expr) => Statement::Throw(fold_node(f, expr, F::fold_expression)),
+        Statement::Block(block) => Statement::Block(walk_block(f, block)),
+        Statement::Variable(declaration) => Statement::Variable(walk_variable_declaration(f, declaration)),
+        Statement::Function(function) => Statement::Function(f.fold_function(function)),
+        Statement::Class(class) => Statement::Class(f.fold_class(class)),
+        Statement::Return(argument) => Statement::Return(argument.map(|argument| fold_node(f, argument, F::fold_expression))),
+        Statement::If(stmt) => Statement::If(IfStatement {
+            test: fold_node(f, stmt.test, F::fold_expression),
+            consequent: fold_node(f, stmt.consequent, F::fold_statement),
+            alternate: stmt.alternate.map(|alternate| fold_node(f, alternate, F::fold_statement)),
+        }),
+        Statement::Switch(stmt) => Statement::Switch(SwitchStatement {
+            discriminant: fold_node(f, stmt.discriminant, F::fold_expression),
+            cases: fold_nodes(f, stmt.cases, |f, case| map_item(case, |case| SwitchCase {
+                test: case.test.map(|test| fold_node(f, test, F::fold_expression)),
+                consequent: fold_statements(f, case.consequent),
+            })),
+        }),
+        Statement::While(stmt) => Statement::While(WhileStatement {
+            test: fold_node(f, stmt.test, F::fold_expression),
+            body: fold_node(f, stmt.body, F::fold_statement),
+        }),
+        Statement::DoWhile(stmt) => Statement::DoWhile(DoWhileStatement {
+            body: fold_node(f, stmt.body, F::fold_statement),
+            test: fold_node(f, stmt.test, F::fold_expression),
+        }),
+        Statement::For(stmt) => {
+            let init = stmt.init.map(|init| match init {
+                ForInit::Variable(declaration) => {
+                    ForInit::Variable(map_item(declaration, |declaration| walk_variable_declaration(f, declaration)))
+                },
+                ForInit::Expression(expr) => ForInit::Expression(fold_node(f, expr, F::fold_expression)),
+            });
+
+            Statement::For(ForStatement {
+                init,
+                test: stmt.test.map(|test| fold_node(f, test, F::fold_expression)),
+                update: stmt.update.map(|update| fold_node(f, update, F::fold_expression)),
+                body: fold_node(f, stmt.body, F::fold_statement),
+            })
+        },
+        Statement::ForIn(stmt) => Statement::ForIn(ForInStatement {
+            left: walk_for_head(f, stmt.left),
+            right: fold_node(f, stmt.right, F::fold_expression),
+            body: fold_node(f, stmt.body, F::fold_statement),
+        }),
+        Statement::ForOf(stmt) => Statement::ForOf(ForOfStatement {
+            left: walk_for_head(f, stmt.left),
+            right: fold_node(f, stmt.right, F::fold_expression),
+            body: fold_node(f, stmt.body, F::fold_statement),
+            ..stmt
+        }),
+        Statement::Try(stmt) => Statement::Try(TryStatement {
+            block: map_item(stmt.block, |block| walk_block(f, block)),
+            handler: stmt.handler.map(|handler| map_item(handler, |handler| CatchClause {
+                param: handler.param.map(|param| fold_node(f, param, F::fold_pattern)),
+                body: map_item(handler.body, |body| walk_block(f, body)),
+            })),
+            finalizer: stmt.finalizer.map(|finalizer| map_item(finalizer, |finalizer| walk_block(f, finalizer))),
+        }),
+        Statement::Labeled(stmt) => Statement::Labeled(LabeledStatement {
+            body: fold_node(f, stmt.body, F::fold_statement),
+            ..stmt
+        }),
+        Statement::With(stmt) => Statement::With(WithStatement {
+            object: fold_node(f, stmt.object, F::fold_expression),
+            body: fold_node(f, stmt.body, F::fold_statement),
+        }),
+        Statement::Import(declaration) => Statement::Import(declaration),
+        Statement::ExportAll(declaration) => Statement::ExportAll(declaration),
+        Statement::ExportNamed(declaration) => Statement::ExportNamed(ExportNamedDeclaration {
+            declaration: declaration.declaration.map(|declaration| fold_node(f, declaration, F::fold_statement)),
+            ..declaration
+        }),
+        Statement::ExportDefault(declaration) => Statement::ExportDefault(match declaration {
+            ExportDefaultDeclaration::Function(function) => ExportDefaultDeclaration::Function(f.fold_function(function)),
+            ExportDefaultDeclaration::Class(class) => ExportDefaultDeclaration::Class(f.fold_class(class)),
+            ExportDefaultDeclaration::Expression(expr) => ExportDefaultDeclaration::Expression(fold_node(f, expr, F::fold_expression)),
+            ExportDefaultDeclaration::TsDeclareFunction(function) => {
+                ExportDefaultDeclaration::TsDeclareFunction(walk_declare_function(f, function))
+            },
+            ExportDefaultDeclaration::TsInterface(declaration) => ExportDefaultDeclaration::TsInterface(declaration),
+        }),
+        Statement::TsInterface(declaration) => Statement::TsInterface(declaration),
+        Statement::TsTypeAlias(declaration) => Statement::TsTypeAlias(declaration),
+        Statement::TsEnum(declaration) => Statement::TsEnum(TsEnumDeclaration {
+            members: fold_nodes(f, declaration.members, |f, member| map_item(member, |member| TsEnumMember {
+                init: member.init.map(|init| fold_node(f, init, F::fold_expression)),
+                ..member
+            })),
+            ..declaration
+        }),
+        Statement::TsModule(module) => Statement::TsModule(TsModuleDeclaration {
+            body: module.body.map(|body| fold_statements(f, body)),
+            ..module
+        }),
+        Statement::TsDeclareFunction(function) => Statement::TsDeclareFunction(walk_declare_function(f, function)),
+        Statement::TsDeclare(declaration) => Statement::TsDeclare(fold_node(f, declaration, F::fold_statement)),
+    })
+}
+
+fn walk_block<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, block: BlockStatement<'ast>) -> BlockStatement<'ast> {
+    BlockStatement {
+        body: fold_statements(f, block.body),
+    }
+}
+
+fn walk_variable_declaration<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, declaration: VariableDeclaration<'ast>) -> VariableDeclaration<'ast> {
+    VariableDeclaration {
+        declarations: fold_nodes(f, declaration.declarations, |f, declarator| map_item(declarator, |declarator| {
+            let id = fold_node(f, declarator.id, F::fold_pattern);
+            let init = declarator.init.map(|init| fold_node(f, init, F::fold_expression));
+            VariableDeclarator { id, init }
+        })),
+        ..declaration
+    }
+}
+
+fn walk_for_head<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, head: ForHead<'ast>) -> ForHead<'ast> {
+    match head {
+        ForHead::Variable(declaration) => {
+            ForHead::Variable(map_item(declaration, |declaration| walk_variable_declaration(f, declaration)))
+        },
+        ForHead::Pattern(pattern) => ForHead::Pattern(fold_node(f, pattern, F::fold_pattern)),
+    }
+}
+
+fn walk_params<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, params: NodeList<'ast, Pattern<'ast>>) -> NodeList<'ast, Pattern<'ast>> {
+    fold_nodes(f, params, F::fold_pattern)
+}
+
+fn walk_declare_function<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, function: TsDeclareFunction<'ast>) -> TsDeclareFunction<'ast> {
+    TsDeclareFunction {
+        params: walk_params(f, function.params),
+        ..function
+    }
+}
+
+/// Computed keys are expressions.
+fn walk_property_key<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, key: PropertyKey<'ast>) -> PropertyKey<'ast> {
+    match key {
+        PropertyKey::Computed(expr) => PropertyKey::Computed(fold_node(f, expr, F::fold_expression)),
+        key => key,
+    }
+}
+
+fn walk_template<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, template: TemplateLiteral<'ast>) -> TemplateLiteral<'ast> {
+    TemplateLiteral {
+        expressions: fold_expressions(f, template.expressions),
+        ..template
+    }
+}
+
+pub fn walk_expression<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, expr: Loc<Expression<'ast>>) -> Loc<Expression<'ast>> {
+    map_item(expr, |item| match item {
+        Expression::This => Expression::This,
+        Expression::Super => Expression::Super,
+        Expression::Identifier(name) => Expression::Identifier(name),
+        Expression::Literal(literal) => Expression::Literal(literal),
+        Expression::MetaProperty(meta) => Expression::MetaProperty(meta),
+        Expression::Template(template) => Expression::Template(walk_template(f, template)),
+        Expression::TaggedTemplate(tagged) => Expression::TaggedTemplate(TaggedTemplateExpression {
+            tag: fold_node(f, tagged.tag, F::fold_expression),
+            quasi: map_item(tagged.quasi, |quasi| walk_template(f, quasi)),
+            ..tagged
+        }),
+        Expression::Array(array) => Expression::Array(ArrayExpression {
+            elements: fold_list(f, array.elements, |f, element| {
+                element.map(|element| fold_node(f, element, F::fold_expression))
+            }),
+        }),
+        Expression::Object(object) => Expression::Object(ObjectExpression {
+            properties: fold_nodes(f, object.properties, F::fold_property),
+        }),
+        Expression::Function(function) => Expression::Function(f.fold_function(function)),
+        Expression::Class(class) => Expression::Class(f.fold_class(class)),
+        Expression::Arrow(arrow) => {
+            let params = walk_params(f, arrow.params);
+            let body = match arrow.body {
+                ArrowBody::Expression(expr) => ArrowBody::Expression(fold_node(f, expr, F::fold_expression)),
+                ArrowBody::Block(block) => ArrowBody::Block(map_item(block, |block| walk_block(f, block))),
+            };
+
+            Expression::Arrow(ArrowFunctionExpression { params, body, ..arrow })
+        },
+        Expression::Parenthesized(expr) => Expression::Parenthesized(fold_node(f, expr, F::fold_expression)),
+        Expression::Spread(expr) => Expression::Spread(fold_node(f, expr, F::fold_expression)),
+        Expression::Await(expr) => Expression::Await(fold_node(f, expr, F::fold_expression)),
+        Expression::Import(expr) => Expression::Import(fold_node(f, expr, F::fold_expression)),
+        Expression::TsNonNull(expr) => Expression::TsNonNull(fold_node(f, expr, F::fold_expression)),
+        Expression::Unary(unary) => Expression::Unary(UnaryExpression {
+            argument: fold_node(f, unary.argument, F::fold_expression),
+            ..unary
+        }),
+        Expression::Update(update) => Expression::Update(UpdateExpression {
+            argument: fold_node(f, update.argument, F::fold_expression),
+            ..update
+        }),
+        Expression::Binary(binary) => Expression::Binary(BinaryExpression {
+            left: fold_node(f, binary.left, F::fold_expression),
+            right: fold_node(f, binary.right, F::fold_expression),
+            ..binary
+        }),
+        Expression::Conditional(conditional) => Expression::Conditional(ConditionalExpression {
+            test: fold_node(f, conditional.test, F::fold_expression),
+            consequent: fold_node(f, conditional.consequent, F::fold_expression),
+            alternate: fold_node(f, conditional.alternate, F::fold_expression),
+        }),
+        Expression::Assignment(assignment) => Expression::Assignment(AssignmentExpression {
+            left: fold_node(f, assignment.left, F::fold_pattern),
+            right: fold_node(f, assignment.right, F::fold_expression),
+            ..assignment
+        }),
+        Expression::Sequence(expressions) => Expression::Sequence(fold_expressions(f, expressions)),
+        Expression::Call(call) => Expression::Call(CallExpression {
+            callee: fold_node(f, call.callee, F::fold_expression),
+            arguments: fold_expressions(f, call.arguments),
+            ..call
+        }),
+        Expression::New(new) => Expression::New(NewExpression {
+            callee: fold_node(f, new.callee, F::fold_expression),
+            arguments: fold_expressions(f, new.arguments),
+            ..new
+        }),
+        Expression::StaticMember(member) => Expression::StaticMember(StaticMemberExpression {
+            object: fold_node(f, member.object, F::fold_expression),
+            ..member
+        }),
+        Expression::ComputedMember(member) => Expression::ComputedMember(ComputedMemberExpression {
+            object: fold_node(f, member.object, F::fold_expression),
+            property: fold_node(f, member.property, F::fold_expression),
+            ..member
+        }),
+        Expression::Yield(expr) => Expression::Yield(YieldExpression {
+            argument: expr.argument.map(|argument| fold_node(f, argument, F::fold_expression)),
+            ..expr
+        }),
+        Expression::Element(elem) => Expression::Element(fold_element_node(f, elem)),
+        Expression::Fragment(fragment) => Expression::Fragment(fold_fragment_node(f, fragment)),
+        Expression::TsAs(expr) => Expression::TsAs(TsAsExpression {
+            expression: fold_node(f, expr.expression, F::fold_expression),
+            ..expr
+        }),
+        Expression::TsSatisfies(expr) => Expression::TsSatisfies(TsAsExpression {
+            expression: fold_node(f, expr.expression, F::fold_expression),
+            ..expr
+        }),
+    })
+}
+
+pub fn walk_pattern<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, pattern: Loc<Pattern<'ast>>) -> Loc<Pattern<'ast>> {
+    map_item(pattern, |item| match item {
+        Pattern::Identifier(name) => Pattern::Identifier(name),
+        Pattern::Object(object) => Pattern::Object(ObjectPattern {
+            properties: fold_nodes(f, object.properties, |f, property| map_item(property, |property| match property {
+                PatternProperty::Property { key, value } => PatternProperty::Property {
+                    key: walk_property_key(f, key),
+                    value: fold_node(f, value, F::fold_pattern),
+                },
+                PatternProperty::Shorthand { name, init } => PatternProperty::Shorthand {
+                    name,
+                    init: init.map(|init| fold_node(f, init, F::fold_expression)),
+                },
+                PatternProperty::Rest(pattern) => PatternProperty::Rest(fold_node(f, pattern, F::fold_pattern)),
+            })),
+        }),
+        Pattern::Array(array) => Pattern::Array(ArrayPattern {
+            elements: fold_list(f, array.elements, |f, element| {
+                element.map(|element| fold_node(f, element, F::fold_pattern))
+            }),
+        }),
+        Pattern::Assignment(assignment) => Pattern::Assignment(AssignmentPattern {
+            left: fold_node(f, assignment.left, F::fold_pattern),
+            right: fold_node(f, assignment.right, F::fold_expression),
+        }),
+        Pattern::Rest(pattern) => Pattern::Rest(fold_node(f, pattern, F::fold_pattern)),
+        Pattern::Expression(expr) => Pattern::Expression(fold_node(f, expr, F::fold_expression)),
+        Pattern::Typed(typed) => Pattern::Typed(TypedPattern {
+            pattern: fold_node(f, typed.pattern, F::fold_pattern),
+            ..typed
+        }),
+        Pattern::TsParameterProperty(property) => Pattern::TsParameterProperty(TsParameterProperty {
+            parameter: fold_node(f, property.parameter, F::fold_pattern),
+            ..property
+        }),
+    })
+}
+
+pub fn walk_function<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, function: Function<'ast>) -> Function<'ast> {
+    Function {
+        params: walk_params(f, function.params),
+        body: map_item(function.body, |body| walk_block(f, body)),
+        ..function
+    }
+}
+
+pub fn walk_class<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, class: Class<'ast>) -> Class<'ast> {
+    Class {
+        super_class: class.super_class.map(|super_class| fold_node(f, super_class, F::fold_expression)),
+        body: fold_nodes(f, class.body, F::fold_class_member),
+        ..class
+    }
+}
+
+pub fn walk_class_member<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, member: Loc<ClassMember<'ast>>) -> Loc<ClassMember<'ast>> {
+    map_item(member, |item| match item {
+        ClassMember::Method { modifiers, is_static, kind, key, optional, value } => ClassMember::Method {
+            modifiers,
+            is_static,
+            kind,
+            key: walk_property_key(f, key),
+            optional,
+            value: map_item(value, |value| f.fold_function(value)),
+        },
+        ClassMember::Property { modifiers, is_static, key, annotation, value } => ClassMember::Property {
+            modifiers,
+            is_static,
+            key: walk_property_key(f, key),
+            annotation,
+            value: value.map(|value| fold_node(f, value, F::fold_expression)),
+        },
+        ClassMember::StaticBlock(block) => ClassMember::StaticBlock(walk_block(f, block)),
+        ClassMember::TsMethodSignature { modifiers, is_static, kind, key, optional, value } => {
+            ClassMember::TsMethodSignature {
+                modifiers,
+                is_static,
+                kind,
+                key: walk_property_key(f, key),
+                optional,
+                value: map_item(value, |value| walk_declare_function(f, value)),
+            }
+        },
+        ClassMember::TsIndexSignature(signature) => ClassMember::TsIndexSignature(TsIndexSignature {
+            params: walk_params(f, signature.params),
+            ..signature
+        }),
+    })
+}
+
+pub fn walk_property<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, property: Loc<Property<'ast>>) -> Loc<Property<'ast>> {
+    map_item(property, |item| match item {
+        Property::Init { key, value } => Property::Init {
+            key: walk_property_key(f, key),
+            value: fold_node(f, value, F::fold_expression),
+        },
+        Property::Shorthand(name) => Property::Shorthand(name),
+        Property::Method { kind, key, value } => Property::Method {
+            kind,
+            key: walk_property_key(f, key),
+            value: map_item(value, |value| f.fold_function(value)),
+        },
+        Property::Spread(expr) => Property::Spread(fold_node(f, expr, F::fold_expression)),
+    })
+}
+
+pub fn walk_element<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, elem: ElementExpression<'ast>) -> ElementExpression<'ast> {
+    let name = f.fold_element_name(elem.name);
+    let arena = f.arena();
+    let attrs = List::from_iter(arena, elem.attrs.iter().flat_map(|attr| f.fold_attribute(*attr)));
+    let children = elem.children.map(|children| fold_children(f, children));
+
+    ElementExpression { name, attrs, children, ..elem }
+}
+
+pub fn walk_fragment<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, fragment: FragmentExpression<'ast>) -> FragmentExpression<'ast> {
+    FragmentExpression {
+        children: fold_children(f, fragment.children),
+    }
+}
+
+pub fn walk_attribute<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, attr: Attribute<'ast>) -> Attribute<'ast> {
+    match attr {
+        Attribute::Normal(normal) => Attribute::Normal(NormalAttribute {
+            name: f.fold_attribute_name(normal.name),
+            init: normal.init.map(|value| f.fold_attribute_value(value)),
+        }),
+        Attribute::Spread(expr) => Attribute::Spread(fold_node(f, expr, F::fold_expression)),
+    }
+}
+
+pub fn walk_attribute_value<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, value: NormalAttributeInitializer<'ast>) -> NormalAttributeInitializer<'ast> {
+    match value {
+        NormalAttributeInitializer::LiteralString(literal) => NormalAttributeInitializer::LiteralString(literal),
+        NormalAttributeInitializer::AssignmentExpression(expr) => {
+            NormalAttributeInitializer::AssignmentExpression(fold_node(f, expr, F::fold_expression))
+        },
+        NormalAttributeInitializer::ElementExpression(elem) => {
+            NormalAttributeInitializer::ElementExpression(fold_element_node(f, elem))
+        },
+        NormalAttributeInitializer::FragmentExpression(fragment) => {
+            NormalAttributeInitializer::FragmentExpression(fold_fragment_node(f, fragment))
+        },
+    }
+}
+
+pub fn walk_child<'ast, F: Fold<'ast> + ?Sized>(f: &mut F, child: Child<'ast>) -> Child<'ast> {
+    match child {
+        Child::Text(text) => Child::Text(text),
+        Child::Element(elem) => Child::Element(fold_element_node(f, elem)),
+        Child::Fragment(fragment) => Child::Fragment(fold_fragment_node(f, fragment)),
+        Child::ChildExpression(expr) => Child::ChildExpression(fold_node(f, expr, F::fold_expression)),
+        Child::Spread(expr) => Child::Spread(fold_node(f, expr, F::fold_expression)),
+        Child::EmptyExpression(empty) => Child::EmptyExpression(empty),
+        Child::Error(error) => Child::Error(error),
+    }
+}
@@ -1,8 +1,13 @@
+use std::fmt;
+
 use crate::unicode_xid::UnicodeXID;
-use crate::error::Error;
+use crate::error::{ Error, LexError, };
+use crate::ast::{ Loc, Comment, };
 
 
 /*
+JSX:
+
 AngleBracket   <>
 Bracket        []
 Paren          ()
@@ -11,8 +16,17 @@ SingleQuote    ''
 DoubleQuote    ""
 Assign         =
 Identifier     XidStart...XidContiue ...
+
+JavaScript:
+
+Punctuators, Identifier, LiteralString, LiteralNumber, LiteralRegex and templates.
+A `/` starts a regex where the previous token cannot end an expression, see
+`regex_allowed`, the parser rescans it where that guess is wrong.
+Keywords are lexed as `Identifier`, the parser tells them apart.
+Comments are skipped like whitespace, also inside JSX tags, see `leading_comments`.
 */
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum Token {
     EndOfProgram,
@@ -40,25 +54,207 @@ pub enum Token {
     Spread,                  // ...
     BraceOpen,               // {
     BraceClose,              // }
+
+    // JSXText
+    Text,                    // abc...
+
+    // ECMAScript
+    LiteralNumber,           // 1 | 1.5 | .5 | 1e3 | 0xff | 0b1 | 0o7 | 1_000 | 10n
+    LiteralRegex,            // /ab+c/gi
+    PrivateIdentifier,       // #abc
+    TemplateString,          // `abc...`
+    TemplateHead,            // `abc${
+    TemplateMiddle,          // }abc${
+    TemplateTail,            // }abc`
+
+    ParenOpen,               // (
+    ParenClose,              // )
+    BracketOpen,             // [
+    BracketClose,            // ]
+    Semicolon,               // ;
+    Question,                // ?
+    OptionalChain,           // ?.
+    Arrow,                   // =>
+
+    Add,                     // +
+    Sub,                     // -
+    Mul,                     // *
+    Div,                     // /
+    Rem,                     // %
+    Exp,                     // **
+    Increment,               // ++
+    Decrement,               // --
+
+    Less,                    // <
+    Greater,                 // >
+    LessEqual,               // <=
+    GreaterEqual,            // >=
+    Equal,                   // ==
+    NotEqual,                // !=
+    StrictEqual,             // ===
+    StrictNotEqual,          // !==
+
+    ShiftLeft,               // <<
+    ShiftRight,              // >>
+    UnsignedShiftRight,      // >>>
+
+    BitAnd,                  // &
+    BitOr,                   // |
+    BitXor,                  // ^
+    BitNot,                  // ~
+    Not,                     // !
+    And,                     // &&
+    Or,                      // ||
+    Nullish,                 // ??
+
+    AddAssign,               // +=
+    SubAssign,               // -=
+    MulAssign,               // *=
+    DivAssign,               // /=
+    RemAssign,               // %=
+    ExpAssign,               // **=
+    ShiftLeftAssign,         // <<=
+    ShiftRightAssign,        // >>=
+    UnsignedShiftRightAssign,// >>>=
+    BitAndAssign,            // &=
+    BitOrAssign,             // |=
+    BitXorAssign,            // ^=
+    AndAssign,               // &&=
+    OrAssign,                // ||=
+    NullishAssign,           // ??=
+}
+
+impl fmt::Display for Token {
+    /// Punctuators are quoted, `)`, other tokens are described, `identifier`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match *self {
+            Token::EndOfProgram => return f.write_str("end of input"),
+            Token::UnexpectedToken => return f.write_str("invalid token"),
+            Token::ElementOpen => "<",
+            Token::ElementClose => ">",
+            Token::SelfClosingElementClose => "/>",
+            Token::ClosingElementOpen => "</",
+            Token::FragmentOpen => "<>",
+            Token::FragmentClose => "</>",
+            Token::Identifier => return f.write_str("identifier"),
+            Token::LiteralString => return f.write_str("string"),
+            Token::Assign => "=",
+            Token::Colon => ":",
+            Token::Dit => ".",
+            Token::Comma => ",",
+            Token::Spread => "...",
+            Token::BraceOpen => "{",
+            Token::BraceClose => "}",
+            Token::Text => return f.write_str("JSX text"),
+            Token::LiteralNumber => return f.write_str("number"),
+            Token::LiteralRegex => return f.write_str("regular expression"),
+            Token::PrivateIdentifier => return f.write_str("private name"),
+            Token::TemplateString => return f.write_str("template"),
+            Token::TemplateHead => return f.write_str("template"),
+            Token::TemplateMiddle => return f.write_str("template"),
+            Token::TemplateTail => return f.write_str("template"),
+            Token::ParenOpen => "(",
+            Token::ParenClose => ")",
+            Token::BracketOpen => "[",
+            Token::BracketClose => "]",
+            Token::Semicolon => ";",
+            Token::Question => "?",
+            Token::OptionalChain => "?.",
+            Token::Arrow => "=>",
+            Token::Add => "+",
+            Token::Sub => "-",
+            Token::Mul => "*",
+            Token::Div => "/",
+            Token::Rem => "%",
+            Token::Exp => "**",
+            Token::Increment => "++",
+            Token::Decrement => "--",
+            Token::Less => "<",
+            Token::Greater => ">",
+            Token::LessEqual => "<=",
+            Token::GreaterEqual => ">=",
+            Token::Equal => "==",
+            Token::NotEqual => "!=",
+            Token::StrictEqual => "===",
+            Token::StrictNotEqual => "!==",
+            Token::ShiftLeft => "<<",
+            Token::ShiftRight => ">>",
+            Token::UnsignedShiftRight => ">>>",
+            Token::BitAnd => "&",
+            Token::BitOr => "|",
+            Token::BitXor => "^",
+            Token::BitNot => "~",
+            Token::Not => "!",
+            Token::And => "&&",
+            Token::Or => "||",
+            Token::Nullish => "??",
+            Token::AddAssign => "+=",
+            Token::SubAssign => "-=",
+            Token::MulAssign => "*=",
+            Token::DivAssign => "/=",
+            Token::RemAssign => "%=",
+            Token::ExpAssign => "**=",
+            Token::ShiftLeftAssign => "<<=",
+            Token::ShiftRightAssign => ">>=",
+            Token::UnsignedShiftRightAssign => ">>>=",
+            Token::BitAndAssign => "&=",
+            Token::BitOrAssign => "|=",
+            Token::BitXorAssign => "^=",
+            Token::AndAssign => "&&=",
+            Token::OrAssign => "||=",
+            Token::NullishAssign => "??=",
+        };
+
+        write!(f, "`{}`", text)
+    }
+}
+
+/// A token with its span and source text, what iterating a `Lexer` yields.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SpannedToken<'a> {
+    pub kind: Token,
+    pub start: usize,
+    pub end: usize,
+    pub text: &'a str,
 }
 
+/// State of a `Lexer` saved by `checkpoint`, `rewind` goes back to it.
+#[derive(Clone)]
+pub struct Checkpoint<'a>(Lexer<'a>);
+
+/// Lexes ECMAScript tokens. As an `Iterator` it yields the tokens of the
+/// whole source, outside of JSX, and ends at the end of the program; the
+/// parser drives it token by token with `consume` and friends.
+#[derive(Clone)]
 pub struct Lexer<'a> {
-    code: &'a [char],
+    code: &'a str,
     /// Current `Token` from the source.
-    pub token: Token,
-    /// Current index
+    pub(crate) token: Token,
+    /// A line terminator was skipped before the current `Token`.
+    pub(crate) newline_before: bool,
+    /// `Token` before the current one, `regex_allowed` looks two tokens back.
+    prev_token: Token,
+    /// Current byte index, always on a char boundary.
     index: usize,
     /// Position of current token in source
     token_start: usize,
+    /// Where the whitespace and comments before the current token start.
+    trivia_start: usize,
     max_index: usize,
 
+    /// Position of the current token, see `track_position`.
     line: usize,
     column: usize,
+    tracked: usize,
+
+    /// Braces open in every template substitution the iterator is in, the
+    /// `}` closing the substitution continues the template.
+    template_braces: Vec<usize>,
 }
 
 impl<'a> Lexer<'a> {
-    pub fn new(code: &'a [char]) -> Self {
-        let token = if code.len() == 0 {
+    pub fn new(code: &'a str) -> Self {
+        let token = if code.is_empty() {
             Token::EndOfProgram
         } else {
             Token::UnexpectedToken
@@ -66,59 +262,250 @@ impl<'a> Lexer<'a> {
 
         let code_len = code.len();
         Lexer {
-            code: code,
-            token: token,
+            code,
+            token,
+            newline_before: false,
+            prev_token: Token::UnexpectedToken,
             index: 0,
             token_start: 0,
-            max_index: if code_len == 0 { 0 } else { code_len - 1 },
+            trivia_start: 0,
+            max_index: code_len,
             line: 0,
             column: 0,
+            tracked: 0,
+            template_braces: Vec::new(),
         }
     }
 
     #[inline]
-    pub fn start(&self) -> usize {
+    pub(crate) fn start(&self) -> usize {
         self.token_start
     }
 
     #[inline]
-    pub fn end(&self) -> usize {
+    pub(crate) fn end(&self) -> usize {
         self.index
     }
 
     #[inline]
-    pub fn loc(&self) -> (usize, usize) {
+    pub(crate) fn loc(&self) -> (usize, usize) {
         (self.start(), self.end())
     }
 
+    /// Moves to the next char, failing if that runs past the end of the source.
     #[inline]
     fn bump(&mut self) -> Result<(), Error> {
+        self.skip_char();
         if self.index < self.max_index {
-            self.index += 1;
             Ok(())
         } else {
             Err(Error::EndOfProgram)
         }
     }
 
+    /// Steps over the last char of a token, the token may end the source.
+    #[inline]
+    fn advance(&mut self) -> Result<(), Error> {
+        self.index += 1;
+        Ok(())
+    }
+
+    /// Moves back (or forward) to `index`, the next `consume` lexes from there.
+    #[inline]
+    pub(crate) fn reset(&mut self, index: usize) {
+        self.index = index;
+        self.token_start = index;
+        self.trivia_start = index;
+        self.token = Token::UnexpectedToken;
+        self.prev_token = Token::UnexpectedToken;
+        self.template_braces.clear();
+        self.track_position();
+    }
+
     #[inline]
     fn read_char(&self) -> char {
-        self.code[self.index]
+        self.code[self.index..].chars().next().unwrap_or('\0')
     }
 
+    /// Steps over the current char, whatever its UTF-8 length.
     #[inline]
-    fn next_char(&mut self) -> Result<char, Error> {
-        self.bump()?;
-        Ok(self.read_char())
+    fn skip_char(&mut self) {
+        self.index += self.read_char().len_utf8();
+    }
+
+    /// Steps over `\` and the char it escapes, `\` and `\r\n` continue a line.
+    #[inline]
+    fn skip_escape(&mut self) {
+        self.index += 1;
+        if self.index < self.max_index {
+            if self.read_char() == '\r' && self.peek_char(1) == Some('\n') {
+                self.index += 1;
+            }
+            self.skip_char();
+        }
+    }
+
+    /// ASCII char `offset` bytes ahead, lookahead only ever looks for punctuators and digits.
+    #[inline]
+    fn peek_char(&self, offset: usize) -> Option<char> {
+        self.code.as_bytes().get(self.index + offset)
+            .filter(|b| b.is_ascii())
+            .map(|b| *b as char)
+    }
+
+    #[inline]
+    fn end_of_program(&mut self) -> Result<(), Error> {
+        self.token = Token::EndOfProgram;
+        self.token_start = self.max_index;
+        self.index = self.max_index;
+        Err(Error::EndOfProgram)
+    }
+
+    /// Lexes the next ECMAScript token.
+    #[inline]
+    pub(crate) fn consume(&mut self) -> Result<(), Error> {
+        let ret = self.lex();
+        self.track_position();
+        ret
+    }
+
+    /// Lexes the next token inside a JSX tag (`<App name="str" {...props}>`).
+    #[inline]
+    pub(crate) fn consume_jsx(&mut self) -> Result<(), Error> {
+        let ret = self.lex_jsx();
+        self.track_position();
+        ret
+    }
+
+    /// Lexes the next JSX child: a run of text, `{`, `<`, `</`, `<>` or `</>`.
+    #[inline]
+    pub(crate) fn consume_jsx_text(&mut self) -> Result<(), Error> {
+        let ret = self.lex_jsx_text();
+        self.track_position();
+        ret
+    }
+
+    /// Lexes the rest of a template after the `}` closing a substitution,
+    /// the current token must be that `BraceClose`.
+    #[inline]
+    pub(crate) fn consume_template(&mut self) -> Result<(), Error> {
+        let ret = self.lex_template();
+        self.track_position();
+        ret
+    }
+
+    /// Token after the current one, see `peek_nth`.
+    #[inline]
+    pub fn peek(&self) -> Option<Result<SpannedToken<'a>, LexError>> {
+        self.peek_nth(0)
+    }
+
+    /// `n`th token the iterator yields next, without moving the lexer.
+    pub fn peek_nth(&self, n: usize) -> Option<Result<SpannedToken<'a>, LexError>> {
+        self.clone().nth(n)
+    }
+
+    /// Saves the state of the lexer, to try lexing ahead and `rewind`.
+    #[inline]
+    pub fn checkpoint(&self) -> Checkpoint<'a> {
+        Checkpoint(self.clone())
+    }
+
+    /// Goes back to the state saved by `checkpoint`, the tokens lexed since
+    /// are lexed again.
+    #[inline]
+    pub fn rewind(&mut self, checkpoint: Checkpoint<'a>) {
+        *self = checkpoint.0;
+    }
+
+    /// The current token, its span and source text.
+    #[inline]
+    pub fn spanned_token(&self) -> SpannedToken<'a> {
+        SpannedToken {
+            kind: self.token,
+            start: self.token_start,
+            end: self.index,
+            text: self.slice_source(self.token_start, self.index),
+        }
+    }
+
+    /// Moves `line` and `column` to the start of the current token,
+    /// walking from the last tracked position, backwards after a `reset`.
+    fn track_position(&mut self) {
+        let code = self.code;
+        let target = self.token_start.min(self.max_index);
+
+        if self.tracked <= target {
+            for (offset, ch) in code[self.tracked..target].char_indices() {
+                if is_line_break(code, self.tracked + offset, ch) {
+                    self.line += 1;
+                    self.column = 0;
+                } else {
+                    self.column += ch.len_utf16();
+                }
+            }
+        } else {
+            self.line -= code[target..self.tracked].char_indices()
+                .filter(|&(offset, ch)| is_line_break(code, target + offset, ch))
+                .count();
+            let line_start = code[..target].char_indices()
+                .rev()
+                .find(|&(_, ch)| is_line_terminator(ch))
+                .map_or(0, |(index, ch)| index + ch.len_utf8());
+            self.column = code[line_start..target].encode_utf16().count();
+        }
+
+        self.tracked = target;
     }
 
+    /// 0-based line of the current token.
     #[inline]
-    pub fn consume(&mut self) -> Result<(), Error> {
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// 0-based column of the current token in UTF-16 code units, like the
+    /// columns of `LineIndex`.
+    #[inline]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Comments between the previous token and the current one.
+    pub(crate) fn leading_comments(&self) -> Vec<Loc<Comment>> {
+        let trivia = self.code.get(self.trivia_start..self.token_start).unwrap_or("");
+        let mut comments = Vec::new();
+
+        let mut offset = 0;
+        while let Some(index) = trivia[offset..].find('/') {
+            let start = offset + index;
+            let rest = &trivia[start..];
+            let (comment, len) = if rest.starts_with("//") {
+                (Comment::Line, rest.find(is_line_terminator).unwrap_or(rest.len()))
+            } else if let Some(body) = rest.strip_prefix("/*") {
+                (Comment::Block, body.find("*/").map_or(rest.len(), |end| end + 4))
+            } else {
+                offset = start + 1;
+                continue;
+            };
+
+            let start = self.trivia_start + start;
+            comments.push(Loc::new(start, start + len, comment));
+            offset = start - self.trivia_start + len;
+        }
+
+        comments
+    }
+
+    #[inline]
+    fn lex_jsx(&mut self) -> Result<(), Error> {
+        self.trivia_start = self.index;
+
         // UnicodeXID::is_xid_start
         // UnicodeXID::is_xid_continue
         loop {
-            if self.token == Token::EndOfProgram {
-                return Err(Error::EndOfProgram);
+            if self.index >= self.max_index {
+                return self.end_of_program();
             }
 
             let ch = self.read_char();
@@ -134,6 +521,12 @@ impl<'a> Lexer<'a> {
                             ' ' => {
                                 self.bump().map_err(|_| Error::UnexpectedEndOfProgram)?;
                             },
+                            // `< /* comment */ div>`
+                            '/' if matches!(self.peek_char(1), Some('/') | Some('*')) => {
+                                self.token = Token::ElementOpen;
+                                self.index = index0;
+                                return Ok(());
+                            },
                             '/' => {
                                 self.token = Token::ClosingElementOpen;
                                 self.bump().map_err(|_| Error::UnexpectedEndOfProgram)?;
@@ -147,7 +540,7 @@ impl<'a> Lexer<'a> {
                                         },
                                         '>' => {
                                             self.token = Token::FragmentClose;
-                                            return self.bump();
+                                            return self.advance();
                                         },
                                         _ => {
                                             self.index = index2;
@@ -158,7 +551,7 @@ impl<'a> Lexer<'a> {
                             },
                             '>' => {
                                 self.token = Token::FragmentOpen;
-                                return self.bump().map_err(|_| Error::UnexpectedEndOfProgram);
+                                return self.advance();
                             },
                             _ => {
                                 self.token = Token::ElementOpen;
@@ -168,6 +561,12 @@ impl<'a> Lexer<'a> {
                         }
                     }
                 },
+                '/' if self.peek_char(1) == Some('/') => {
+                    self.skip_line_comment();
+                },
+                '/' if self.peek_char(1) == Some('*') => {
+                    self.skip_block_comment()?;
+                },
                 '/' => {
                     let token_start = self.token_start;
 
@@ -182,7 +581,7 @@ impl<'a> Lexer<'a> {
                             },
                             '>' => {
                                 self.token = Token::SelfClosingElementClose;
-                                return self.bump();
+                                return self.advance();
                             },
                             _ => {
                                 // self.token = Token::UnexpectedToken;
@@ -197,33 +596,22 @@ impl<'a> Lexer<'a> {
                     self.token_start = self.index;
                     self.token = Token::ElementClose;
 
-                    self.bump()?;
-                    
-                    return Ok(());
+                    return self.advance();
                 },
                 '=' => {
                     self.token_start = self.index;
                     self.token = Token::Assign;
-                    return self.bump().map_err(|_| Error::UnexpectedEndOfProgram);
+                    return self.advance();
                 },
                 '"' | '\'' => {
                     self.token_start = self.index;
                     self.token = Token::LiteralString;
 
+                    // JSX strings have no escape sequences, `"C:\"` is a whole string.
                     loop {
                         self.bump().map_err(|_| Error::UnexpectedEndOfProgram)?;
-                        let c = self.read_char();
-                        match c {
-                            '\\' => {
-                                continue;
-                            },
-                            _ => {
-                                if c == ch {
-                                    return self.bump();
-                                } else {
-                                    continue;
-                                }
-                            }
+                        if self.read_char() == ch {
+                            return self.advance();
                         }
                     }
                 },
@@ -233,17 +621,17 @@ impl<'a> Lexer<'a> {
                     // { JSXChildExpression }       JSXChild
                     self.token_start = self.index;
                     self.token = Token::BraceOpen;
-                    return self.bump().map_err(|_| Error::UnexpectedEndOfProgram);
+                    return self.advance();
                 },
                 '}' => {
                     self.token_start = self.index;
                     self.token = Token::BraceClose;
-                    return self.bump();
+                    return self.advance();
                 },
                 ',' => {
                     self.token_start = self.index;
                     self.token = Token::Comma;
-                    return self.bump().map_err(|_| Error::UnexpectedEndOfProgram);
+                    return self.advance();
                 },
                 '.' => {
                     self.token_start = self.index;
@@ -257,7 +645,7 @@ impl<'a> Lexer<'a> {
                         self.bump().map_err(|_| Error::UnexpectedEndOfProgram)?;
                         if self.read_char() == '.' {
                             self.token = Token::Spread;
-                            return self.bump().map_err(|_| Error::UnexpectedEndOfProgram);
+                            return self.advance();
                         } else {
                             self.token = Token::UnexpectedToken;
                             return Err(Error::UnexpectedEndOfProgram);
@@ -271,57 +659,697 @@ impl<'a> Lexer<'a> {
                     self.token_start = self.index;
                     self.token = Token::Colon;
 
-                    return self.bump().map_err(|_| Error::UnexpectedEndOfProgram);
+                    return self.advance();
                 },
                 _ => {
-                    if UnicodeXID::is_xid_start(ch) {
+                    // JSX identifiers take `-` after the first char, `aria-label`.
+                    if is_identifier_start(ch) {
                         self.token_start = self.index;
                         self.token = Token::Identifier;
                         loop {
-                            let c = self.next_char()?;
-                            if !UnicodeXID::is_xid_continue(c) {
+                            self.skip_char();
+                            if self.index >= self.max_index {
                                 return Ok(());
                             }
+                            match self.read_char() {
+                                '-' | '$' | '\u{200c}' | '\u{200d}' => {},
+                                c if UnicodeXID::is_xid_continue(c) => {},
+                                _ => return Ok(()),
+                            }
                         }
+                    } else if ch.is_whitespace() || ch == '\u{feff}' {
+                        self.skip_char();
                     } else {
-                        // Ignore
-                        self.bump()?;
+                        // `@click`, `#id` and `123` are no attribute names.
+                        self.token_start = self.index;
+                        self.token = Token::UnexpectedToken;
+                        return Err(Error::UnexpectedToken);
+                    }
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn lex_jsx_text(&mut self) -> Result<(), Error> {
+        self.trivia_start = self.index;
+        if self.index >= self.max_index {
+            return self.end_of_program();
+        }
+
+        match self.read_char() {
+            '<' | '{' => self.lex_jsx(),
+            // `>` and `}` are not allowed in text, like in Babel, `{'>'}` is.
+            '>' | '}' => {
+                self.token_start = self.index;
+                self.token = Token::UnexpectedToken;
+                Err(Error::UnexpectedToken)
+            },
+            _ => {
+                self.token_start = self.index;
+                self.token = Token::Text;
+                while self.index < self.max_index {
+                    match self.read_char() {
+                        '<' | '{' | '>' | '}' => break,
+                        _ => self.skip_char(),
                     }
                 }
+                Ok(())
             }
         }
     }
 
     #[inline]
-    pub fn slice_source(&self, start: usize, end: usize) -> &[char] {
-        assert_eq!(end >= start, true);
-        &self.code[start..end]
+    fn lex_template(&mut self) -> Result<(), Error> {
+        self.trivia_start = self.index;
+        if self.token != Token::BraceClose {
+            return Err(Error::UnexpectedToken);
+        }
+
+        self.read_template(Token::TemplateMiddle, Token::TemplateTail)
     }
+
+    fn read_template(&mut self, substitution: Token, tail: Token) -> Result<(), Error> {
+        // self.index is right after the opening ` or }
+        loop {
+            if self.index >= self.max_index {
+                self.token = Token::UnexpectedToken;
+                return Err(Error::UnexpectedEndOfProgram);
+            }
+
+            match self.read_char() {
+                '\\' => {
+                    self.skip_escape();
+                },
+                '`' => {
+                    self.token = tail;
+                    return self.advance();
+                },
+                '$' if self.peek_char(1) == Some('{') => {
+                    self.token = substitution;
+                    self.index += 2;
+                    return Ok(());
+                },
+                _ => {
+                    self.skip_char();
+                },
+            }
+        }
+    }
+
+    #[inline]
+    fn punctuator(&mut self, token: Token, len: usize) -> Result<(), Error> {
+        self.token = token;
+        self.index += len;
+        Ok(())
+    }
+
+    fn lex(&mut self) -> Result<(), Error> {
+        let regex_allowed = self.regex_allowed();
+        self.prev_token = self.token;
+        self.newline_before = false;
+        self.trivia_start = self.index;
+
+        loop {
+            if self.index >= self.max_index {
+                return self.end_of_program();
+            }
+
+            let ch = self.read_char();
+            self.token_start = self.index;
+
+            let next = self.peek_char(1);
+            let next2 = self.peek_char(2);
+            let next3 = self.peek_char(3);
+
+            return match ch {
+                '\n' | '\r' | '\u{2028}' | '\u{2029}' => {
+                    self.newline_before = true;
+                    self.index += ch.len_utf8();
+                    continue;
+                },
+                '\u{feff}' => {
+                    self.index += ch.len_utf8();
+                    continue;
+                },
+                c if c.is_whitespace() => {
+                    self.index += c.len_utf8();
+                    continue;
+                },
+                '/' if next == Some('/') => {
+                    self.skip_line_comment();
+                    continue;
+                },
+                '/' if next == Some('*') => {
+                    self.skip_block_comment()?;
+                    continue;
+                },
+                // #!/usr/bin/env node
+                '#' if self.index == 0 && next == Some('!') => {
+                    self.skip_line_comment();
+                    continue;
+                },
+                '#' => {
+                    self.index += 1;
+                    if !self.read_identifier_name() {
+                        self.token = Token::UnexpectedToken;
+                        return Err(Error::UnexpectedToken);
+                    }
+                    self.token = Token::PrivateIdentifier;
+                    Ok(())
+                },
+                '{' => self.punctuator(Token::BraceOpen, 1),
+                '}' => self.punctuator(Token::BraceClose, 1),
+                '(' => self.punctuator(Token::ParenOpen, 1),
+                ')' => self.punctuator(Token::ParenClose, 1),
+                '[' => self.punctuator(Token::BracketOpen, 1),
+                ']' => self.punctuator(Token::BracketClose, 1),
+                ';' => self.punctuator(Token::Semicolon, 1),
+                ',' => self.punctuator(Token::Comma, 1),
+                ':' => self.punctuator(Token::Colon, 1),
+                '~' => self.punctuator(Token::BitNot, 1),
+                '?' => match (next, next2) {
+                    (Some('?'), Some('=')) => self.punctuator(Token::NullishAssign, 3),
+                    (Some('?'), _) => self.punctuator(Token::Nullish, 2),
+                    // `a?.5:b` is a conditional
+                    (Some('.'), Some(c)) if c.is_ascii_digit() => self.punctuator(Token::Question, 1),
+                    (Some('.'), _) => self.punctuator(Token::OptionalChain, 2),
+                    _ => self.punctuator(Token::Question, 1),
+                },
+                '.' => match (next, next2) {
+                    (Some(c), _) if c.is_ascii_digit() => self.read_number(),
+                    (Some('.'), Some('.')) => self.punctuator(Token::Spread, 3),
+                    _ => self.punctuator(Token::Dit, 1),
+                },
+                '<' => match (next, next2) {
+                    (Some('<'), Some('=')) => self.punctuator(Token::ShiftLeftAssign, 3),
+                    (Some('<'), _) => self.punctuator(Token::ShiftLeft, 2),
+                    (Some('='), _) => self.punctuator(Token::LessEqual, 2),
+                    _ => self.punctuator(Token::Less, 1),
+                },
+                '>' => match (next, next2, next3) {
+                    (Some('>'), Some('>'), Some('=')) => self.punctuator(Token::UnsignedShiftRightAssign, 4),
+                    (Some('>'), Some('>'), _) => self.punctuator(Token::UnsignedShiftRight, 3),
+                    (Some('>'), Some('='), _) => self.punctuator(Token::ShiftRightAssign, 3),
+                    (Some('>'), _, _) => self.punctuator(Token::ShiftRight, 2),
+                    (Some('='), _, _) => self.punctuator(Token::GreaterEqual, 2),
+                    _ => self.punctuator(Token::Greater, 1),
+                },
+                '=' => match (next, next2) {
+                    (Some('='), Some('=')) => self.punctuator(Token::StrictEqual, 3),
+                    (Some('='), _) => self.punctuator(Token::Equal, 2),
+                    (Some('>'), _) => self.punctuator(Token::Arrow, 2),
+                    _ => self.punctuator(Token::Assign, 1),
+                },
+                '!' => match (next, next2) {
+                    (Some('='), Some('=')) => self.punctuator(Token::StrictNotEqual, 3),
+                    (Some('='), _) => self.punctuator(Token::NotEqual, 2),
+                    _ => self.punctuator(Token::Not, 1),
+                },
+                '+' => match next {
+                    Some('+') => self.punctuator(Token::Increment, 2),
+                    Some('=') => self.punctuator(Token::AddAssign, 2),
+                    _ => self.punctuator(Token::Add, 1),
+                },
+                '-' => match next {
+                    Some('-') => self.punctuator(Token::Decrement, 2),
+                    Some('=') => self.punctuator(Token::SubAssign, 2),
+                    _ => self.punctuator(Token::Sub, 1),
+                },
+                '*' => match (next, next2) {
+                    (Some('*'), Some('=')) => self.punctuator(Token::ExpAssign, 3),
+                    (Some('*'), _) => self.punctuator(Token::Exp, 2),
+                    (Some('='), _) => self.punctuator(Token::MulAssign, 2),
+                    _ => self.punctuator(Token::Mul, 1),
+                },
+                '/' if regex_allowed => self.read_regex_or_div(),
+                '/' => self.read_div(),
+                '%' => match next {
+                    Some('=') => self.punctuator(Token::RemAssign, 2),
+                    _ => self.punctuator(Token::Rem, 1),
+                },
+                '&' => match (next, next2) {
+                    (Some('&'), Some('=')) => self.punctuator(Token::AndAssign, 3),
+                    (Some('&'), _) => self.punctuator(Token::And, 2),
+                    (Some('='), _) => self.punctuator(Token::BitAndAssign, 2),
+                    _ => self.punctuator(Token::BitAnd, 1),
+                },
+                '|' => match (next, next2) {
+                    (Some('|'), Some('=')) => self.punctuator(Token::OrAssign, 3),
+                    (Some('|'), _) => self.punctuator(Token::Or, 2),
+                    (Some('='), _) => self.punctuator(Token::BitOrAssign, 2),
+                    _ => self.punctuator(Token::BitOr, 1),
+                },
+                '^' => match next {
+                    Some('=') => self.punctuator(Token::BitXorAssign, 2),
+                    _ => self.punctuator(Token::BitXor, 1),
+                },
+                '"' | '\'' => self.read_string(ch),
+                '`' => {
+                    self.index += 1;
+                    self.read_template(Token::TemplateHead, Token::TemplateString)
+                },
+                '0'..='9' => self.read_number(),
+                c if is_identifier_start(c) => {
+                    self.token = Token::Identifier;
+                    self.read_identifier_name();
+                    Ok(())
+                },
+                _ => {
+                    self.token = Token::UnexpectedToken;
+                    Err(Error::UnexpectedToken)
+                },
+            };
+        }
+    }
+
+    /// A `/` after the current token starts a regex rather than a division,
+    /// the current token cannot end an expression.
+    ///
+    // return /a/ | x = /a/ | ) /a/ | a.return / 2
+    pub(crate) fn regex_allowed(&self) -> bool {
+        match self.token {
+            Token::Identifier => {
+                !matches!(self.prev_token, Token::Dit | Token::OptionalChain)
+                    && REGEX_KEYWORDS.iter().any(|keyword| self.token_text_eq(keyword))
+            },
+            Token::ParenClose | Token::BracketClose
+            | Token::LiteralString | Token::LiteralNumber | Token::LiteralRegex
+            | Token::TemplateString | Token::TemplateTail
+            | Token::PrivateIdentifier | Token::Increment | Token::Decrement
+            | Token::ElementClose | Token::SelfClosingElementClose | Token::FragmentClose => false,
+            _ => true,
+        }
+    }
+
+    /// Lexes the current `/` or `/=` token again, as a regex or as a division.
+    pub(crate) fn rescan_slash(&mut self, regex: bool) -> Result<(), Error> {
+        self.index = self.token_start;
+        let ret = if regex { self.read_regex() } else { self.read_div() };
+        self.track_position();
+        ret
+    }
+
+    #[inline]
+    fn read_div(&mut self) -> Result<(), Error> {
+        match self.peek_char(1) {
+            Some('=') => self.punctuator(Token::DivAssign, 2),
+            _ => self.punctuator(Token::Div, 1),
+        }
+    }
+
+    /// `regex_allowed` is a guess, a division when no regex ends on the line,
+    /// `function () {} / 2`.
+    fn read_regex_or_div(&mut self) -> Result<(), Error> {
+        if self.read_regex().is_ok() {
+            return Ok(());
+        }
+        self.index = self.token_start;
+        self.read_div()
+    }
+
+    // `/body/flags`, a `/` inside a class `[/]` does not end the body.
+    fn read_regex(&mut self) -> Result<(), Error> {
+        self.token = Token::LiteralRegex;
+        self.index += 1;
+
+        let mut in_class = false;
+        loop {
+            if self.index >= self.max_index {
+                self.token = Token::UnexpectedToken;
+                return Err(Error::UnexpectedEndOfProgram);
+            }
+
+            match self.read_char() {
+                '\\' => {
+                    self.skip_escape();
+                    continue;
+                },
+                c if is_line_terminator(c) => {
+                    self.token = Token::UnexpectedToken;
+                    return Err(Error::UnexpectedToken);
+                },
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '/' if !in_class => break,
+                _ => {},
+            }
+            self.skip_char();
+        }
+        self.index += 1;
+
+        // flags
+        while self.index < self.max_index && UnicodeXID::is_xid_continue(self.read_char()) {
+            self.skip_char();
+        }
+
+        Ok(())
+    }
+
+    /// Skips an identifier, false when none starts at the current char.
+    fn read_identifier_name(&mut self) -> bool {
+        if self.index >= self.max_index || !is_identifier_start(self.read_char()) {
+            return false;
+        }
+
+        self.skip_char();
+        while self.index < self.max_index {
+            let c = self.read_char();
+            if c == '$' || c == '\u{200c}' || c == '\u{200d}' || UnicodeXID::is_xid_continue(c) {
+                self.index += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+
+        true
+    }
+
+    // `// ...` up to the line terminator, which is left for `consume`.
+    fn skip_line_comment(&mut self) {
+        while self.index < self.max_index {
+            match self.read_char() {
+                '\n' | '\r' | '\u{2028}' | '\u{2029}' => break,
+                _ => self.skip_char(),
+            }
+        }
+    }
+
+    // `/* ... */`, a line terminator inside counts as one between the tokens.
+    fn skip_block_comment(&mut self) -> Result<(), Error> {
+        self.index += 2;
+
+        loop {
+            if self.index >= self.max_index {
+                self.token = Token::UnexpectedToken;
+                return Err(Error::UnexpectedEndOfProgram);
+            }
+
+            match self.read_char() {
+                '*' if self.peek_char(1) == Some('/') => {
+                    self.index += 2;
+                    return Ok(());
+                },
+                '\n' | '\r' | '\u{2028}' | '\u{2029}' => {
+                    self.newline_before = true;
+                    self.skip_char();
+                },
+                _ => self.skip_char(),
+            }
+        }
+    }
+
+    fn read_string(&mut self, quote: char) -> Result<(), Error> {
+        self.token = Token::LiteralString;
+        self.index += 1;
+
+        loop {
+            if self.index >= self.max_index {
+                self.token = Token::UnexpectedToken;
+                return Err(Error::UnexpectedEndOfProgram);
+            }
+
+            match self.read_char() {
+                '\\' => {
+                    // The escaped char never ends the string.
+                    self.skip_escape();
+                },
+                '\n' | '\r' => {
+                    self.token = Token::UnexpectedToken;
+                    return Err(Error::UnexpectedToken);
+                },
+                c => {
+                    self.index += c.len_utf8();
+                    if c == quote {
+                        return Ok(());
+                    }
+                },
+            }
+        }
+    }
+
+    fn read_number(&mut self) -> Result<(), Error> {
+        self.token = Token::LiteralNumber;
+
+        let radix = match (self.read_char(), self.peek_char(1)) {
+            ('0', Some('x')) | ('0', Some('X')) => 16,
+            ('0', Some('o')) | ('0', Some('O')) => 8,
+            ('0', Some('b')) | ('0', Some('B')) => 2,
+            _ => 10,
+        };
+
+        let mut is_integer = true;
+        if radix != 10 {
+            self.index += 2;
+            self.read_digits(radix);
+        } else {
+            self.read_digits(10);
+
+            if self.index < self.max_index && self.read_char() == '.' {
+                is_integer = false;
+                self.index += 1;
+                self.read_digits(10);
+            }
+
+            if self.index < self.max_index {
+                if let 'e' | 'E' = self.read_char() {
+                    let offset = match self.peek_char(1) {
+                        Some('+') | Some('-') => 2,
+                        _ => 1,
+                    };
+                    if self.peek_char(offset).map(|c| c.is_ascii_digit()).unwrap_or(false) {
+                        is_integer = false;
+                        self.index += offset;
+                        self.read_digits(10);
+                    }
+                }
+            }
+        }
+
+        // BigInt, 10n | 0xffn
+        if is_integer && self.index < self.max_index && self.read_char() == 'n' {
+            self.index += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Digits of `radix` and `_` separators, `1_000`.
+    #[inline]
+    fn read_digits(&mut self, radix: u32) {
+        while self.index < self.max_index {
+            match self.read_char() {
+                c if c == '_' || c.is_digit(radix) => self.index += 1,
+                _ => break,
+            }
+        }
+    }
+
+    /// The source text of the current token equals `text`.
+    #[inline]
+    pub(crate) fn token_text_eq(&self, text: &str) -> bool {
+        &self.code[self.token_start..self.index] == text
+    }
+
+    #[inline]
+    pub fn source(&self) -> &'a str {
+        self.code
+    }
+
+    /// Source text of `start..end`, empty when the range is not in the source.
+    #[inline]
+    pub(crate) fn slice_source(&self, start: usize, end: usize) -> &'a str {
+        self.code.get(start..end).unwrap_or("")
+    }
+}
+
+// "`a${b}c`" is `TemplateHead`, `Identifier` and `TemplateTail`, a `/`
+// after a token that ends no expression starts a regex, see `regex_allowed`.
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<SpannedToken<'a>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut ret = self.consume();
+        if ret.is_ok() && self.token == Token::BraceClose {
+            match self.template_braces.last_mut() {
+                // The `}` closing a substitution continues the template.
+                Some(&mut 0) => {
+                    self.template_braces.pop();
+                    ret = self.consume_template();
+                },
+                Some(braces) => *braces -= 1,
+                None => {},
+            }
+        }
+
+        match ret {
+            Ok(()) => {},
+            Err(Error::EndOfProgram) => return None,
+            Err(kind) => {
+                // The lexer stops before a char it cannot read, `@`, the
+                // next token starts after it.
+                let start = self.token_start;
+                if self.index == start {
+                    self.skip_char();
+                }
+                self.index = self.index.min(self.max_index);
+
+                return Some(Err(LexError { kind, start, end: self.index }));
+            },
+        }
+
+        match self.token {
+            Token::TemplateHead | Token::TemplateMiddle => self.template_braces.push(0),
+            Token::BraceOpen => {
+                if let Some(braces) = self.template_braces.last_mut() {
+                    *braces += 1;
+                }
+            },
+            _ => {},
+        }
+
+        Some(Ok(self.spanned_token()))
+    }
+}
+
+/// `\n`, `\r`, U+2028 and U+2029.
+#[inline]
+pub fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+/// `ch` at `index` ends a line, `\r\n` ends it at the `\n`.
+#[inline]
+fn is_line_break(code: &str, index: usize, ch: char) -> bool {
+    match ch {
+        '\r' => code.as_bytes().get(index + 1) != Some(&b'\n'),
+        _ => is_line_terminator(ch),
+    }
+}
+
+/// Keywords after which a `/` starts a regex.
+const REGEX_KEYWORDS: &[&str] = &[
+    "return", "typeof", "instanceof", "in", "of", "new", "delete", "void",
+    "throw", "case", "do", "else", "yield", "await",
+];
+
+#[inline]
+fn is_identifier_start(c: char) -> bool {
+    c == '$' || c == '_' || UnicodeXID::is_xid_start(c)
 }
 
 
-pub fn parse(source: &str) {
-    println!("Parse:\n--------------\n{}\n------------\n", source);
+#[cfg(test)]
+mod tests {
+    use crate::error::{ Error, LexError, };
 
-    let code = source.chars().collect::<Vec<char>>();
-    let mut lexer = Lexer::new(&code);
+    use super::{ Lexer, Token, };
+    use super::Token::*;
 
-    loop {
-        if let Err(e) = lexer.consume() {
-            break;
+    /// The ECMAScript tokens of `code` and their text, up to the end or the first error.
+    fn tokens(code: &str) -> Vec<(Token, &str)> {
+        let mut lexer = Lexer::new(code);
+        let mut tokens = Vec::new();
+        while lexer.consume().is_ok() && lexer.token != EndOfProgram {
+            let (start, end) = lexer.loc();
+            tokens.push((lexer.token, &code[start..end]));
         }
+        tokens
+    }
+
+    #[test]
+    fn regex_or_division() {
+        assert_eq!(tokens("x = /ab+c/gi;"), [(Identifier, "x"), (Assign, "="), (LiteralRegex, "/ab+c/gi"), (Semicolon, ";")]);
+        assert_eq!(tokens("a / b /= c"), [(Identifier, "a"), (Div, "/"), (Identifier, "b"), (DivAssign, "/="), (Identifier, "c")]);
+        assert_eq!(tokens("(x) / 2"), [(ParenOpen, "("), (Identifier, "x"), (ParenClose, ")"), (Div, "/"), (LiteralNumber, "2")]);
+        assert_eq!(tokens("return /[/]\\//"), [(Identifier, "return"), (LiteralRegex, "/[/]\\//")]);
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(tokens("1_000 0xffn 1e-3 .5 0b1010n 10n"), [
+            (LiteralNumber, "1_000"),
+            (LiteralNumber, "0xffn"),
+            (LiteralNumber, "1e-3"),
+            (LiteralNumber, ".5"),
+            (LiteralNumber, "0b1010n"),
+            (LiteralNumber, "10n"),
+        ]);
+        assert_eq!(tokens("1..toString"), [(LiteralNumber, "1."), (Dit, "."), (Identifier, "toString")]);
+    }
+
+    #[test]
+    fn line_continuations() {
+        assert_eq!(tokens("'a\\\nb' \"c\\\r\nd\""), [(LiteralString, "'a\\\nb'"), (LiteralString, "\"c\\\r\nd\"")]);
+    }
+
+    #[test]
+    fn templates() {
+        assert_eq!(tokens("`a\\`b`"), [(TemplateString, "`a\\`b`")]);
+
+        let code = "`a${b}c${d}e`";
+        let mut lexer = Lexer::new(code);
+        let mut next = |template: bool| {
+            let _ = if template { lexer.consume_template() } else { lexer.consume() };
+            let (start, end) = lexer.loc();
+            (lexer.token, &code[start..end])
+        };
+        assert_eq!(next(false), (TemplateHead, "`a${"));
+        assert_eq!(next(false), (Identifier, "b"));
+        assert_eq!(next(false), (BraceClose, "}"));
+        assert_eq!(next(true), (TemplateMiddle, "}c${"));
+        assert_eq!(next(false), (Identifier, "d"));
+        assert_eq!(next(false), (BraceClose, "}"));
+        assert_eq!(next(true), (TemplateTail, "}e`"));
+    }
 
-        assert_eq!(lexer.token != Token::UnexpectedToken, true);
-        assert_eq!(lexer.token != Token::EndOfProgram, true);
+    #[test]
+    fn iterate_templates() {
+        let tokens: Vec<_> = Lexer::new("`a${ {b} }c` / 2").map(|token| token.map(|token| (token.kind, token.text))).collect();
+        assert_eq!(tokens, [
+            Ok((TemplateHead, "`a${")),
+            Ok((BraceOpen, "{")),
+            Ok((Identifier, "b")),
+            Ok((BraceClose, "}")),
+            Ok((TemplateTail, "}c`")),
+            Ok((Div, "/")),
+            Ok((LiteralNumber, "2")),
+        ]);
+    }
 
-        let loc = lexer.loc();
-        let (start, end) = loc;
-        println!("token: {:?} Postion: {:?} Text: {:?}", lexer.token, loc, &source[start..end]);
+    #[test]
+    fn iterate_past_errors() {
+        let tokens: Vec<_> = Lexer::new("a @ b").map(|token| token.map(|token| token.text)).collect();
+        assert_eq!(tokens, [Ok("a"), Err(LexError { kind: Error::UnexpectedToken, start: 2, end: 3 }), Ok("b")]);
     }
 
-    let loc = lexer.loc();
-    let (start, end) = loc;
-    println!("latest token: {:?} Postion: {:?} Text: {:?}", lexer.token, loc, &source[start..end]);
+    #[test]
+    fn peek_and_rewind() {
+        let mut lexer = Lexer::new("a + b");
+        assert_eq!(lexer.peek_nth(2).unwrap().map(|token| (token.text, token.start)), Ok(("b", 4)));
+        assert_eq!(lexer.peek_nth(3), None);
+        assert_eq!(lexer.next().unwrap().map(|token| token.text), Ok("a"));
 
-    println!("> end!");
+        let checkpoint = lexer.checkpoint();
+        assert_eq!(lexer.nth(1).unwrap().map(|token| token.text), Ok("b"));
+        assert_eq!(lexer.next(), None);
+
+        lexer.rewind(checkpoint);
+        assert_eq!(lexer.peek().unwrap().map(|token| token.kind), Ok(Add));
+        assert_eq!(lexer.next().unwrap().map(|token| (token.kind, token.start, token.end)), Ok((Add, 2, 3)));
+    }
+
+    #[test]
+    fn utf16_columns() {
+        let mut lexer = Lexer::new("\"\u{1f600}\" x\r\n  y");
+        let mut positions = Vec::new();
+        while let Some(Ok(token)) = lexer.next() {
+            positions.push((token.text, lexer.line(), lexer.column()));
+        }
+        assert_eq!(positions, [("\"\u{1f600}\"", 0, 0), ("x", 0, 5), ("y", 1, 2)]);
+
+        lexer.reset(7);
+        assert!(lexer.consume().is_ok());
+        assert_eq!((lexer.line(), lexer.column()), (0, 5));
+    }
 }
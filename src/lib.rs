@@ -0,0 +1,83 @@
+//! JSX parser and compiler.
+//!
+//! `parse` reads a `.jsx` or `.tsx` module into a `Program` allocated in an
+//! `Arena`, its names and text borrow the source. `transform` compiles its JSX
+//! to `React.createElement` calls or the automatic runtime.
+//! `parse_recovering` and `tokenize` go on after syntax errors, `to_json`
+//! prints a `Program` as Babel compatible ESTree JSON. `LineIndex` maps the
+//! byte offsets of tokens and nodes to lines and columns.
+//! The public modules are the AST and its tools: `ast` and `list` for its
+//! nodes, `visit` and `fold` to walk and rewrite it, `lex` for the tokens.
+
+extern crate unicode_xid;
+
+
+pub mod lex;
+pub mod ast;
+pub mod list;
+pub mod visit;
+pub mod fold;
+mod error;
+mod parser;
+mod transform;
+mod source_map;
+mod entity;
+mod estree;
+
+pub use crate::ast::{ Loc, Program, };
+pub use crate::lex::{ Lexer, Token, SpannedToken, Checkpoint, };
+pub use crate::error::{ Error, LexError, ParseError, Diagnostics, };
+pub use crate::parser::{ Syntax, ParseOptions, };
+pub use crate::transform::{ Runtime, TransformOptions, Output, write_string_literal, };
+pub use crate::source_map::{ SourceMap, LineIndex, };
+pub use crate::estree::to_json;
+pub use crate::fold::Fold;
+pub use toolshed::Arena;
+
+use crate::parser::Parser;
+
+
+/// Parses `source` as a module into `arena`, reporting every syntax error
+/// instead of stopping at the first.
+pub fn parse<'ast>(source: &'ast str, arena: &'ast Arena, options: ParseOptions) -> Result<Program<'ast>, Diagnostics> {
+    let (program, errors) = parse_recovering(source, arena, options);
+    if !errors.is_empty() {
+        return Err(Diagnostics { errors });
+    }
+
+    Ok(program)
+}
+
+/// Like `parse`, also returns the program when there are errors, with `Error`
+/// nodes for the source the parser skipped.
+pub fn parse_recovering<'ast>(source: &'ast str, arena: &'ast Arena, options: ParseOptions) -> (Program<'ast>, Vec<ParseError>) {
+    Parser::with_syntax(source, arena, options.syntax).parse_recovering()
+}
+
+/// Every token of `source`, lexed in the mode the parser reads it in: JSX
+/// text, tags and template parts included, comments and whitespace left out.
+/// The source skipped after an error has no tokens.
+pub fn tokenize(source: &str, options: ParseOptions) -> (Vec<Loc<Token>>, Vec<ParseError>) {
+    Parser::with_syntax(source, &Arena::new(), options.syntax).tokenize()
+}
+
+/// Compiles the JSX of `source` for `options.runtime`, stripping its types
+/// with `Syntax::Tsx`. `@jsx`, `@jsxFrag`, `@jsxRuntime` and `@jsxImportSource`
+/// comments in the source take precedence over `options`.
+pub fn transform(source: &str, options: TransformOptions) -> Result<Output, Diagnostics> {
+    transform_with_folds(source, &Arena::new(), options, &mut [])
+}
+
+/// `transform` with custom rewrites, `folds` run in order over the program
+/// parsed into `arena` before its JSX is compiled. See `fold::Fold`.
+pub fn transform_with_folds<'ast>(
+    source: &'ast str,
+    arena: &'ast Arena,
+    options: TransformOptions,
+    folds: &mut [&mut dyn Fold<'ast>],
+) -> Result<Output, Diagnostics> {
+    let mut code = String::new();
+    let source_map = transform::transform_with_folds(source, arena, &mut code, &options, folds)?;
+
+    Ok(Output { code, source_map })
+}
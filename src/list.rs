@@ -0,0 +1,202 @@
+use toolshed::Arena;
+
+use std::cell::Cell;
+use std::fmt::{ self, Debug, };
+use std::marker::PhantomData;
+use std::ptr;
+
+
+// `toolshed::list` links its nodes through `CopyCell`, which writes through
+// shared references. Release builds assume a `GrowableList` never changes
+// and parse every list as empty, so the AST links its own nodes. A node is
+// only written through the pointer it was allocated with, while it is the
+// last node of its builder.
+
+#[derive(Clone, Copy)]
+struct ListNode<T> {
+    value: T,
+    next: *const ListNode<T>,
+}
+
+/// A singly linked list allocated in an `Arena`. Lists are `Copy` and never
+/// change once built, see `GrowableList`.
+#[derive(Clone, Copy)]
+pub struct List<'arena, T> {
+    first: *const ListNode<T>,
+    marker: PhantomData<&'arena T>,
+}
+
+impl<'arena, T> List<'arena, T> {
+    pub const fn empty() -> Self {
+        List { first: ptr::null(), marker: PhantomData }
+    }
+
+    #[inline]
+    pub fn iter(&self) -> ListIter<'arena, T> {
+        ListIter { next: self.first, marker: PhantomData }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.first.is_null()
+    }
+
+    #[inline]
+    pub fn first_element(&self) -> Option<&'arena T> {
+        self.iter().next()
+    }
+
+    /// The element of a list of one element.
+    pub fn only_element(&self) -> Option<&'arena T> {
+        let mut iter = self.iter();
+        match (iter.next(), iter.next()) {
+            (Some(value), None) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Takes the first element, the list goes on from the second.
+    pub fn shift_ref(&mut self) -> Option<&'arena T> {
+        let mut iter = self.iter();
+        let value = iter.next()?;
+        self.first = iter.next;
+
+        Some(value)
+    }
+}
+
+impl<'arena, T: Copy> List<'arena, T> {
+    pub fn from(arena: &'arena Arena, value: T) -> Self {
+        let list = GrowableList::new();
+        list.push(arena, value);
+        list.as_list()
+    }
+
+    pub fn from_iter<I: IntoIterator<Item = T>>(arena: &'arena Arena, source: I) -> Self {
+        let list = GrowableList::new();
+        for value in source {
+            list.push(arena, value);
+        }
+        list.as_list()
+    }
+}
+
+impl<'arena, T: Debug> Debug for List<'arena, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'arena, T: PartialEq> PartialEq for List<'arena, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'arena, T: serde::Serialize> serde::Serialize for List<'arena, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'arena, T> IntoIterator for List<'arena, T> {
+    type Item = &'arena T;
+    type IntoIter = ListIter<'arena, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'arena, T> IntoIterator for &List<'arena, T> {
+    type Item = &'arena T;
+    type IntoIter = ListIter<'arena, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct ListIter<'arena, T> {
+    next: *const ListNode<T>,
+    marker: PhantomData<&'arena T>,
+}
+
+impl<'arena, T> Iterator for ListIter<'arena, T> {
+    type Item = &'arena T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'arena T> {
+        // Nodes live as long as the arena, only `next` of a last node changes.
+        let node = unsafe { self.next.as_ref() }?;
+        self.next = node.next;
+
+        Some(&node.value)
+    }
+}
+
+/// Builds a `List` by pushing to its end.
+pub struct GrowableList<'arena, T> {
+    first: Cell<*const ListNode<T>>,
+    last: Cell<*mut ListNode<T>>,
+    marker: PhantomData<&'arena T>,
+}
+
+impl<'arena, T: Copy> GrowableList<'arena, T> {
+    pub const fn new() -> Self {
+        GrowableList { first: Cell::new(ptr::null()), last: Cell::new(ptr::null_mut()), marker: PhantomData }
+    }
+
+    pub fn push(&self, arena: &'arena Arena, value: T) {
+        let node: *mut ListNode<T> = arena.alloc(ListNode { value, next: ptr::null() });
+        let last = self.last.get();
+        if last.is_null() {
+            self.first.set(node);
+        } else {
+            // Writes `next` alone, references to `value` stay valid.
+            unsafe { (*last).next = node };
+        }
+        self.last.set(node);
+    }
+
+    /// The items pushed so far.
+    #[inline]
+    pub fn as_list(&self) -> List<'arena, T> {
+        List { first: self.first.get(), marker: PhantomData }
+    }
+}
+
+impl<'arena, T: Copy> Default for GrowableList<'arena, T> {
+    fn default() -> Self {
+        GrowableList::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use toolshed::Arena;
+
+    use super::{ List, GrowableList, };
+
+    #[test]
+    fn push_and_shift() {
+        let arena = Arena::new();
+        let list = GrowableList::new();
+        assert!(list.as_list().is_empty());
+        list.push(&arena, 1);
+        assert_eq!(list.as_list().only_element(), Some(&1));
+        list.push(&arena, 2);
+        list.push(&arena, 3);
+
+        let mut rest = list.as_list();
+        assert_eq!(rest.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(rest.shift_ref(), Some(&1));
+        assert_eq!(rest, List::from_iter(&arena, vec![2, 3]));
+        assert_eq!(rest.only_element(), None);
+        assert_eq!(format!("{:?}", List::from(&arena, 4)), "[4]");
+    }
+}
@@ -1,50 +1,485 @@
-#![allow(unused_imports, unused_variables, unused_mut, unreachable_code, 
-    unused_assignments, dead_code)]
+extern crate jsx;
+extern crate clap;
+extern crate glob;
 
+use jsx::{ Arena, LineIndex, ParseError, Syntax, ParseOptions, Runtime, TransformOptions, write_string_literal, };
 
-extern crate unicode_xid;
+use clap::{ App, AppSettings, Arg, ArgMatches, SubCommand, };
 
-use unicode_xid::UnicodeXID;
+use std::fmt;
+use std::fs;
+use std::io::{ self, Read, Write, };
+use std::path::{ Path, PathBuf, Component, };
+use std::process;
 
 
-pub mod error;
-pub mod lex;
-pub mod ast;
-pub mod parser;
-pub mod transform;
+// https://facebook.github.io/jsx/
 
+// jsx transform 'src/**/*.jsx' --out-dir dist
+// jsx check 'src/**/*.jsx'
+// cat app.jsx | jsx parse -
 
-use crate::error::Error;
+/// Exit code when an input has syntax errors.
+const EXIT_SYNTAX_ERROR: i32 = 1;
+/// Exit code of bad arguments and files that cannot be read or written.
+const EXIT_FAILURE: i32 = 2;
 
 
-use std::io::{ self, Read, Seek, SeekFrom, Cursor, };
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Command {
+    Tokenize,
+    Parse,
+    Check,
+    Transform,
+}
 
+impl Command {
+    /// Extension of the files written to `--out-dir`.
+    fn extension(self) -> &'static str {
+        match self {
+            Command::Tokenize => "tokens",
+            Command::Parse => "json",
+            Command::Check => "",
+            Command::Transform => "js",
+        }
+    }
+}
 
-// https://facebook.github.io/jsx/
+/// A file, or stdin when `path` is `None`.
+struct Input {
+    path: Option<PathBuf>,
+    /// Directory the glob matching `path` starts at, the path below it is
+    /// kept in `--out-dir`.
+    base: PathBuf,
+}
+
+impl Input {
+    fn read(&self) -> io::Result<String> {
+        match self.path {
+            Some(ref path) => fs::read_to_string(path),
+            None => {
+                let mut code = String::new();
+                io::stdin().read_to_string(&mut code)?;
+                Ok(code)
+            },
+        }
+    }
+
+    /// `.tsx` files are parsed with TypeScript, `.ts` files are rejected
+    /// since TSX reads a `<T>value` type assertion as an element.
+    fn syntax(&self) -> Result<Syntax, String> {
+        match self.path.as_ref().and_then(|path| path.extension()).and_then(|ext| ext.to_str()) {
+            Some("tsx") => Ok(Syntax::Tsx),
+            Some("ts") => Err(format!("{}: `.ts` files are not supported, only TypeScript with JSX in `.tsx` files", self)),
+            _ => Ok(Syntax::Jsx),
+        }
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.path {
+            Some(ref path) => write!(f, "{}", path.display()),
+            None => f.write_str("<stdin>"),
+        }
+    }
+}
+
+enum Output {
+    Stdout,
+    File(PathBuf),
+    Dir(PathBuf),
+}
+
+impl Output {
+    /// Writes what `command` made of `input`, stdout gets every input in turn.
+    fn write(&self, input: &Input, command: Command, text: &str) -> io::Result<()> {
+        let path = match *self {
+            Output::Stdout => {
+                let stdout = io::stdout();
+                let mut stdout = stdout.lock();
+                stdout.write_all(text.as_bytes())?;
+                return stdout.flush();
+            },
+            Output::File(ref path) => path.clone(),
+            Output::Dir(ref dir) => {
+                let path = input.path.as_deref().unwrap_or_else(|| Path::new("stdin"));
+                out_dir_path(dir, path, &input.base).with_extension(command.extension())
+            },
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, text)
+    }
+}
+
+// src/pages/app.jsx matched by src/**/*.jsx => dist/pages/app.jsx
+fn out_dir_path(dir: &Path, path: &Path, base: &Path) -> PathBuf {
+    let relative = path.strip_prefix(base).unwrap_or(path);
+    let is_below = relative.is_relative() && relative.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if is_below {
+        dir.join(relative)
+    } else {
+        dir.join(path.file_name().unwrap_or_default())
+    }
+}
 
 
+fn app() -> App<'static, 'static> {
+    let inputs = Arg::with_name("INPUT")
+        .help("Files or glob patterns to read, `-` reads stdin")
+        .required(true)
+        .multiple(true);
+    let tsx = Arg::with_name("tsx")
+        .long("tsx")
+        .help("Parse TypeScript with JSX, the default for .tsx files");
+    let output = Arg::with_name("output")
+        .short("o")
+        .long("output")
+        .value_name("FILE")
+        .help("Write to FILE instead of stdout, takes a single input");
+    let out_dir = Arg::with_name("out-dir")
+        .long("out-dir")
+        .value_name("DIR")
+        .conflicts_with("output")
+        .help("Write a file per input to DIR, keeping its path below the glob");
+
+    App::new("jsx")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Parses and transforms JSX")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .setting(AppSettings::VersionlessSubcommands)
+        .subcommand(SubCommand::with_name("tokenize")
+            .about("Prints the tokens of the inputs, one per line")
+            .args(&[inputs.clone(), tsx.clone(), output.clone(), out_dir.clone()]))
+        .subcommand(SubCommand::with_name("parse")
+            .about("Prints the ESTree AST of the inputs as JSON")
+            .args(&[inputs.clone(), tsx.clone(), output.clone(), out_dir.clone()]))
+        .subcommand(SubCommand::with_name("check")
+            .about("Reports the syntax errors of the inputs")
+            .args(&[inputs.clone(), tsx.clone()]))
+        .subcommand(SubCommand::with_name("transform")
+            .about("Compiles the JSX of the inputs to JavaScript")
+            .args(&[inputs, tsx, output, out_dir])
+            .arg(Arg::with_name("runtime")
+                .long("runtime")
+                .value_name("RUNTIME")
+                .possible_values(&["classic", "automatic"])
+                .help("`React.createElement` calls or `react/jsx-runtime` imports"))
+            .arg(Arg::with_name("pragma")
+                .long("pragma")
+                .value_name("FUNCTION")
+                .help("Function called for elements, `React.createElement` by default"))
+            .arg(Arg::with_name("pragma-frag")
+                .long("pragma-frag")
+                .value_name("COMPONENT")
+                .help("Type of fragments, `React.Fragment` by default"))
+            .arg(Arg::with_name("import-source")
+                .long("import-source")
+                .value_name("MODULE")
+                .help("Module the automatic runtime is imported from, `react` by default"))
+            .arg(Arg::with_name("development")
+                .long("development")
+                .help("Use the development runtime and pass `__source`"))
+            .arg(Arg::with_name("object-assign")
+                .long("object-assign")
+                .help("Merge spread props with `Object.assign` instead of object spread"))
+            .arg(Arg::with_name("source-map")
+                .long("source-map")
+                .help("Append an inline source map")))
+}
+
 fn main() {
-    let source = r#"
-
-<>
-    <App name="str" num={ true } >
-        <div height={16}> hi </div>
-    </App>
-</>
-
-<div style={styles.items} child=<h1>hi</h1> >
-    {
-        [].map(function (){
-            <div style={styles.item} key={index}>
-                <span style={styles.splitLine} />
-                <p style={styles.title}>{item.title}</p>
-              </div>
-        })
-    }
-  
-</div>
-
-"#;
-    // lex::parse(source);
-    parser::parse(source);
+    let matches = match app().get_matches_safe() {
+        Ok(matches) => matches,
+        Err(e) => match e.kind {
+            clap::ErrorKind::HelpDisplayed | clap::ErrorKind::VersionDisplayed => e.exit(),
+            _ => {
+                eprintln!("{}", e.message);
+                process::exit(EXIT_FAILURE);
+            },
+        },
+    };
+
+    process::exit(run(&matches));
+}
+
+/// Runs the subcommand on every input, returns the exit code.
+fn run(matches: &ArgMatches) -> i32 {
+    let (command, matches) = match matches.subcommand() {
+        ("tokenize", Some(matches)) => (Command::Tokenize, matches),
+        ("parse", Some(matches)) => (Command::Parse, matches),
+        ("check", Some(matches)) => (Command::Check, matches),
+        ("transform", Some(matches)) => (Command::Transform, matches),
+        _ => return EXIT_FAILURE,
+    };
+
+    let inputs = match expand_inputs(matches.values_of("INPUT").into_iter().flatten()) {
+        Ok(inputs) => inputs,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return EXIT_FAILURE;
+        },
+    };
+
+    let output = match (matches.value_of("output"), matches.value_of("out-dir")) {
+        (Some("-"), _) | (None, None) => Output::Stdout,
+        (Some(path), _) => Output::File(PathBuf::from(path)),
+        (None, Some(dir)) => Output::Dir(PathBuf::from(dir)),
+    };
+    if let Output::File(_) = output {
+        if inputs.len() > 1 {
+            eprintln!("error: `--output` takes a single input, use `--out-dir`");
+            return EXIT_FAILURE;
+        }
+    }
+
+    let options = transform_options(matches);
+
+    let mut status = 0;
+    for input in inputs.iter() {
+        let code = match input.read() {
+            Ok(code) => code,
+            Err(e) => {
+                eprintln!("error: {}: {}", input, e);
+                status = EXIT_FAILURE;
+                continue;
+            },
+        };
+
+        let syntax = match input.syntax() {
+            _ if matches.is_present("tsx") => Syntax::Tsx,
+            Ok(syntax) => syntax,
+            Err(message) => {
+                eprintln!("error: {}", message);
+                status = EXIT_FAILURE;
+                continue;
+            },
+        };
+        let (text, errors) = match command {
+            Command::Tokenize => tokenize(&code, syntax),
+            Command::Parse => parse(&code, syntax),
+            Command::Check => match jsx::parse(&code, &Arena::new(), ParseOptions { syntax }) {
+                Ok(_) => (None, Vec::new()),
+                Err(diagnostics) => (None, diagnostics.errors),
+            },
+            Command::Transform => {
+                let filename = input.path.as_ref().map(|path| path.display().to_string());
+                let options = TransformOptions { syntax, filename, ..options.clone() };
+                match jsx::transform(&code, options) {
+                    Ok(output) => (Some(compiled(output)), Vec::new()),
+                    Err(diagnostics) => (None, diagnostics.errors),
+                }
+            },
+        };
+
+        for error in errors.iter() {
+            eprintln!("{}:{}", input, error);
+        }
+        if !errors.is_empty() {
+            status = status.max(EXIT_SYNTAX_ERROR);
+        }
+
+        if let Some(text) = text {
+            if let Err(e) = output.write(input, command, &text) {
+                eprintln!("error: {}: {}", input, e);
+                status = EXIT_FAILURE;
+            }
+        }
+    }
+
+    status
+}
+
+/// Inputs in argument order, every glob expanded to the files it matches in
+/// path order.
+fn expand_inputs<'a, I: Iterator<Item = &'a str>>(args: I) -> Result<Vec<Input>, String> {
+    let mut inputs = Vec::new();
+
+    for arg in args {
+        if arg == "-" {
+            inputs.push(Input { path: None, base: PathBuf::new() });
+            continue;
+        }
+
+        if !is_glob(arg) {
+            let path = PathBuf::from(arg);
+            let base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            inputs.push(Input { path: Some(path), base });
+            continue;
+        }
+
+        let base = glob_base(arg);
+        let paths = glob::glob(arg).map_err(|e| format!("{}: {}", arg, e))?;
+        let start = inputs.len();
+        for path in paths {
+            let path = path.map_err(|e| e.to_string())?;
+            if path.is_file() {
+                inputs.push(Input { path: Some(path), base: base.clone() });
+            }
+        }
+        if inputs.len() == start {
+            return Err(format!("{}: no files match", arg));
+        }
+    }
+
+    Ok(inputs)
+}
+
+#[inline]
+fn is_glob(arg: &str) -> bool {
+    arg.contains(['*', '?', '['])
+}
+
+// src/**/*.jsx => src
+fn glob_base(pattern: &str) -> PathBuf {
+    Path::new(pattern)
+        .components()
+        .take_while(|c| !is_glob(&c.as_os_str().to_string_lossy()))
+        .collect()
+}
+
+fn transform_options(matches: &ArgMatches) -> TransformOptions {
+    let mut options = TransformOptions::default();
+
+    if matches.value_of("runtime") == Some("automatic") {
+        options.runtime = Runtime::Automatic;
+    }
+    if let Some(pragma) = matches.value_of("pragma") {
+        options.pragma = pragma.to_string();
+    }
+    if let Some(pragma_frag) = matches.value_of("pragma-frag") {
+        options.pragma_frag = pragma_frag.to_string();
+    }
+    if let Some(import_source) = matches.value_of("import-source") {
+        options.import_source = import_source.to_string();
+    }
+    options.development = matches.is_present("development");
+    options.object_assign = matches.is_present("object-assign");
+    options.source_map = matches.is_present("source-map");
+
+    options
+}
+
+// 1:1	Keyword	"const"
+fn tokenize(code: &str, syntax: Syntax) -> (Option<String>, Vec<ParseError>) {
+    let (tokens, errors) = jsx::tokenize(code, ParseOptions { syntax });
+    let lines = LineIndex::new(code);
+
+    let mut output = String::new();
+    for token in tokens.iter() {
+        let (line, column) = lines.line_column(token.start);
+        output.push_str(&format!("{}:{}\t{:?}\t", line + 1, column + 1, token.item));
+        write_string_literal(&mut output, &code[token.start..token.end]);
+        output.push('\n');
+    }
+
+    (Some(output), errors)
+}
+
+/// The AST is printed even when there are errors, with `Error` nodes for the
+/// source the parser skipped.
+fn parse(code: &str, syntax: Syntax) -> (Option<String>, Vec<ParseError>) {
+    let arena = Arena::new();
+    let (program, errors) = jsx::parse_recovering(code, &arena, ParseOptions { syntax });
+    let mut output = jsx::to_json(code, &program);
+    output.push('\n');
+
+    (Some(output), errors)
+}
+
+/// The code with its inline source map.
+fn compiled(output: jsx::Output) -> String {
+    let mut code = output.code;
+    if let Some(source_map) = output.source_map {
+        if !code.ends_with('\n') {
+            code.push('\n');
+        }
+        code.push_str(&source_map.to_inline_comment());
+    }
+    if !code.ends_with('\n') {
+        code.push('\n');
+    }
+
+    code
+}
+
+
+#[cfg(test)]
+mod tests {
+    use jsx::Syntax;
+
+    use super::{ app, run, tokenize, out_dir_path, glob_base, Input, EXIT_SYNTAX_ERROR, EXIT_FAILURE, };
+
+    use std::fs;
+    use std::path::{ Path, PathBuf, };
+
+    fn jsx(args: &[&str]) -> i32 {
+        let matches = app().get_matches_from_safe(Some("jsx").into_iter().chain(args.iter().cloned())).unwrap();
+        run(&matches)
+    }
+
+    /// An empty directory for `name`, removed first if a previous run left it.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("jsx-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn out_dir_paths() {
+        assert_eq!(glob_base("src/**/*.jsx"), Path::new("src"));
+        assert_eq!(glob_base("*.jsx"), Path::new(""));
+
+        let dist = Path::new("dist");
+        assert_eq!(out_dir_path(dist, Path::new("src/pages/app.jsx"), Path::new("src")), Path::new("dist/pages/app.jsx"));
+        assert_eq!(out_dir_path(dist, Path::new("../app.jsx"), Path::new("")), Path::new("dist/app.jsx"));
+    }
+
+    #[test]
+    fn syntax_from_extension() {
+        let syntax = |path: &str| Input { path: Some(PathBuf::from(path)), base: PathBuf::new() }.syntax();
+        assert_eq!(syntax("app.tsx"), Ok(Syntax::Tsx));
+        assert_eq!(syntax("app.jsx"), Ok(Syntax::Jsx));
+        assert_eq!(syntax("app.ts"), Err("app.ts: `.ts` files are not supported, only TypeScript with JSX in `.tsx` files".to_string()));
+        assert_eq!(Input { path: None, base: PathBuf::new() }.syntax(), Ok(Syntax::Jsx));
+    }
+
+    #[test]
+    fn tokens_with_positions() {
+        let (text, errors) = tokenize("a =\n  <b c=\"\u{e9}\"/>;", Syntax::Jsx);
+        assert_eq!(text.unwrap(), concat!(
+            "1:1\tIdentifier\t\"a\"\n",
+            "1:3\tAssign\t\"=\"\n",
+            "2:3\tElementOpen\t\"<\"\n",
+            "2:4\tIdentifier\t\"b\"\n",
+            "2:6\tIdentifier\t\"c\"\n",
+            "2:7\tAssign\t\"=\"\n",
+            "2:8\tLiteralString\t\"\\\"\u{e9}\\\"\"\n",
+            "2:11\tSelfClosingElementClose\t\"/>\"\n",
+            "2:13\tSemicolon\t\";\"\n",
+        ));
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn transform_to_out_dir() {
+        let dir = temp_dir("transform");
+        fs::create_dir_all(dir.join("src/pages")).unwrap();
+        fs::write(dir.join("src/pages/app.jsx"), "<App />;").unwrap();
+        fs::write(dir.join("src/bad.jsx"), "<App>;").unwrap();
+
+        let pattern = dir.join("src/**/*.jsx").display().to_string();
+        let out_dir = dir.join("dist").display().to_string();
+        assert_eq!(jsx(&["transform", &pattern, "--out-dir", &out_dir]), EXIT_SYNTAX_ERROR);
+        assert_eq!(fs::read_to_string(dir.join("dist/pages/app.js")).unwrap(), "React.createElement(App, null);\n");
+        assert!(!dir.join("dist/bad.js").exists());
+
+        assert_eq!(jsx(&["check", &dir.join("src/pages/app.jsx").display().to_string()]), 0);
+        assert_eq!(jsx(&["check", &dir.join("missing.jsx").display().to_string()]), EXIT_FAILURE);
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
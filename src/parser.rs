@@ -1,97 +1,181 @@
-use crate::error::Error;
-use crate::lex::{ Token, Lexer, };
+use crate::error::{ Error, ParseError, };
+use crate::lex::{ Token, Lexer, Checkpoint, };
 use crate::ast::{
-    Loc, Node,
+    Loc, Node, NodeList, Program, Comment,
 
     ElementExpression, FragmentExpression,
     ElementName, MemberExpression, NamespacedName, 
     Attribute, NormalAttribute, NormalAttributeName, NormalAttributeInitializer,
 
     OpeningOrSelfClosingElement,
-    OpeningElement, ClosingElement, SelfClosingElement, 
+    ClosingElement,
     
     Child, 
 
-    Text, ChildExpression, AssignmentExpression,
+    Text, EmptyExpression, ErrorNode,
+
+    Expression, Literal, TemplateLiteral, TemplateElement, TaggedTemplateExpression,
+    ArrayExpression, ObjectExpression, Property, PropertyKey, MethodKind,
+    Function, Class, ClassMember, ArrowFunctionExpression, ArrowBody,
+    UnaryExpression, UnaryOperator, UpdateExpression, UpdateOperator,
+    BinaryExpression, BinaryOperator, ConditionalExpression,
+    AssignmentExpression, AssignmentOperator,
+    CallExpression, NewExpression, StaticMemberExpression, ComputedMemberExpression,
+    YieldExpression, MetaProperty,
+
+    Pattern, PatternProperty, ObjectPattern, ArrayPattern, AssignmentPattern,
+
+    Statement, BlockStatement, VariableKind, VariableDeclaration, VariableDeclarator, IfStatement,
+    SwitchStatement, SwitchCase, WhileStatement, DoWhileStatement,
+    ForStatement, ForInit, ForHead, ForInStatement, ForOfStatement,
+    TryStatement, CatchClause, LabeledStatement, WithStatement,
+
+    ImportDeclaration, ImportSpecifier, ExportSpecifier,
+    ExportNamedDeclaration, ExportDefaultDeclaration, ExportAllDeclaration,
+
+    TypedPattern, TsModifier, TsTypeAnnotation, TsTypeParameters, TsTypeParameter, TsTypeArguments,
+    TsType, TsTypeReference, TsTemplateLiteralType, TsTupleElement, TsFunctionType,
+    TsTypeMember, TsSignature, TsIndexSignature, TsMappedModifier, TsMappedType,
+    TsTypeOperator, TsTypeOperatorKind, TsTypeQuery, TsImportType, TsIndexedAccessType,
+    TsConditionalType, TsInferType, TsTypePredicate, TsAsExpression, TsDeclareFunction,
+    TsInterfaceDeclaration, TsTypeAliasDeclaration, TsEnumDeclaration, TsEnumMember,
+    TsModuleDeclaration, TsParameterProperty,
 };
 
+use std::collections::HashSet;
+use toolshed::Arena;
+use crate::list::{ List, GrowableList, };
 
-pub struct Parser<'a> {
-    lexer: Lexer<'a>,
-    pub body: Vec<Loc<Node>>,
+
+/// The language accepted by the parser.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Syntax {
+    /// ECMAScript with JSX.
+    #[default]
+    Jsx,
+    /// TypeScript with JSX, `.tsx` files.
+    Tsx,
 }
 
-impl<'a> Parser<'a> {
-    pub fn new(code: &'a [char]) -> Self {
-        Parser {
-            lexer: Lexer::new(code),
-            body: Vec::new(),
-        }
-    }
+/// Options of `jsx::parse`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ParseOptions {
+    pub syntax: Syntax,
+}
 
-    #[inline]
-    pub fn parse_assignment_expression(&mut self) -> Result<AssignmentExpression, Error> {
-        assert_eq!(self.lexer.token, Token::BraceOpen);
-        let start = self.lexer.end();
-        
-        loop {
-             self.lexer.consume()?;
 
-             if self.lexer.token == Token::BraceClose {
-                break;
-             }
+/// Recursive descent parser of a module, the AST borrows the source and the arena.
+pub(crate) struct Parser<'a> {
+    lexer: Lexer<'a>,
+    /// Holds the nodes, the AST borrows the arena and the source.
+    arena: &'a Arena,
+    /// End of the last consumed ECMAScript token, the end of the node being parsed.
+    last_end: usize,
+    /// `yield` is an operator inside generator functions.
+    in_generator: bool,
+    /// `in` is not an operator in the head of a `for` statement.
+    no_in: bool,
+    /// Type annotations are parsed, `Syntax::Tsx`.
+    typescript: bool,
+    /// `a ? (b) : c`, the next arrow function may not have a return type.
+    no_arrow_return_type: bool,
+    /// Starts of the parentheses that turned out not to hold arrow function
+    /// parameters, `(a = (b = (c)))` is tried once per level, not once per path.
+    no_arrow_at: HashSet<usize>,
+    /// Why the last error was raised, dropped when the parser rewinds.
+    failure: Option<Failure>,
+    /// Errors are recorded and skipped over, `parse_recovering`.
+    recover: bool,
+    errors: Vec<ParseError>,
+    /// Names of the elements whose children are being parsed, `""` for fragments.
+    open_tags: Vec<&'a str>,
+    /// Nesting of the node being parsed, see `nested`.
+    depth: usize,
+    /// Comments skipped by the lexer so far, in source order.
+    comments: Vec<Loc<Comment>>,
+    /// Tokens lexed so far in source order, only kept by `tokenize`.
+    tokens: Option<Vec<Loc<Token>>>,
+}
 
-             if self.lexer.token == Token::BraceOpen {
-                loop {
-                    self.lexer.consume()?;
-                    if self.lexer.token == Token::BraceClose {
-                        break;
-                    }
-                }
-             }
+struct Failure {
+    start: usize,
+    end: usize,
+    expected: Vec<String>,
+    message: Option<String>,
+}
 
-             if self.lexer.token == Token::ElementOpen {
-                // TODO: 
-                let _ = self.parse_elem()?;
+/// Where parsing resumes after `recover` skipped the source of an error.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Resume {
+    /// The next top-level statement: past a `;` or a stray `}`, or at a
+    /// statement keyword starting a line.
+    Statement,
+    /// The next JSX child: past the element or `{}` the error is in, or at
+    /// the closing tag of the parent.
+    Child,
+}
 
-             }
+/// What `Parser::skip` is inside of, it decides how the next token is lexed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Frame {
+    /// `(` or `[`, they hold no statements.
+    Paren,
+    /// `{`, also the `{}` of an attribute or a child.
+    Brace,
+    /// `<a b="c"` up to its `>` or `/>`.
+    Tag,
+    /// `</a` up to its `>`, which also ends the children before it.
+    ClosingTag,
+    /// The children of an element or fragment up to its closing tag.
+    Children,
+}
 
-             if self.lexer.token == Token::FragmentOpen {
-                // TODO:
-                let _ = self.parse_fragment()?;
-             }
+impl<'a> Parser<'a> {
+    pub(crate) fn with_syntax(code: &'a str, arena: &'a Arena, syntax: Syntax) -> Self {
+        Parser {
+            lexer: Lexer::new(code),
+            arena,
+            last_end: 0,
+            in_generator: false,
+            no_in: false,
+            typescript: syntax == Syntax::Tsx,
+            no_arrow_return_type: false,
+            no_arrow_at: HashSet::new(),
+            failure: None,
+            recover: false,
+            errors: Vec::new(),
+            open_tags: Vec::new(),
+            depth: 0,
+            comments: Vec::new(),
+            tokens: None,
         }
-
-        Ok(AssignmentExpression {
-            start: start,
-            end: self.lexer.start(),
-        })
     }
 
     #[inline]
-    pub fn parse_elem_name(&mut self) -> Result<ElementName, Error> {
+    pub(crate) fn parse_elem_name(&mut self) -> Result<ElementName<'a>, Error> {
         // Name
         // Name:abc
         // Name.abc
         if self.lexer.token != Token::Identifier {
             return Err(Error::UnexpectedToken);
         }
-        let ns = Loc::new(self.lexer.start(), self.lexer.end(), self.lexer.token);
-        self.lexer.consume()?;
+        let ns = self.token_loc();
+        self.consume_with(Lexer::consume_jsx)?;
 
         let name: ElementName;
         match self.lexer.token {
             Token::Dit => {
-                let mut members: Vec<Loc<Token>> = vec![ ns, ];
+                let members = GrowableList::new();
+                members.push(self.arena, ns);
 
                 loop {
-                    self.lexer.consume().map_err(|_| Error::UnexpectedToken)?;
+                    self.consume_with(Lexer::consume_jsx).map_err(|_| Error::UnexpectedToken)?;
                     if self.lexer.token != Token::Identifier {
                         return Err(Error::UnexpectedToken);
                     }
-                    
-                    members.push(Loc::new(self.lexer.start(), self.lexer.end(), self.lexer.token));
-                    self.lexer.consume()?;
+
+                    members.push(self.arena, self.token_loc());
+                    self.consume_with(Lexer::consume_jsx)?;
 
                     if self.lexer.token == Token::Dit {
                         continue;
@@ -100,18 +184,25 @@ impl<'a> Parser<'a> {
                     }
                 }
 
-                let mem_expr = MemberExpression { members, };
+                // `<ui.date-picker>`, only a plain identifier names a member.
+                if let Some(member) = members.as_list().iter().find(|member| member.item.contains('-')) {
+                    let message = format!("`{}` cannot be part of a member expression, it contains a `-`", member.item);
+                    self.failure = Some(Failure { start: member.start, end: member.end, expected: Vec::new(), message: Some(message) });
+                    return Err(Error::UnexpectedToken);
+                }
+
+                let mem_expr = MemberExpression { members: members.as_list() };
                 name = ElementName::MemberExpression(mem_expr);
             },
             Token::Colon => {
-                self.lexer.consume()?;
+                self.consume_with(Lexer::consume_jsx)?;
                 if self.lexer.token != Token::Identifier {
                     return Err(Error::UnexpectedToken);
                 }
-                let subname = Loc::new(self.lexer.start(), self.lexer.end(), self.lexer.token);
+                let subname = self.token_loc();
                 name = ElementName::NamespacedName(NamespacedName { ns, name: subname });
 
-                self.lexer.consume()?;
+                self.consume_with(Lexer::consume_jsx)?;
             },
             _ => {
                 name = ElementName::Identifier(ns);
@@ -122,30 +213,29 @@ impl<'a> Parser<'a> {
     }
 
     #[inline]
-    pub fn parse_elem_attr_name(&mut self) -> Result<NormalAttributeName, Error> {
+    pub(crate) fn parse_elem_attr_name(&mut self) -> Result<NormalAttributeName<'a>, Error> {
         // displayName
         // displayName:subname
 
-        let (start, end) = self.lexer.loc();
         if self.lexer.token != Token::Identifier {
             return Err(Error::UnexpectedToken);
         }
 
-        let ns = Loc::new(self.lexer.start(), self.lexer.end(), self.lexer.token);
-        self.lexer.consume()?;
+        let ns = self.token_loc();
+        self.consume_with(Lexer::consume_jsx)?;
 
         let name: NormalAttributeName;
         match self.lexer.token {
             Token::Colon => {
-                self.lexer.consume()?;
+                self.consume_with(Lexer::consume_jsx)?;
                 if self.lexer.token != Token::Identifier {
                     return Err(Error::UnexpectedToken);
                 }
 
-                let subname = Loc::new(self.lexer.start(), self.lexer.end(), self.lexer.token);
+                let subname = self.token_loc();
                 name = NormalAttributeName::NamespacedName(NamespacedName { ns, name: subname });
 
-                self.lexer.consume()?;
+                self.consume_with(Lexer::consume_jsx)?;
             },
             _ => {
                 name = NormalAttributeName::Identifier(ns);
@@ -156,52 +246,53 @@ impl<'a> Parser<'a> {
     }
 
     #[inline]
-    pub fn parse_elem_attr_value(&mut self) -> Result<Option<NormalAttributeInitializer>, Error> {
+    pub(crate) fn parse_elem_attr_value(&mut self) -> Result<Option<NormalAttributeInitializer<'a>>, Error> {
         if self.lexer.token != Token::Assign {
             return Ok(None)
         }
 
-        self.lexer.consume()?;
+        self.consume_with(Lexer::consume_jsx)?;
 
         match self.lexer.token {
             Token::LiteralString => {
-                let (start, end) = self.lexer.loc();
-                let initializer = NormalAttributeInitializer::LiteralString(Loc::new(start, end, self.lexer.token));
+                let initializer = NormalAttributeInitializer::LiteralString(self.token_loc());
 
-                self.lexer.consume()?;
+                self.consume_with(Lexer::consume_jsx)?;
 
                 Ok(Some(initializer))
             },
             Token::BraceOpen => {
-                let start = self.lexer.end();
+                self.next_token()?;
                 let assignment_expression = self.parse_assignment_expression()?;
-                let (_, end) = self.lexer.loc();
+                if self.lexer.token != Token::BraceClose {
+                    return Err(Error::UnexpectedToken);
+                }
 
-                let initializer = NormalAttributeInitializer::AssignmentExpression(Loc::new(start, end, assignment_expression));
+                let initializer = NormalAttributeInitializer::AssignmentExpression(assignment_expression);
 
-                self.lexer.consume()?;
+                self.consume_with(Lexer::consume_jsx)?;
 
                 Ok(Some(initializer))
             },
             Token::ElementOpen => {
-                let start = self.lexer.end();
+                let start = self.lexer.start();
                 let elem = self.parse_elem()?;
-                let (_, end) = self.lexer.loc();
+                let end = self.lexer.end();
 
-                let initializer = NormalAttributeInitializer::ElementExpression(Loc::new(start, end, elem));
+                let initializer = NormalAttributeInitializer::ElementExpression(self.alloc(Loc::new(start, end, elem)));
 
-                self.lexer.consume()?;
+                self.consume_with(Lexer::consume_jsx)?;
 
                 Ok(Some(initializer))
             },
             Token::FragmentOpen => {
-                let start = self.lexer.end();
+                let start = self.lexer.start();
                 let fragment_elem = self.parse_fragment()?;
-                let (_, end) = self.lexer.loc();
+                let end = self.lexer.end();
 
-                let initializer = NormalAttributeInitializer::FragmentExpression(Loc::new(start, end, fragment_elem));
+                let initializer = NormalAttributeInitializer::FragmentExpression(self.alloc(Loc::new(start, end, fragment_elem)));
 
-                self.lexer.consume()?;
+                self.consume_with(Lexer::consume_jsx)?;
 
                 Ok(Some(initializer))
 
@@ -211,8 +302,9 @@ impl<'a> Parser<'a> {
     }
 
     #[inline]
-    pub fn parse_elem_attr(&mut self) -> Result<Option<Attribute>, Error> {
+    pub(crate) fn parse_elem_attr(&mut self) -> Result<Option<Attribute<'a>>, Error> {
         // { ...props }
+        // { ...getProps() }
         // displayName="value"
         // displayName={ true }
         // displayName=<></>
@@ -221,40 +313,31 @@ impl<'a> Parser<'a> {
         match self.lexer.token {
             Token::BraceOpen => {
                 // Spread Attribute
-                let (start, end) = self.lexer.loc();
-
-                self.lexer.consume()?;
+                self.next_token()?;
                 if self.lexer.token != Token::Spread {
                     return Err(Error::UnexpectedToken);
                 }
 
-                self.lexer.consume()?;
-                if self.lexer.token != Token::Identifier {
-                    return Err(Error::UnexpectedToken);
-                }
-
-                let attr = Attribute::Spread(Loc::new(self.lexer.start(), self.lexer.end(), self.lexer.token));
+                self.next_token()?;
+                let attr = Attribute::Spread(self.parse_assignment_expression()?);
 
-                self.lexer.consume()?;
                 if self.lexer.token != Token::BraceClose {
-                    return Err(Error::UnexpectedToken);
+                    return Err(self.expected(Token::BraceClose.to_string()));
                 }
 
-                self.lexer.consume()?;
+                self.consume_with(Lexer::consume_jsx)?;
 
 
                 Ok(Some(attr))
             },
             Token::Identifier => {
                 // Normal Attribute
-                let (start, end) = self.lexer.loc();
-                
                 let name = self.parse_elem_attr_name()?;
                 let init = self.parse_elem_attr_value()?;
 
                 let attr = NormalAttribute {
-                    name: name,
-                    init: init,
+                    name,
+                    init,
                 };
 
                 Ok(Some(Attribute::Normal(attr)))
@@ -266,35 +349,43 @@ impl<'a> Parser<'a> {
     }
 
     #[inline]
-    pub fn parse_opening_or_self_closing_elem(&mut self) -> Result<OpeningOrSelfClosingElement, Error> {
+    pub(crate) fn parse_opening_or_self_closing_elem(&mut self) -> Result<OpeningOrSelfClosingElement<'a>, Error> {
         // <App />
         // <App>
         if self.lexer.token != Token::ElementOpen {
             return Err(Error::UnexpectedToken);
         }
 
-        let (start, end) = self.lexer.loc();
-
         // Name
-        self.lexer.consume()?;
+        self.consume_with(Lexer::consume_jsx)?;
         let name = self.parse_elem_name()?;
 
+        // <Select<Option> />, the type arguments are lexed as ECMAScript.
+        let type_args = if self.typescript && self.lexer.token == Token::ElementOpen {
+            let type_args_start = self.lexer.start();
+            self.lexer.reset(type_args_start);
+            self.next_token()?;
+            let type_args = self.parse_ts_type_args()?;
+            self.lexer.reset(self.last_end);
+            self.consume_with(Lexer::consume_jsx)?;
+            Some(self.alloc(type_args))
+        } else {
+            None
+        };
+
         // Attrs
-        let mut attrs: Vec<Attribute> = Vec::new();
-        loop {
-            if let Some(attr) = self.parse_elem_attr()? {
-                attrs.push(attr);
-            } else {
-                break;
-            }
+        let attrs = GrowableList::new();
+        while let Some(attr) = self.parse_elem_attr()? {
+            attrs.push(self.arena, attr);
         }
+        let attrs = attrs.as_list();
 
         match self.lexer.token {
             Token::ElementClose => {
-                Ok(OpeningOrSelfClosingElement::Opening((name,attrs )))
+                Ok(OpeningOrSelfClosingElement::Opening((name, type_args, attrs)))
             },
             Token::SelfClosingElementClose => {
-                Ok(OpeningOrSelfClosingElement::SelfClosing((name,attrs )))
+                Ok(OpeningOrSelfClosingElement::SelfClosing((name, type_args, attrs)))
             },
             _ => {
                 Err(Error::UnexpectedToken)
@@ -303,12 +394,12 @@ impl<'a> Parser<'a> {
     }
 
     #[inline]
-    pub fn parse_closing_elem(&mut self) -> Result<ClosingElement, Error> {
+    pub(crate) fn parse_closing_elem(&mut self) -> Result<ClosingElement<'a>, Error> {
         if self.lexer.token != Token::ClosingElementOpen {
             return Err(Error::UnexpectedToken);
         }
     
-        self.lexer.consume()?;
+        self.consume_with(Lexer::consume_jsx)?;
         let name = self.parse_elem_name()?;
 
         if self.lexer.token != Token::ElementClose {
@@ -316,12 +407,12 @@ impl<'a> Parser<'a> {
         }
 
         Ok(ClosingElement {
-            name: name,
+            name,
         })
     }
 
     #[inline]
-    pub fn parse_elem(&mut self) -> Result<ElementExpression, Error> {
+    pub(crate) fn parse_elem(&mut self) -> Result<ElementExpression<'a>, Error> {
         if self.lexer.token != Token::ElementOpen {
             return Err(Error::UnexpectedToken);
         }
@@ -329,77 +420,63 @@ impl<'a> Parser<'a> {
         let opening_or_self_closing_elem = self.parse_opening_or_self_closing_elem()?;
 
         match opening_or_self_closing_elem {
-            OpeningOrSelfClosingElement::Opening((name, attrs)) => {
+            OpeningOrSelfClosingElement::Opening((name, type_args, attrs)) => {
                 // jsx children
-                let children = self.parse_children()?;
+                let (name_start, name_end) = elem_name_span(&name);
+                let open_name = self.lexer.slice_source(name_start, name_end);
+                let children = self.parse_children_of(open_name)?;
+
+                if self.close_unclosed(open_name) {
+                    return Ok(ElementExpression {
+                        is_self_closing: false,
+                        name,
+                        type_args,
+                        attrs,
+                        children: Some(children),
+                    });
+                }
 
                 // jsx ClosingElement
                 let closing_elem = self.parse_closing_elem()?;
                 let name2 = closing_elem.name;
 
-                let is_name_eq = match name {
-                    ElementName::Identifier(loc_token) => {
-                        match name2 {
-                            ElementName::Identifier(loc_token2) => {
-                                let a = self.lexer.slice_source(loc_token.start, loc_token.end);
-                                let b = self.lexer.slice_source(loc_token2.start, loc_token2.end);
-                                a == b
-                            },
-                            _ => { false }
-                        }
-                    },
-                    ElementName::NamespacedName(ref name_spaced_name) => {
-                        match name2 {
-                            ElementName::NamespacedName(name_spaced_name2) => {
-                                let a = self.lexer.slice_source(name_spaced_name.ns.start, name_spaced_name.ns.end);
-                                let b = self.lexer.slice_source(name_spaced_name2.ns.start, name_spaced_name2.ns.end);
-
-                                let c = self.lexer.slice_source(name_spaced_name.name.start, name_spaced_name.name.end);
-                                let d = self.lexer.slice_source(name_spaced_name2.name.start, name_spaced_name2.name.end);
-
-                                a == b && c == d
-                            },
-                            _ => { false }
-                        }
-                    },
-                    ElementName::MemberExpression(ref member_expr) => {
-                        match name2 {
-                            ElementName::MemberExpression(member_expr2) => {
-                                let a = member_expr.members.iter()
-                                    .map(|loc_token| {
-                                        self.lexer.slice_source(loc_token.start, loc_token.end)
-                                    });
-                                let b = member_expr2.members.iter()
-                                    .map(|loc_token| {
-                                        self.lexer.slice_source(loc_token.start, loc_token.end)
-                                    });
-                                a.zip(b).map(|(c, d)| {
-                                    c == d
-                                }).all(|ret| ret == true )
-                            },
-                            _ => { false }
-                        }
-                    },
-                };
+                // Names are equal when their parts are, spans aside.
+                let is_name_eq = name == name2;
 
-                if is_name_eq == false {
-                    return Err(Error::UnexpectedToken);
+                if !is_name_eq {
+                    let (start, end) = elem_name_span(&name2);
+                    let (open_start, open_end) = elem_name_span(&name);
+                    let message = format!(
+                        "closing tag `</{}>` does not match opening tag `<{}>`",
+                        self.lexer.slice_source(start, end),
+                        self.lexer.slice_source(open_start, open_end),
+                    );
+                    self.failure = Some(Failure { start, end, expected: Vec::new(), message: Some(message) });
+                    if !self.recover {
+                        return Err(Error::UnexpectedToken);
+                    }
+                    // The element is kept, closed by the wrong tag.
+                    let error = self.error(Error::UnexpectedToken);
+                    self.push_error(error);
+                    self.failure = None;
                 }
 
                 let elem = ElementExpression {
                     is_self_closing: false,
-                    name: name,
-                    attrs: attrs,
+                    name,
+                    type_args,
+                    attrs,
                     children: Some(children),
                 };
 
                 Ok(elem)
             },
-            OpeningOrSelfClosingElement::SelfClosing((name, attrs)) => {
+            OpeningOrSelfClosingElement::SelfClosing((name, type_args, attrs)) => {
                 let elem = ElementExpression {
                     is_self_closing: true,
-                    name: name,
-                    attrs: attrs,
+                    name,
+                    type_args,
+                    attrs,
                     children: None,
                 };
 
@@ -409,154 +486,3951 @@ impl<'a> Parser<'a> {
     }
 
     #[inline]
-    pub fn parse_children(&mut self) -> Result<Vec<Child>, Error> {
+    pub(crate) fn parse_children(&mut self) -> Result<List<'a, Child<'a>>, Error> {
         // JSXText
         // JSXElement
+        // JSXFragment
         // { JSXChildExpression }
-        assert_eq!(self.lexer.token == Token::ElementClose || self.lexer.token == Token::FragmentOpen, true);
+        if self.lexer.token != Token::ElementClose && self.lexer.token != Token::FragmentOpen {
+            return Err(Error::UnexpectedToken);
+        }
+
+        let children = GrowableList::new();
+        // A child was skipped after an error.
+        let mut after_error = false;
 
-        let mut children: Vec<Child> = Vec::new();
+        loop {
+            let mut start = self.lexer.end();
+            let child = match self.consume_with(Lexer::consume_jsx_text) {
+                Ok(_) => {
+                    start = self.lexer.start();
+                    self.parse_child()
+                },
+                Err(Error::UnexpectedToken) => Err(self.unexpected_in_text()),
+                Err(e) => Err(e),
+            };
 
-        let start = self.lexer.end();
+            match child {
+                Ok(Some(child)) => children.push(self.arena, child),
+                // What was left of a skipped element, `<div a=></div>`.
+                Ok(None) if self.recover && !self.open_tags.contains(&self.closing_tag_name()) && after_error => {
+                    let rest = &self.lexer.source()[self.lexer.end()..];
+                    let end = self.lexer.end() + rest.find('>').map_or(rest.len(), |index| index + 1);
+                    self.lexer.reset(end);
+                },
+                Ok(None) => break,
+                // An unclosed element takes the rest of the file.
+                Err(e @ Error::EndOfProgram) | Err(e @ Error::UnexpectedEndOfProgram) => {
+                    return Err(unexpected_end_of_program(e));
+                },
+                Err(e) => {
+                    let end = self.recover(e, start, Resume::Child)?;
+                    self.lexer.reset(end);
+                    children.push(self.arena, Child::Error( Loc::new(start, end, ErrorNode) ));
+                    after_error = true;
+                },
+            }
+        }
 
-        let mut text_child: Text = Text { start: self.lexer.end(), end: self.lexer.end() };
+        Ok(children.as_list())
+    }
 
-        loop {
-            self.lexer.consume()?;
-            match self.lexer.token {
-                Token::ElementOpen => {
-                    text_child.end = self.lexer.start();
-                    if text_child.end > text_child.start {
-                        children.push(Child::Text( Loc::new(text_child.start, text_child.end, text_child) ));
-                    }
+    /// `parse_children` of the element named `name`.
+    fn parse_children_of(&mut self, name: &'a str) -> Result<List<'a, Child<'a>>, Error> {
+        self.open_tags.push(name);
+        let children = self.parse_children();
+        self.open_tags.pop();
 
-                    let elem = self.parse_elem()?;
+        children
+    }
 
-                    children.push(Child::Element( elem ));
+    /// Name in the closing tag at the current token, `App` in `</App>`, empty in `</>`.
+    fn closing_tag_name(&self) -> &'a str {
+        if self.lexer.token != Token::ClosingElementOpen {
+            return "";
+        }
 
-                    text_child.start = self.lexer.end();
-                    text_child.end = self.lexer.end();
-                },
-                Token::BraceOpen => {
-                    text_child.end = self.lexer.start();
-                    if text_child.end > text_child.start {
-                        children.push(Child::Text( Loc::new(text_child.start, text_child.end, text_child) ));
-                    }
+        let rest = &self.lexer.source()[self.lexer.end()..];
+        rest[..rest.find('>').unwrap_or(rest.len())].trim()
+    }
 
-                    let assignment_expression = self.parse_assignment_expression()?;
-                    children.push(Child::ChildExpression( assignment_expression ));
+    /// When recovering, the closing tag of an enclosing element also closes the
+    /// element `open_name`. The tag is left for the enclosing element.
+    fn close_unclosed(&mut self, open_name: &str) -> bool {
+        let name = self.closing_tag_name();
+        if !self.recover || name == open_name || !self.open_tags.contains(&name) {
+            return false;
+        }
 
-                    text_child.start = self.lexer.end();
-                    text_child.end = self.lexer.end();
-                },
-                Token::EndOfProgram | Token::UnexpectedToken => {
-                    unreachable!();
-                },
-                Token::ClosingElementOpen | Token::FragmentClose => {
-                    break;
-                },
-                _ => {
+        let start = self.lexer.start();
+        let rest = &self.lexer.source()[start..];
+        let end = start + rest.find('>').map_or(rest.len(), |index| index + 1);
+        let message = format!("closing tag `</{}>` does not match opening tag `<{}>`", name, open_name);
+        self.failure = Some(Failure { start, end, expected: Vec::new(), message: Some(message) });
 
+        let error = self.error(Error::UnexpectedToken);
+        self.push_error(error);
+        self.failure = None;
+        self.lexer.reset(start);
+
+        true
+    }
+
+    /// The child starting at the current token, `None` at the closing tag.
+    fn parse_child(&mut self) -> Result<Option<Child<'a>>, Error> {
+        self.nested(Self::parse_child_inner)
+    }
+
+    fn parse_child_inner(&mut self) -> Result<Option<Child<'a>>, Error> {
+        let child = match self.lexer.token {
+            Token::Text => {
+                let text = self.token_loc();
+                Child::Text( Loc::new(text.start, text.end, Text { raw: text.item }) )
+            },
+            Token::ElementOpen => {
+                let elem_start = self.lexer.start();
+                let elem = self.parse_elem()?;
+
+                Child::Element( self.alloc(Loc::new(elem_start, self.lexer.end(), elem)) )
+            },
+            Token::FragmentOpen => {
+                let fragment_start = self.lexer.start();
+                let fragment_elem = self.parse_fragment()?;
+
+                Child::Fragment( self.alloc(Loc::new(fragment_start, self.lexer.end(), fragment_elem)) )
+            },
+            Token::BraceOpen => {
+                let start = self.lexer.end();
+                self.next_token()?;
+
+                if self.lexer.token == Token::BraceClose {
+                    let end = self.lexer.start();
+                    return Ok(Some(Child::EmptyExpression( Loc::new(start, end, EmptyExpression) )));
+                }
+
+                let is_spread = self.lexer.token == Token::Spread;
+                if is_spread {
+                    self.next_token()?;
+                }
+
+                let expr = self.parse_assignment_expression()?;
+                if self.lexer.token != Token::BraceClose {
+                    return Err(self.expected(Token::BraceClose.to_string()));
+                }
+
+                if is_spread {
+                    Child::Spread( expr )
+                } else {
+                    Child::ChildExpression( expr )
                 }
+            },
+            Token::ClosingElementOpen | Token::FragmentClose => {
+                return Ok(None);
+            },
+            _ => {
+                return Err(self.unexpected());
             }
-        }
+        };
 
-        Ok(children)
+        Ok(Some(child))
     }
 
     #[inline]
-    pub fn parse_fragment(&mut self) -> Result<FragmentExpression, Error> {
+    pub(crate) fn parse_fragment(&mut self) -> Result<FragmentExpression<'a>, Error> {
         // <> </>
         if self.lexer.token != Token::FragmentOpen {
             return Err(Error::UnexpectedToken);
         }
 
-        let (start, end) = self.lexer.loc();
-
         // jsx children
-        let children = self.parse_children()?;
+        let children = self.parse_children_of("")?;
+
+        if self.close_unclosed("") {
+            return Ok(FragmentExpression { children });
+        }
 
         if self.lexer.token != Token::FragmentClose {
             return Err(Error::UnexpectedToken);
         }
 
         Ok(FragmentExpression {
-            children: children,
+            children,
         })
     }
 
-    pub fn parse(&mut self) -> Result<(), Error> {
+    /// Parses the whole source as an ECMAScript module, `<` starts JSX
+    /// wherever an expression is expected.
+    pub(crate) fn parse(&mut self) -> Result<Program<'a>, ParseError> {
+        self.parse_program().map_err(|e| self.error(e))
+    }
+
+    /// Parses the whole source like `parse` but does not stop at the first error,
+    /// the parser skips the tokens of the part that failed and leaves an `Error`
+    /// node for the skipped source. A JSX child is the smallest part skipped, a
+    /// top-level statement the largest. Reports at most `MAX_ERRORS` errors.
+    pub(crate) fn parse_recovering(&mut self) -> (Program<'a>, Vec<ParseError>) {
+        self.recover = true;
+
+        let program = match self.parse_program() {
+            Ok(program) => program,
+            Err(e) => {
+                let error = self.error(e);
+                self.push_error(error);
+                Program { body: List::empty(), comments: List::from_iter(self.arena, self.comments.drain(..)) }
+            },
+        };
+        self.recover = false;
+
+        (program, std::mem::take(&mut self.errors))
+    }
+
+    /// Every token of the source, lexed in the mode the parser reads it in:
+    /// JSX text, tags and template parts included, comments and whitespace
+    /// left out. Errors are recovered from like `parse_recovering` does and
+    /// the skipped source has no tokens.
+    pub(crate) fn tokenize(&mut self) -> (Vec<Loc<Token>>, Vec<ParseError>) {
+        self.tokens = Some(Vec::new());
+        let (_, errors) = self.parse_recovering();
+
+        (self.tokens.take().unwrap_or_default(), errors)
+    }
+
+    fn parse_program(&mut self) -> Result<Program<'a>, Error> {
+        let body = GrowableList::new();
+        if let Err(e) = self.next_token() {
+            body.push(self.arena, self.recover_statement(e, 0)?);
+        }
+
+        while self.lexer.token != Token::EndOfProgram {
+            let start = self.lexer.start();
+            let item = match self.parse_module_item() {
+                Ok(item) => item,
+                Err(e) => self.recover_statement(e, start)?,
+            };
+            body.push(self.arena, item);
+        }
+
+        Ok(Program { body: body.as_list(), comments: List::from_iter(self.arena, self.comments.drain(..)) })
+    }
+
+    /// Records `e` and skips the part that failed, starting at `start`, up to
+    /// where parsing resumes. Returns the index skipped to, always past `start`
+    /// or the same error comes back. Fails with `e` unless the parser recovers.
+    fn recover(&mut self, e: Error, start: usize, resume: Resume) -> Result<usize, Error> {
+        if !self.recover {
+            return Err(e);
+        }
+
+        let code = self.lexer.source();
+        if self.errors.len() >= MAX_ERRORS {
+            return Ok(code.len());
+        }
+
+        let error = self.error(e);
+        let end = self.skip(start, error.start, resume);
+
+        self.push_error(error);
+        self.failure = None;
+
+        Ok(end)
+    }
+
+    /// Index where parsing resumes after the error at `error_start`, lexing
+    /// from `start` in the modes the parser would: JSX tags and children
+    /// switch modes like in `parse_elem`, so a `;`, `}` or `<` in a string, a
+    /// comment, a regex or JSX text never ends the skipped source. Brackets
+    /// open at the error are left open, `x = (;` ends at its `;`.
+    fn skip(&self, start: usize, error_start: usize, resume: Resume) -> usize {
+        let code = self.lexer.source();
+        let mut lexer = self.lexer.clone();
+        lexer.reset(start);
+
+        let mut frames = match resume {
+            Resume::Statement => Vec::new(),
+            Resume::Child => vec![Frame::Children],
+        };
+        let base = frames.len();
+        // Brackets open before JSX at the error, set once the error is reached.
+        let mut floor = None;
+        let is_bracket = |frame: &Frame| matches!(frame, Frame::Paren | Frame::Brace);
+
         loop {
-            self.lexer.consume()?;
+            let end = lexer.end();
+            if resume == Resume::Child && frames.len() <= base && end > start && end > error_start {
+                return end;
+            }
 
-            match self.lexer.token {
-                Token::FragmentOpen => {
-                    let (start, end) = self.lexer.loc();
-                    let fragment_elem = self.parse_fragment()?;
+            let expression_start = lexer.regex_allowed();
+            let ret = match frames.last() {
+                Some(Frame::Tag) | Some(Frame::ClosingTag) => lexer.consume_jsx(),
+                Some(Frame::Children) => lexer.consume_jsx_text(),
+                _ => match lexer.next() {
+                    Some(Ok(_)) => Ok(()),
+                    Some(Err(_)) => Err(Error::UnexpectedToken),
+                    None => Err(Error::EndOfProgram),
+                },
+            };
+            match ret {
+                Ok(_) => {},
+                Err(Error::EndOfProgram) | Err(Error::UnexpectedEndOfProgram) => return code.len(),
+                // A char that starts no token is skipped.
+                Err(_) => {
+                    let mut next = lexer.end().max(lexer.start() + 1).min(code.len());
+                    while !code.is_char_boundary(next) {
+                        next += 1;
+                    }
+                    lexer.reset(next);
+                    continue;
+                },
+            }
+
+            let (token_start, token_end) = lexer.loc();
+            if floor.is_none() && token_end > error_start {
+                floor = Some(frames.iter().take_while(|frame| is_bracket(frame)).count());
+            }
+            let is_es = !matches!(frames.last(), Some(Frame::Tag) | Some(Frame::ClosingTag) | Some(Frame::Children));
+
+            if let (Resume::Statement, true, Some(floor)) = (resume, is_es, floor) {
+                // `(` and `[` opened since the error are left unclosed at a statement keyword.
+                let in_statement = frames.len() <= floor;
+                let in_parens = frames.iter().skip(floor).all(|&frame| frame == Frame::Paren);
+                match lexer.token {
+                    Token::Semicolon if in_statement => return token_end,
+                    Token::BraceClose if frames.is_empty() => return token_end,
+                    Token::Identifier if in_parens && lexer.newline_before && token_start > start
+                        && STATEMENT_KEYWORDS.iter().any(|word| lexer.token_text_eq(word)) => return token_start,
+                    _ => {},
+                }
+            }
 
-                    let node = Node::Fragment(fragment_elem);
-                    self.body.push(Loc::new(start, self.lexer.end(), node));
+            match (frames.last(), lexer.token) {
+                (Some(Frame::Tag), Token::BraceOpen) | (Some(Frame::Children), Token::BraceOpen) => frames.push(Frame::Brace),
+                (Some(Frame::Tag), Token::SelfClosingElementClose) => {
+                    frames.pop();
                 },
-                Token::ElementOpen => {
-                    // <
-                    // <aaa>
-                    // <aa />
-                    let start = self.lexer.start();
-                    let elem = self.parse_elem()?;
-
-                    let node = Node::Element(elem);
-                    self.body.push(Loc::new(start, self.lexer.end(), node));
+                (Some(Frame::Tag), Token::ElementClose) => {
+                    frames.pop();
+                    frames.push(Frame::Children);
                 },
-                Token::EndOfProgram | Token::UnexpectedToken => {
-                    // Should return error.
-                    unreachable!();
+                (Some(Frame::ClosingTag), Token::ElementClose) => {
+                    frames.pop();
+                    if frames.last() == Some(&Frame::Children) {
+                        frames.pop();
+                    }
                 },
-                _ => {
-                    continue;
-                }
+                (Some(Frame::Children), Token::ClosingElementOpen) | (Some(Frame::Children), Token::FragmentClose)
+                    if resume == Resume::Child && frames.len() <= base => return token_start,
+                (Some(Frame::Children), Token::ElementOpen) => frames.push(Frame::Tag),
+                (Some(Frame::Children), Token::FragmentOpen) => frames.push(Frame::Children),
+                (Some(Frame::Children), Token::ClosingElementOpen) => frames.push(Frame::ClosingTag),
+                (Some(Frame::Children), Token::FragmentClose) => {
+                    frames.pop();
+                },
+                (_, Token::ParenOpen) | (_, Token::BracketOpen) if is_es => frames.push(Frame::Paren),
+                (_, Token::BraceOpen) if is_es => frames.push(Frame::Brace),
+                (Some(&frame), Token::ParenClose) | (Some(&frame), Token::BracketClose)
+                | (Some(&frame), Token::BraceClose) if is_bracket(&frame) => {
+                    frames.pop();
+                },
+                // `{a </div>`, the closing tag of the element the `{` is in.
+                (_, Token::Less) if code[token_end..].starts_with('/') && frames.contains(&Frame::Children) => {
+                    let children = frames.iter().rposition(|&frame| frame == Frame::Children).unwrap_or(0);
+                    frames.truncate(children + 1);
+                    lexer.reset(token_start);
+                },
+                // JSX, the `<` is lexed again as a tag like in `parse_jsx_expression`.
+                (_, Token::Less) if expression_start => {
+                    lexer.reset(token_start);
+                    match lexer.consume_jsx() {
+                        Ok(_) if lexer.token == Token::ElementOpen => frames.push(Frame::Tag),
+                        Ok(_) if lexer.token == Token::FragmentOpen => frames.push(Frame::Children),
+                        _ => lexer.reset(token_end),
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Skips a top-level statement that failed with `e`, a stray closing
+    /// tag, `</div>`, is skipped whole.
+    fn recover_statement(&mut self, e: Error, start: usize) -> Result<Node<'a, Statement<'a>>, Error> {
+        let code = self.lexer.source();
+
+        let mut end = self.recover(e, start, Resume::Statement)?;
+        if code[start..].starts_with("</") {
+            end = code[start..].find('>').map_or(code.len(), |index| start + index + 1);
+        }
+        loop {
+            self.lexer.reset(end);
+            match self.next_token() {
+                Ok(_) => return Ok(self.alloc(Loc::new(start, end, Statement::Error))),
+                Err(e) => end = self.recover(e, end, Resume::Statement)?,
             }
         }
+    }
+
+    /// Errors from failed alternatives are dropped by `rewind`, the same error
+    /// is not reported twice.
+    fn push_error(&mut self, error: ParseError) {
+        if self.errors.last() != Some(&error) {
+            self.errors.push(error);
+        }
+    }
+
+    /// Locates `kind`, raised by the last call into the parser.
+    pub(crate) fn error(&self, kind: Error) -> ParseError {
+        let code = self.lexer.source();
 
-        Ok(())
+        match self.failure {
+            Some(ref failure) => {
+                ParseError::new(code, kind, (failure.start, failure.end), failure.expected.clone(), failure.message.clone())
+            },
+            None => ParseError::new(code, kind, self.lexer.loc(), Vec::new(), None),
+        }
     }
 }
 
 
-fn debug(source: &[char], (start, end): (usize, usize)) {
-    let mut line_break: usize = end;
-    while &source[line_break..line_break+1] != ['\n'] && line_break < source.len() - 1 {
-        line_break += 1;
+// ECMAScript
+// https://tc39.es/ecma262/#sec-ecmascript-language-expressions
+
+const RELATIONAL_PRECEDENCE: u8 = 7;
+
+/// Deepest nesting of `nested` parse calls, a parenthesized expression takes
+/// two levels. Fits the 8 MiB main thread stack in debug builds and a 2 MiB
+/// thread stack in release builds.
+const MAX_DEPTH: usize = 256;
+
+/// `parse_recovering` skips the rest of the source after this many errors.
+const MAX_ERRORS: usize = 100;
+
+/// Keywords starting a statement, a line starting with one ends the
+/// statement skipped after an error.
+const STATEMENT_KEYWORDS: &[&str] = &[
+    "break", "class", "const", "continue", "debugger", "do", "export", "for",
+    "function", "if", "import", "let", "return", "switch", "throw", "try", "var",
+    "while", "with", "interface", "type", "enum", "declare",
+];
+
+const RESERVED_WORDS: &[&str] = &[
+    "break", "case", "catch", "class", "const", "continue", "debugger", "default",
+    "delete", "do", "else", "export", "extends", "finally", "for", "function", "if",
+    "import", "in", "instanceof", "new", "return", "super", "switch", "this", "throw",
+    "try", "typeof", "var", "void", "while", "with", "null", "true", "false", "enum",
+];
+
+impl<'a> Parser<'a> {
+    /// Moves to the next ECMAScript token, the end of the program is a token here.
+    #[inline]
+    fn next_token(&mut self) -> Result<(), Error> {
+        self.last_end = self.lexer.end();
+        match self.consume_with(Lexer::consume) {
+            Err(Error::EndOfProgram) => Ok(()),
+            ret => ret,
+        }
     }
 
-    for c in &source[..line_break] {
-        print!("{}", c);
+    #[inline]
+    fn expect(&mut self, token: Token) -> Result<(), Error> {
+        if self.lexer.token != token {
+            return Err(self.expected(token.to_string()));
+        }
+        self.next_token()
     }
-    print!("\n\n\n");
 
-    // println!("{}", &source[..line_break]);
-    // println!("token: {:?} {:?}", token, &source[start..end]);
-    // println!("{}", &source[line_break..]);
-}
+    /// Fails on the current token.
+    fn unexpected(&mut self) -> Error {
+        let (start, end) = self.lexer.loc();
+        match self.failure {
+            // Alternatives tried at the same token add up, "expected `)` or `,`".
+            Some(ref failure) if failure.start == start && failure.message.is_none() => { },
+            _ => self.failure = Some(Failure { start, end, expected: Vec::new(), message: None }),
+        }
 
-pub fn parse(source: &str) {
-    let code = source.chars().collect::<Vec<char>>();
-    let mut parser = Parser::new(&code);
-    
-    match parser.parse() {
-        Ok(_) | Err(Error::EndOfProgram) => {
-            println!("{:?}", parser.body);
-        },
-        Err(e) => {
-            let (start, end) = parser.lexer.loc();
+        match self.lexer.token {
+            Token::EndOfProgram => Error::UnexpectedEndOfProgram,
+            _ => Error::UnexpectedToken,
+        }
+    }
+
+    /// Fails on the `>` or `}` JSX text stopped at, like Babel it suggests
+    /// writing them as an entity or an expression.
+    fn unexpected_in_text(&mut self) -> Error {
+        let start = self.lexer.start();
+        let (ch, entity) = match self.lexer.source()[start..].chars().next() {
+            Some('>') => ('>', "&gt;"),
+            Some('}') => ('}', "&rbrace;"),
+            _ => return self.unexpected(),
+        };
+
+        let message = format!("unexpected `{}`, did you mean `{}` or `{{'{}'}}`?", ch, entity, ch);
+        self.failure = Some(Failure { start, end: start + 1, expected: Vec::new(), message: Some(message) });
+        Error::UnexpectedToken
+    }
+
+    /// Fails on the current token, `what` would have been accepted.
+    fn expected(&mut self, what: String) -> Error {
+        let e = self.unexpected();
+        if let Some(ref mut failure) = self.failure {
+            if !failure.expected.contains(&what) {
+                failure.expected.push(what);
+            }
+        }
+        e
+    }
+
+    fn expected_one_of(&mut self, tokens: &[Token]) -> Error {
+        let mut e = self.unexpected();
+        for token in tokens {
+            e = self.expected(token.to_string());
+        }
+        e
+    }
+
+    #[inline]
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.lexer.token == Token::Identifier && self.lexer.token_text_eq(keyword)
+    }
+
+    #[inline]
+    fn is_reserved_word(&self) -> bool {
+        self.lexer.token == Token::Identifier
+            && RESERVED_WORDS.iter().any(|word| self.lexer.token_text_eq(word))
+    }
+
+    /// Token after the current one and whether a line break precedes it.
+    fn peek(&self) -> (Token, bool) {
+        let mut lexer = self.lexer.clone();
+        match lexer.consume() {
+            Ok(_) | Err(Error::EndOfProgram) => (lexer.token, lexer.newline_before),
+            Err(_) => (Token::UnexpectedToken, lexer.newline_before),
+        }
+    }
+
+    #[inline]
+    fn checkpoint(&self) -> (Checkpoint<'a>, usize, usize, usize) {
+        (self.lexer.checkpoint(), self.last_end, self.errors.len(), self.comments.len())
+    }
+
+    #[inline]
+    fn rewind(&mut self, (lexer, last_end, errors, comments): (Checkpoint<'a>, usize, usize, usize)) {
+        self.lexer.rewind(lexer);
+        self.last_end = last_end;
+        self.failure = None;
+        self.errors.truncate(errors);
+        self.comments.truncate(comments);
+    }
+
+    /// Lexes the next token with `lex` and keeps the comments skipped before it,
+    /// once, the lexer goes over the same source again after a `reset`.
+    #[inline]
+    fn consume_with(&mut self, lex: fn(&mut Lexer<'a>) -> Result<(), Error>) -> Result<(), Error> {
+        let ret = lex(&mut self.lexer);
+        for comment in self.lexer.leading_comments() {
+            if self.comments.last().is_none_or(|last| comment.start >= last.end) {
+                self.comments.push(comment);
+            }
+        }
+        self.record_token();
+        ret
+    }
+
+    /// Lexes the current `/` again as a regex or as a division.
+    #[inline]
+    fn rescan_slash(&mut self, regex: bool) -> Result<(), Error> {
+        let ret = self.lexer.rescan_slash(regex);
+        self.record_token();
+        ret
+    }
 
-            debug(&code, (start, end));
+    /// Keeps the current token for `tokenize`, replacing the tokens lexed
+    /// from the same source before a `reset` or a rescan.
+    fn record_token(&mut self) {
+        if let Some(ref mut tokens) = self.tokens {
+            let start = self.lexer.start();
+            while tokens.last().is_some_and(|last| last.end > start) {
+                tokens.pop();
+            }
+            if self.lexer.token != Token::EndOfProgram && self.lexer.token != Token::UnexpectedToken {
+                tokens.push(Loc::new(start, self.lexer.end(), self.lexer.token));
+            }
+        }
+    }
+
+    #[inline]
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        if !self.is_keyword(keyword) {
+            return Err(self.expected(format!("`{}`", keyword)));
+        }
+        self.next_token()
+    }
 
-            println!("latest token: {:?}", parser.lexer.token);
-            println!("{:?}({}:{}): Text: {}", e, start, end, &code[start..end].iter().collect::<String>() );
+    /// Parses with `in` as an operator again, inside brackets and function bodies.
+    fn with_in<T, F>(&mut self, parse: F) -> Result<T, Error>
+        where F: FnOnce(&mut Self) -> Result<T, Error>
+    {
+        let no_in = self.no_in;
+        self.no_in = false;
+        let ret = parse(self);
+        self.no_in = no_in;
 
+        ret
+    }
+
+    /// Runs `parse` one nesting level deeper, source nested past `MAX_DEPTH`
+    /// fails instead of overflowing the stack.
+    fn nested<T, F>(&mut self, parse: F) -> Result<T, Error>
+        where F: FnOnce(&mut Self) -> Result<T, Error>
+    {
+        if self.depth >= MAX_DEPTH {
+            let (start, end) = self.lexer.loc();
+            let message = Some("nested too deeply".to_string());
+            self.failure = Some(Failure { start, end, expected: Vec::new(), message });
+            return Err(Error::UnexpectedToken);
         }
+
+        self.depth += 1;
+        let ret = parse(self);
+        self.depth -= 1;
+
+        ret
+    }
+
+    /// Parses without `in` as an operator, `for (a in b)`.
+    fn without_in<T, F>(&mut self, parse: F) -> Result<T, Error>
+        where F: FnOnce(&mut Self) -> Result<T, Error>
+    {
+        let no_in = self.no_in;
+        self.no_in = true;
+        let ret = parse(self);
+        self.no_in = no_in;
+
+        ret
     }
 
-    // std::thread::sleep(std::time::Duration::new(10, 0));
-    // print!("len: {:?}", parser.body.len());
-}
\ No newline at end of file
+    /// `async function`, no line break is allowed after `async`.
+    fn is_async_function(&self) -> bool {
+        if !self.is_keyword("async") {
+            return false;
+        }
+
+        let mut lexer = self.lexer.clone();
+        lexer.consume().is_ok() && !lexer.newline_before && lexer.token == Token::Identifier
+            && lexer.token_text_eq("function")
+    }
+
+    /// Loc from `start` to the end of the last consumed token.
+    #[inline]
+    fn finish<T>(&self, start: usize, item: T) -> Loc<T> {
+        Loc::new(start, self.last_end, item)
+    }
+
+    /// `finish`, allocated in the arena.
+    #[inline]
+    fn finish_node<T: Copy>(&self, start: usize, item: T) -> Node<'a, T> {
+        self.alloc(self.finish(start, item))
+    }
+
+    #[inline]
+    fn alloc<T: Copy>(&self, loc: Loc<T>) -> Node<'a, T> {
+        self.arena.alloc(loc)
+    }
+
+    /// Runs `parse` and allocates the node it returns.
+    #[inline]
+    fn node<T, F>(&mut self, parse: F) -> Result<Node<'a, T>, Error>
+        where T: Copy, F: FnOnce(&mut Self) -> Result<Loc<T>, Error>
+    {
+        let loc = parse(self)?;
+        Ok(self.alloc(loc))
+    }
+
+    /// The current token with its source text.
+    #[inline]
+    fn token_loc(&self) -> Loc<&'a str> {
+        let (start, end) = self.lexer.loc();
+        Loc::new(start, end, self.lexer.slice_source(start, end))
+    }
+
+    /// Automatic semicolon insertion.
+    fn consume_semicolon(&mut self) -> Result<(), Error> {
+        match self.lexer.token {
+            Token::Semicolon => self.next_token(),
+            Token::BraceClose | Token::EndOfProgram => Ok(()),
+            _ if self.lexer.newline_before => Ok(()),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_binding_identifier(&mut self) -> Result<Loc<&'a str>, Error> {
+        if self.lexer.token != Token::Identifier || self.is_reserved_word() {
+            return Err(self.unexpected());
+        }
+
+        let ident = self.token_loc();
+        self.next_token()?;
+
+        Ok(ident)
+    }
+
+    /// Any identifier, reserved words included, as in `a.default`.
+    fn parse_identifier_name(&mut self) -> Result<Loc<&'a str>, Error> {
+        if self.lexer.token != Token::Identifier {
+            return Err(self.unexpected());
+        }
+
+        let ident = self.token_loc();
+        self.next_token()?;
+
+        Ok(ident)
+    }
+
+    // Expression , AssignmentExpression
+    pub(crate) fn parse_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        let start = self.lexer.start();
+        let expr = self.parse_assignment_expression()?;
+        if self.lexer.token != Token::Comma {
+            return Ok(expr);
+        }
+
+        let expressions = GrowableList::new();
+        expressions.push(self.arena, expr);
+        while self.lexer.token == Token::Comma {
+            self.next_token()?;
+            expressions.push(self.arena, self.parse_assignment_expression()?);
+        }
+
+        Ok(self.finish_node(start, Expression::Sequence(expressions.as_list())))
+    }
+
+    pub(crate) fn parse_assignment_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        self.nested(Self::parse_assignment_expression_inner)
+    }
+
+    fn parse_assignment_expression_inner(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        let start = self.lexer.start();
+
+        if self.in_generator && self.is_keyword("yield") {
+            return self.parse_yield_expression();
+        }
+
+        if let Some(arrow) = self.try_parse_arrow_function()? {
+            return Ok(arrow);
+        }
+
+        let left = self.parse_conditional_expression()?;
+
+        // x => x
+        if self.lexer.token == Token::Arrow && !self.lexer.newline_before {
+            if let Expression::Identifier(name) = left.item {
+                let param = self.alloc(Loc::new(left.start, left.end, Pattern::Identifier(name)));
+                return self.parse_arrow_function_body(start, false, None, List::from(self.arena, param), None);
+            }
+        }
+
+        let operator = match assignment_operator(self.lexer.token) {
+            Some(operator) => operator,
+            None => return Ok(left),
+        };
+
+        let target = self.expression_to_pattern(left, operator == AssignmentOperator::Assign)?;
+        self.next_token()?;
+        let right = self.parse_assignment_expression()?;
+
+        Ok(self.finish_node(start, Expression::Assignment(AssignmentExpression {
+            operator,
+            left: target,
+            right,
+        })))
+    }
+
+    fn parse_yield_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        let start = self.lexer.start();
+        self.next_token()?;
+
+        let mut delegate = false;
+        if self.lexer.token == Token::Mul && !self.lexer.newline_before {
+            delegate = true;
+            self.next_token()?;
+        }
+
+        let has_argument = delegate || (!self.lexer.newline_before && !matches!(self.lexer.token,
+            Token::ParenClose | Token::BracketClose | Token::BraceClose | Token::Comma
+            | Token::Semicolon | Token::Colon | Token::EndOfProgram
+        ));
+
+        let argument = if has_argument {
+            Some(self.parse_assignment_expression()?)
+        } else {
+            None
+        };
+
+        Ok(self.finish_node(start, Expression::Yield(YieldExpression { delegate, argument })))
+    }
+
+    /// `(a, b) => ...`, `async x => ...`, `async (a) => ...` and, in TSX,
+    /// `<T,>(a: T): T => ...`. The current token is left untouched when no
+    /// arrow function starts here.
+    fn try_parse_arrow_function(&mut self) -> Result<Option<Node<'a, Expression<'a>>>, Error> {
+        let start = self.lexer.start();
+        let no_return_type = self.no_arrow_return_type;
+        self.no_arrow_return_type = false;
+
+        let is_async = self.is_keyword("async") && {
+            let (token, newline_before) = self.peek();
+            !newline_before && (token == Token::Identifier || token == Token::ParenOpen
+                || (token == Token::Less && self.typescript))
+        };
+
+        let checkpoint = self.checkpoint();
+        if is_async {
+            self.next_token()?;
+        }
+
+        let is_generic = self.is_ts_generic_arrow();
+        if !is_async && !is_generic && self.lexer.token != Token::ParenOpen {
+            return Ok(None);
+        }
+        // A head that failed with a return type allowed fails without one too.
+        if self.no_arrow_at.contains(&start) {
+            self.rewind(checkpoint);
+            return Ok(None);
+        }
+
+        let arrow = match self.lexer.token {
+            Token::Identifier if is_async => {
+                self.parse_binding_identifier()
+                    .map(|ident| {
+                        let param = self.alloc(Loc::new(ident.start, ident.end, Pattern::Identifier(ident.item)));
+                        (None, List::from(self.arena, param), None)
+                    })
+            },
+            _ => self.parse_function_head(!no_return_type),
+        };
+
+        match arrow {
+            Ok((type_params, params, return_type)) if self.lexer.token == Token::Arrow && !self.lexer.newline_before => {
+                self.parse_arrow_function_body(start, is_async, type_params, params, return_type).map(Some)
+            },
+            Err(e) if is_generic => Err(e),
+            _ => {
+                self.rewind(checkpoint);
+                if !no_return_type {
+                    self.no_arrow_at.insert(start);
+                }
+                Ok(None)
+            },
+        }
+    }
+
+    /// `<T,>` and `<T extends U>` start a generic arrow function in TSX, `<T>` is an element.
+    fn is_ts_generic_arrow(&self) -> bool {
+        if !self.typescript || self.lexer.token != Token::Less {
+            return false;
+        }
+
+        let mut lexer = self.lexer.clone();
+        if lexer.consume().is_err() || lexer.token != Token::Identifier {
+            return false;
+        }
+        if lexer.token_text_eq("const") && lexer.consume().is_err() {
+            return false;
+        }
+
+        lexer.consume().is_ok() && (lexer.token == Token::Comma
+            || (lexer.token == Token::Identifier && lexer.token_text_eq("extends")))
+    }
+
+    fn parse_arrow_function_body(
+        &mut self,
+        start: usize,
+        is_async: bool,
+        type_params: Option<Node<'a, TsTypeParameters<'a>>>,
+        params: NodeList<'a, Pattern<'a>>,
+        return_type: Option<Node<'a, TsTypeAnnotation<'a>>>,
+    ) -> Result<Node<'a, Expression<'a>>, Error> {
+        self.expect(Token::Arrow)?;
+
+        let body = if self.lexer.token == Token::BraceOpen {
+            ArrowBody::Block(self.parse_function_body(false)?)
+        } else {
+            let in_generator = self.in_generator;
+            self.in_generator = false;
+            let expr = self.parse_assignment_expression();
+            self.in_generator = in_generator;
+
+            ArrowBody::Expression(expr?)
+        };
+
+        Ok(self.finish_node(start, Expression::Arrow(ArrowFunctionExpression {
+            is_async,
+            type_params,
+            params,
+            return_type,
+            body,
+        })))
+    }
+
+    fn parse_conditional_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        let start = self.lexer.start();
+        let test = self.parse_binary_expression(0)?;
+
+        if self.lexer.token != Token::Question {
+            return Ok(test);
+        }
+
+        self.next_token()?;
+        let checkpoint = self.checkpoint();
+        let mut consequent = self.with_in(Self::parse_assignment_expression);
+        // `a ? (b) : c => d`, the `: c` read as the return type of an arrow
+        // function leaves no `:` for the alternate.
+        if self.typescript && (consequent.is_err() || self.lexer.token != Token::Colon) {
+            self.rewind(checkpoint);
+            self.no_arrow_return_type = true;
+            consequent = self.with_in(Self::parse_assignment_expression);
+            self.no_arrow_return_type = false;
+        }
+        let consequent = consequent?;
+        self.expect(Token::Colon)?;
+        let alternate = self.parse_assignment_expression()?;
+
+        Ok(self.finish_node(start, Expression::Conditional(ConditionalExpression {
+            test,
+            consequent,
+            alternate,
+        })))
+    }
+
+    fn binary_operator(&self) -> Option<(BinaryOperator, u8)> {
+        let operator = match self.lexer.token {
+            Token::Nullish => (BinaryOperator::Nullish, 1),
+            Token::Or => (BinaryOperator::Or, 1),
+            Token::And => (BinaryOperator::And, 2),
+            Token::BitOr => (BinaryOperator::BitOr, 3),
+            Token::BitXor => (BinaryOperator::BitXor, 4),
+            Token::BitAnd => (BinaryOperator::BitAnd, 5),
+            Token::Equal => (BinaryOperator::Equal, 6),
+            Token::NotEqual => (BinaryOperator::NotEqual, 6),
+            Token::StrictEqual => (BinaryOperator::StrictEqual, 6),
+            Token::StrictNotEqual => (BinaryOperator::StrictNotEqual, 6),
+            Token::Less => (BinaryOperator::Less, RELATIONAL_PRECEDENCE),
+            Token::Greater => (BinaryOperator::Greater, RELATIONAL_PRECEDENCE),
+            Token::LessEqual => (BinaryOperator::LessEqual, RELATIONAL_PRECEDENCE),
+            Token::GreaterEqual => (BinaryOperator::GreaterEqual, RELATIONAL_PRECEDENCE),
+            Token::Identifier if self.lexer.token_text_eq("instanceof") => (BinaryOperator::Instanceof, RELATIONAL_PRECEDENCE),
+            Token::Identifier if !self.no_in && self.lexer.token_text_eq("in") => (BinaryOperator::In, RELATIONAL_PRECEDENCE),
+            Token::ShiftLeft => (BinaryOperator::ShiftLeft, 8),
+            Token::ShiftRight => (BinaryOperator::ShiftRight, 8),
+            Token::UnsignedShiftRight => (BinaryOperator::UnsignedShiftRight, 8),
+            Token::Add => (BinaryOperator::Add, 9),
+            Token::Sub => (BinaryOperator::Sub, 9),
+            Token::Mul => (BinaryOperator::Mul, 10),
+            Token::Div => (BinaryOperator::Div, 10),
+            Token::Rem => (BinaryOperator::Rem, 10),
+            Token::Exp => (BinaryOperator::Exp, 11),
+            _ => return None,
+        };
+
+        Some(operator)
+    }
+
+    /// Operator precedence parsing of every operator binding tighter than `min_precedence`.
+    fn parse_binary_expression(&mut self, min_precedence: u8) -> Result<Node<'a, Expression<'a>>, Error> {
+        let start = self.lexer.start();
+        let mut left = self.parse_unary_expression()?;
+
+        loop {
+            // `as` and `satisfies` bind like relational operators.
+            if self.typescript && RELATIONAL_PRECEDENCE > min_precedence && !self.lexer.newline_before
+                && (self.is_keyword("as") || self.is_keyword("satisfies"))
+            {
+                let is_as = self.is_keyword("as");
+                self.next_token()?;
+                let ty = self.parse_ts_type()?;
+                let expr = TsAsExpression { expression: left, ty: self.alloc(ty) };
+                left = self.finish_node(start, if is_as { Expression::TsAs(expr) } else { Expression::TsSatisfies(expr) });
+                continue;
+            }
+
+            // `function () {} / 2`, the lexer took the `/` for a regex.
+            if self.lexer.token == Token::LiteralRegex {
+                self.rescan_slash(false)?;
+            }
+
+            let (operator, precedence) = match self.binary_operator() {
+                Some(operator) => operator,
+                None => break,
+            };
+
+            if precedence <= min_precedence {
+                break;
+            }
+
+            // `<` right after JSX starts the next element, `<a />\n<b />`.
+            if operator == BinaryOperator::Less {
+                if let Expression::Element(_) | Expression::Fragment(_) = left.item {
+                    break;
+                }
+            }
+
+            self.next_token()?;
+            // `**` is right associative.
+            let right = if operator == BinaryOperator::Exp {
+                self.parse_binary_expression(precedence - 1)?
+            } else {
+                self.parse_binary_expression(precedence)?
+            };
+
+            left = self.finish_node(start, Expression::Binary(BinaryExpression {
+                operator,
+                left,
+                right,
+            }));
+        }
+
+        Ok(left)
+    }
+
+    fn parse_unary_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        self.nested(Self::parse_unary_expression_inner)
+    }
+
+    fn parse_unary_expression_inner(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        let start = self.lexer.start();
+
+        let operator = match self.lexer.token {
+            Token::Sub => Some(UnaryOperator::Minus),
+            Token::Add => Some(UnaryOperator::Plus),
+            Token::Not => Some(UnaryOperator::Not),
+            Token::BitNot => Some(UnaryOperator::BitNot),
+            Token::Identifier if self.lexer.token_text_eq("typeof") => Some(UnaryOperator::Typeof),
+            Token::Identifier if self.lexer.token_text_eq("void") => Some(UnaryOperator::Void),
+            Token::Identifier if self.lexer.token_text_eq("delete") => Some(UnaryOperator::Delete),
+            _ => None,
+        };
+
+        if let Some(operator) = operator {
+            self.next_token()?;
+            let argument = self.parse_unary_expression()?;
+            return Ok(self.finish_node(start, Expression::Unary(UnaryExpression {
+                operator,
+                argument,
+            })));
+        }
+
+        if self.is_keyword("await") {
+            self.next_token()?;
+            let argument = self.parse_unary_expression()?;
+            return Ok(self.finish_node(start, Expression::Await(argument)));
+        }
+
+        let update_operator = match self.lexer.token {
+            Token::Increment => Some(UpdateOperator::Increment),
+            Token::Decrement => Some(UpdateOperator::Decrement),
+            _ => None,
+        };
+
+        if let Some(operator) = update_operator {
+            self.next_token()?;
+            let argument = self.parse_unary_expression()?;
+            check_simple_assignment_target(&argument)?;
+            return Ok(self.finish_node(start, Expression::Update(UpdateExpression {
+                operator,
+                prefix: true,
+                argument,
+            })));
+        }
+
+        let expr = self.parse_left_hand_side_expression()?;
+
+        let update_operator = match self.lexer.token {
+            Token::Increment if !self.lexer.newline_before => UpdateOperator::Increment,
+            Token::Decrement if !self.lexer.newline_before => UpdateOperator::Decrement,
+            _ => return Ok(expr),
+        };
+
+        check_simple_assignment_target(&expr)?;
+        self.next_token()?;
+
+        Ok(self.finish_node(start, Expression::Update(UpdateExpression {
+            operator: update_operator,
+            prefix: false,
+            argument: expr,
+        })))
+    }
+
+    fn parse_left_hand_side_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        let start = self.lexer.start();
+        let expr = if self.is_keyword("new") {
+            self.parse_new_expression()?
+        } else {
+            self.parse_primary_expression()?
+        };
+
+        self.parse_member_chain(start, expr, true)
+    }
+
+    fn parse_new_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        self.nested(Self::parse_new_expression_inner)
+    }
+
+    fn parse_new_expression_inner(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        // new Callee
+        // new Callee(arguments)
+        // new Callee<Type>(arguments)
+        let start = self.lexer.start();
+        let meta = self.token_loc();
+        self.next_token()?;
+
+        // new.target
+        if self.lexer.token == Token::Dit {
+            self.next_token()?;
+            if !self.is_keyword("target") {
+                return Err(self.unexpected());
+            }
+            let property = self.parse_identifier_name()?;
+            return Ok(self.finish_node(start, Expression::MetaProperty(MetaProperty { meta, property })));
+        }
+
+        let callee_start = self.lexer.start();
+        let callee = if self.is_keyword("new") {
+            self.parse_new_expression()?
+        } else {
+            self.parse_primary_expression()?
+        };
+        let callee = self.parse_member_chain(callee_start, callee, false)?;
+        let type_args = self.try_parse_ts_type_args_in_expression();
+
+        let arguments = if self.lexer.token == Token::ParenOpen {
+            self.parse_arguments()?
+        } else {
+            List::empty()
+        };
+
+        Ok(self.finish_node(start, Expression::New(NewExpression {
+            callee,
+            type_args,
+            arguments,
+        })))
+    }
+
+    /// Member accesses, calls and tagged templates following `expr`.
+    fn parse_member_chain(&mut self, start: usize, mut expr: Node<'a, Expression<'a>>, allow_call: bool) -> Result<Node<'a, Expression<'a>>, Error> {
+        loop {
+            expr = match self.lexer.token {
+                Token::Dit => {
+                    self.next_token()?;
+                    let property = self.parse_member_property()?;
+                    self.finish_node(start, Expression::StaticMember(StaticMemberExpression {
+                        object: expr,
+                        property,
+                        optional: false,
+                    }))
+                },
+                Token::BracketOpen => {
+                    self.next_token()?;
+                    let property = self.with_in(Self::parse_expression)?;
+                    self.expect(Token::BracketClose)?;
+                    self.finish_node(start, Expression::ComputedMember(ComputedMemberExpression {
+                        object: expr,
+                        property,
+                        optional: false,
+                    }))
+                },
+                Token::TemplateString | Token::TemplateHead => {
+                    let quasi = self.parse_template_literal()?;
+                    self.finish_node(start, Expression::TaggedTemplate(TaggedTemplateExpression {
+                        tag: expr,
+                        type_args: None,
+                        quasi,
+                    }))
+                },
+                Token::ParenOpen if allow_call => {
+                    let arguments = self.parse_arguments()?;
+                    self.finish_node(start, Expression::Call(CallExpression {
+                        callee: expr,
+                        type_args: None,
+                        arguments,
+                        optional: false,
+                    }))
+                },
+                // f<Type>(arguments), tag<Type>`template`
+                Token::Less if allow_call && self.typescript => {
+                    let type_args = match self.try_parse_ts_type_args_in_expression() {
+                        Some(type_args) => Some(type_args),
+                        None => return Ok(expr),
+                    };
+
+                    if self.lexer.token == Token::ParenOpen {
+                        let arguments = self.parse_arguments()?;
+                        self.finish_node(start, Expression::Call(CallExpression {
+                            callee: expr,
+                            type_args,
+                            arguments,
+                            optional: false,
+                        }))
+                    } else {
+                        let quasi = self.parse_template_literal()?;
+                        self.finish_node(start, Expression::TaggedTemplate(TaggedTemplateExpression {
+                            tag: expr,
+                            type_args,
+                            quasi,
+                        }))
+                    }
+                },
+                // expr!
+                Token::Not if self.typescript && !self.lexer.newline_before => {
+                    self.next_token()?;
+                    self.finish_node(start, Expression::TsNonNull(expr))
+                },
+                Token::OptionalChain if allow_call => {
+                    self.next_token()?;
+                    match self.lexer.token {
+                        Token::ParenOpen => {
+                            let arguments = self.parse_arguments()?;
+                            self.finish_node(start, Expression::Call(CallExpression {
+                                callee: expr,
+                                type_args: None,
+                                arguments,
+                                optional: true,
+                            }))
+                        },
+                        Token::BracketOpen => {
+                            self.next_token()?;
+                            let property = self.with_in(Self::parse_expression)?;
+                            self.expect(Token::BracketClose)?;
+                            self.finish_node(start, Expression::ComputedMember(ComputedMemberExpression {
+                                object: expr,
+                                property,
+                                optional: true,
+                            }))
+                        },
+                        _ => {
+                            let property = self.parse_member_property()?;
+                            self.finish_node(start, Expression::StaticMember(StaticMemberExpression {
+                                object: expr,
+                                property,
+                                optional: true,
+                            }))
+                        },
+                    }
+                },
+                _ => return Ok(expr),
+            };
+        }
+    }
+
+    // .name, .#name
+    fn parse_member_property(&mut self) -> Result<Loc<&'a str>, Error> {
+        if self.lexer.token == Token::PrivateIdentifier {
+            let name = self.token_loc();
+            self.next_token()?;
+            return Ok(name);
+        }
+
+        self.parse_identifier_name()
+    }
+
+    // ( a, ...b, )
+    fn parse_arguments(&mut self) -> Result<NodeList<'a, Expression<'a>>, Error> {
+        self.with_in(Self::parse_arguments_list)
+    }
+
+    fn parse_arguments_list(&mut self) -> Result<NodeList<'a, Expression<'a>>, Error> {
+        self.expect(Token::ParenOpen)?;
+
+        let arguments = GrowableList::new();
+        while self.lexer.token != Token::ParenClose {
+            arguments.push(self.arena, self.parse_spread_or_assignment_expression()?);
+
+            if self.lexer.token == Token::Comma {
+                self.next_token()?;
+            } else if self.lexer.token != Token::ParenClose {
+                return Err(self.expected_one_of(&[Token::Comma, Token::ParenClose]));
+            }
+        }
+        self.next_token()?;
+
+        Ok(arguments.as_list())
+    }
+
+    fn parse_spread_or_assignment_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        if self.lexer.token != Token::Spread {
+            return self.parse_assignment_expression();
+        }
+
+        let start = self.lexer.start();
+        self.next_token()?;
+        let argument = self.parse_assignment_expression()?;
+
+        Ok(self.finish_node(start, Expression::Spread(argument)))
+    }
+
+    pub(crate) fn parse_primary_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        let start = self.lexer.start();
+
+        match self.lexer.token {
+            Token::Identifier => {
+                let expr = if self.lexer.token_text_eq("this") {
+                    Expression::This
+                } else if self.lexer.token_text_eq("super") {
+                    Expression::Super
+                } else if self.lexer.token_text_eq("null") {
+                    Expression::Literal(Literal::Null)
+                } else if self.lexer.token_text_eq("true") {
+                    Expression::Literal(Literal::True)
+                } else if self.lexer.token_text_eq("false") {
+                    Expression::Literal(Literal::False)
+                } else if self.lexer.token_text_eq("function") {
+                    let function = self.parse_function(false)?;
+                    return Ok(self.finish_node(start, Expression::Function(function)));
+                } else if self.is_async_function() {
+                    self.next_token()?;
+                    let function = self.parse_function(true)?;
+                    return Ok(self.finish_node(start, Expression::Function(function)));
+                } else if self.lexer.token_text_eq("class") {
+                    let class = self.parse_class(List::empty())?;
+                    return Ok(self.finish_node(start, Expression::Class(class)));
+                } else if self.lexer.token_text_eq("import") {
+                    return self.parse_import_expression();
+                } else if self.is_reserved_word() {
+                    return Err(Error::UnexpectedToken);
+                } else {
+                    Expression::Identifier(self.lexer.slice_source(start, self.lexer.end()))
+                };
+
+                self.next_token()?;
+                Ok(self.finish_node(start, expr))
+            },
+            Token::LiteralString => {
+                self.next_token()?;
+                Ok(self.finish_node(start, Expression::Literal(Literal::String)))
+            },
+            Token::LiteralNumber => {
+                let literal = if self.lexer.slice_source(start, self.lexer.end()).ends_with('n') {
+                    Literal::BigInt
+                } else {
+                    Literal::Number
+                };
+                self.next_token()?;
+                Ok(self.finish_node(start, Expression::Literal(literal)))
+            },
+            // `if (a) /b/.test(c)`, the lexer took the `/` for a division.
+            Token::Div | Token::DivAssign => {
+                self.rescan_slash(true)?;
+                self.parse_primary_expression()
+            },
+            Token::LiteralRegex => {
+                self.next_token()?;
+                Ok(self.finish_node(start, Expression::Literal(Literal::RegExp)))
+            },
+            Token::TemplateString | Token::TemplateHead => {
+                let template = self.parse_template_literal()?;
+                Ok(self.alloc(Loc::new(template.start, template.end, Expression::Template(template.item))))
+            },
+            Token::ParenOpen => {
+                self.next_token()?;
+                let expr = self.with_in(Self::parse_expression)?;
+                self.expect(Token::ParenClose)?;
+                Ok(self.finish_node(start, Expression::Parenthesized(expr)))
+            },
+            Token::BracketOpen => self.with_in(Self::parse_array_literal),
+            Token::BraceOpen => self.with_in(Self::parse_object_literal),
+            Token::Less => self.with_in(Self::parse_jsx_expression),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    // import(source), import.meta
+    fn parse_import_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        let start = self.lexer.start();
+        let meta = self.token_loc();
+        self.next_token()?;
+
+        match self.lexer.token {
+            Token::Dit => {
+                self.next_token()?;
+                if !self.is_keyword("meta") {
+                    return Err(self.unexpected());
+                }
+                let property = self.parse_identifier_name()?;
+                Ok(self.finish_node(start, Expression::MetaProperty(MetaProperty { meta, property })))
+            },
+            Token::ParenOpen => {
+                self.next_token()?;
+                let source = self.with_in(Self::parse_assignment_expression)?;
+                if self.lexer.token == Token::Comma {
+                    self.next_token()?;
+                }
+                self.expect(Token::ParenClose)?;
+                Ok(self.finish_node(start, Expression::Import(source)))
+            },
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_jsx_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        let start = self.lexer.start();
+
+        match self.lexer.token {
+            Token::Less => {
+                // JSX, the `<` is lexed again as a tag.
+                self.lexer.reset(start);
+                self.consume_with(Lexer::consume_jsx).map_err(unexpected_end_of_program)?;
+
+                let expr = match self.lexer.token {
+                    Token::ElementOpen => {
+                        let elem = self.parse_elem().map_err(unexpected_end_of_program)?;
+                        Expression::Element(self.alloc(Loc::new(start, self.lexer.end(), elem)))
+                    },
+                    Token::FragmentOpen => {
+                        let fragment = self.parse_fragment().map_err(unexpected_end_of_program)?;
+                        Expression::Fragment(self.alloc(Loc::new(start, self.lexer.end(), fragment)))
+                    },
+                    _ => return Err(Error::UnexpectedToken),
+                };
+
+                self.next_token()?;
+                Ok(self.finish_node(start, expr))
+            },
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_template_literal(&mut self) -> Result<Loc<TemplateLiteral<'a>>, Error> {
+        // `abc`
+        // `abc${ expr }def${ expr }ghi`
+        let start = self.lexer.start();
+        let quasis = GrowableList::new();
+        let expressions = GrowableList::new();
+
+        loop {
+            let (token_start, token_end) = self.lexer.loc();
+            match self.lexer.token {
+                Token::TemplateString | Token::TemplateTail => {
+                    quasis.push(self.arena, Loc::new(token_start + 1, token_end - 1, TemplateElement { tail: true }));
+                    self.next_token()?;
+                    break;
+                },
+                Token::TemplateHead | Token::TemplateMiddle => {
+                    quasis.push(self.arena, Loc::new(token_start + 1, token_end - 2, TemplateElement { tail: false }));
+                },
+                _ => return Err(self.unexpected()),
+            }
+
+            self.next_token()?;
+            expressions.push(self.arena, self.with_in(Self::parse_expression)?);
+            self.consume_with(Lexer::consume_template)?;
+        }
+
+        Ok(self.finish(start, TemplateLiteral { quasis: quasis.as_list(), expressions: expressions.as_list() }))
+    }
+
+    fn parse_array_literal(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        // [ a, , ...b ]
+        let start = self.lexer.start();
+        self.expect(Token::BracketOpen)?;
+
+        let elements = GrowableList::new();
+        while self.lexer.token != Token::BracketClose {
+            if self.lexer.token == Token::Comma {
+                self.next_token()?;
+                elements.push(self.arena, None);
+                continue;
+            }
+
+            elements.push(self.arena, Some(self.parse_spread_or_assignment_expression()?));
+
+            if self.lexer.token == Token::Comma {
+                self.next_token()?;
+            } else if self.lexer.token != Token::BracketClose {
+                return Err(self.expected_one_of(&[Token::Comma, Token::BracketClose]));
+            }
+        }
+        self.next_token()?;
+
+        Ok(self.finish_node(start, Expression::Array(ArrayExpression { elements: elements.as_list() })))
+    }
+
+    fn parse_property_key(&mut self) -> Result<PropertyKey<'a>, Error> {
+        let (start, end) = self.lexer.loc();
+
+        let key = match self.lexer.token {
+            Token::Identifier => PropertyKey::Identifier(self.token_loc()),
+            Token::PrivateIdentifier => PropertyKey::PrivateName(self.token_loc()),
+            Token::LiteralString => PropertyKey::Literal(Loc::new(start, end, Literal::String)),
+            Token::LiteralNumber => PropertyKey::Literal(Loc::new(start, end, Literal::Number)),
+            Token::BracketOpen => {
+                self.next_token()?;
+                let expr = self.with_in(Self::parse_assignment_expression)?;
+                if self.lexer.token != Token::BracketClose {
+                    return Err(self.unexpected());
+                }
+                PropertyKey::Computed(expr)
+            },
+            _ => return Err(self.unexpected()),
+        };
+        self.next_token()?;
+
+        Ok(key)
+    }
+
+    /// `get`, `set` and `async` are only modifiers when a property key follows.
+    fn is_method_modifier(&self) -> bool {
+        let is_modifier = self.is_keyword("get") || self.is_keyword("set") || self.is_keyword("async");
+        if !is_modifier {
+            return false;
+        }
+
+        match self.peek() {
+            (Token::ParenOpen, _) | (Token::Colon, _) | (Token::Comma, _) | (Token::Semicolon, _)
+            | (Token::BraceClose, _) | (Token::Assign, _) => false,
+            (_, newline_before) => !(newline_before && self.is_keyword("async")),
+        }
+    }
+
+    fn parse_object_literal(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        // { a: 1, b, [c]: 2, ...d, e() {}, get f() {}, async *g() {} }
+        let start = self.lexer.start();
+        self.expect(Token::BraceOpen)?;
+
+        let properties = GrowableList::new();
+        while self.lexer.token != Token::BraceClose {
+            let property_start = self.lexer.start();
+
+            let property = if self.lexer.token == Token::Spread {
+                self.next_token()?;
+                Property::Spread(self.parse_assignment_expression()?)
+            } else {
+                let mut kind = MethodKind::Method;
+                let mut is_async = false;
+                let mut is_generator = false;
+
+                if self.is_method_modifier() {
+                    if self.is_keyword("get") {
+                        kind = MethodKind::Get;
+                    } else if self.is_keyword("set") {
+                        kind = MethodKind::Set;
+                    } else {
+                        is_async = true;
+                    }
+                    self.next_token()?;
+                }
+
+                if self.lexer.token == Token::Mul {
+                    is_generator = true;
+                    self.next_token()?;
+                }
+
+                let key = self.parse_property_key()?;
+                if let PropertyKey::PrivateName(_) = key {
+                    return Err(Error::UnexpectedToken);
+                }
+                let is_plain = kind == MethodKind::Method && !is_async && !is_generator;
+
+                match (self.lexer.token, key) {
+                    (token, key) if token == Token::ParenOpen || (token == Token::Less && self.typescript) => {
+                        let function_start = self.lexer.start();
+                        let value = self.parse_function_rest(None, is_async, is_generator)?;
+                        Property::Method { kind, key, value: self.finish(function_start, value) }
+                    },
+                    (Token::Colon, key) if is_plain => {
+                        self.next_token()?;
+                        Property::Init { key, value: self.parse_assignment_expression()? }
+                    },
+                    // { a = 1 } is only valid as a pattern, it is kept as { a: a = 1 }.
+                    (Token::Assign, PropertyKey::Identifier(name)) if is_plain => {
+                        self.next_token()?;
+                        let right = self.parse_assignment_expression()?;
+                        let value = self.finish_node(name.start, Expression::Assignment(AssignmentExpression {
+                            operator: AssignmentOperator::Assign,
+                            left: self.alloc(Loc::new(name.start, name.end, Pattern::Identifier(name.item))),
+                            right,
+                        }));
+                        Property::Init { key: PropertyKey::Identifier(name), value }
+                    },
+                    (_, PropertyKey::Identifier(name)) if is_plain => {
+                        Property::Shorthand(name)
+                    },
+                    _ => return Err(self.unexpected()),
+                }
+            };
+
+            properties.push(self.arena, self.finish_node(property_start, property));
+
+            if self.lexer.token == Token::Comma {
+                self.next_token()?;
+            } else if self.lexer.token != Token::BraceClose {
+                return Err(self.expected_one_of(&[Token::Comma, Token::BraceClose]));
+            }
+        }
+        self.next_token()?;
+
+        Ok(self.finish_node(start, Expression::Object(ObjectExpression { properties: properties.as_list() })))
+    }
+
+    /// `function name(params) { body }`, the current token is `function`.
+    fn parse_function(&mut self, is_async: bool) -> Result<Function<'a>, Error> {
+        let (id, is_generator) = self.parse_function_start()?;
+        self.parse_function_rest(id, is_async, is_generator)
+    }
+
+    /// `function* name`, the current token is `function`.
+    fn parse_function_start(&mut self) -> Result<(Option<Loc<&'a str>>, bool), Error> {
+        self.next_token()?;
+
+        let mut is_generator = false;
+        if self.lexer.token == Token::Mul {
+            is_generator = true;
+            self.next_token()?;
+        }
+
+        let id = if self.lexer.token == Token::Identifier {
+            Some(self.parse_binding_identifier()?)
+        } else {
+            None
+        };
+
+        Ok((id, is_generator))
+    }
+
+    /// The parameters and body of a function, the current token is `(` or `<`.
+    fn parse_function_rest(&mut self, id: Option<Loc<&'a str>>, is_async: bool, is_generator: bool) -> Result<Function<'a>, Error> {
+        let (type_params, params, return_type) = self.parse_function_head(true)?;
+        let body = self.parse_function_body(is_generator)?;
+
+        Ok(Function {
+            id,
+            is_async,
+            is_generator,
+            type_params,
+            params,
+            return_type,
+            body,
+        })
+    }
+
+    // <T>(params): ReturnType
+    #[allow(clippy::type_complexity)]
+    fn parse_function_head(&mut self, allow_return_type: bool) -> Result<(Option<Node<'a, TsTypeParameters<'a>>>, NodeList<'a, Pattern<'a>>, Option<Node<'a, TsTypeAnnotation<'a>>>), Error> {
+        let type_params = self.parse_ts_type_params_opt()?;
+        let params = self.parse_formal_params()?;
+        let return_type = if allow_return_type {
+            self.parse_ts_return_type_annotation()?
+        } else {
+            None
+        };
+
+        Ok((type_params, params, return_type))
+    }
+
+    // ( a, b = 1, { c }, ...d )
+    fn parse_formal_params(&mut self) -> Result<NodeList<'a, Pattern<'a>>, Error> {
+        self.expect(Token::ParenOpen)?;
+
+        let params = GrowableList::new();
+        while self.lexer.token != Token::ParenClose {
+            if self.lexer.token == Token::Spread {
+                params.push(self.arena, self.parse_rest_element()?);
+                if self.lexer.token == Token::Comma && self.typescript {
+                    self.next_token()?;
+                }
+                if self.lexer.token != Token::ParenClose {
+                    return Err(self.unexpected());
+                }
+                break;
+            }
+
+            // function f(this: Window) {}
+            if params.as_list().is_empty() && self.typescript && self.is_keyword("this") && self.peek().0 == Token::Colon {
+                let this = self.alloc(Loc::new(self.lexer.start(), self.lexer.end(), Expression::This));
+                self.next_token()?;
+                let pattern = self.alloc(Loc::new(this.start, this.end, Pattern::Expression(this)));
+                params.push(self.arena, self.parse_ts_binding_annotation(pattern, false, false)?);
+            } else if self.parameter_modifier().is_some() {
+                params.push(self.arena, self.parse_ts_parameter_property()?);
+            } else {
+                params.push(self.arena, self.parse_binding_element()?);
+            }
+
+            if self.lexer.token == Token::Comma {
+                self.next_token()?;
+            } else if self.lexer.token != Token::ParenClose {
+                return Err(self.expected_one_of(&[Token::Comma, Token::ParenClose]));
+            }
+        }
+        self.next_token()?;
+
+        Ok(params.as_list())
+    }
+
+    fn parse_function_body(&mut self, is_generator: bool) -> Result<Loc<BlockStatement<'a>>, Error> {
+        self.nested(|parser| parser.parse_function_body_inner(is_generator))
+    }
+
+    fn parse_function_body_inner(&mut self, is_generator: bool) -> Result<Loc<BlockStatement<'a>>, Error> {
+        let in_generator = self.in_generator;
+        self.in_generator = is_generator;
+        let body = self.with_in(Self::parse_block_statement);
+        self.in_generator = in_generator;
+
+        body
+    }
+
+    /// `class Name<T> extends Base<T> implements A { members }`, the current token is `class`.
+    fn parse_class(&mut self, modifiers: List<'a, Loc<TsModifier>>) -> Result<Class<'a>, Error> {
+        self.nested(|parser| parser.parse_class_inner(modifiers))
+    }
+
+    fn parse_class_inner(&mut self, modifiers: List<'a, Loc<TsModifier>>) -> Result<Class<'a>, Error> {
+        self.next_token()?;
+
+        let id = if self.lexer.token == Token::Identifier && !self.is_keyword("extends") && !self.is_keyword("implements") {
+            Some(self.parse_binding_identifier()?)
+        } else {
+            None
+        };
+        let type_params = self.parse_ts_type_params_opt()?;
+
+        let mut super_type_args = None;
+        let super_class = if self.is_keyword("extends") {
+            self.next_token()?;
+            let super_class = self.parse_left_hand_side_expression()?;
+            if self.typescript && self.lexer.token == Token::Less {
+                super_type_args = Some(self.node(Self::parse_ts_type_args)?);
+            }
+            Some(super_class)
+        } else {
+            None
+        };
+
+        let implements = if self.typescript && self.is_keyword("implements") {
+            let start = self.lexer.start();
+            self.next_token()?;
+            let types = GrowableList::new();
+            types.push(self.arena, self.node(Self::parse_ts_type_reference)?);
+            while self.lexer.token == Token::Comma {
+                self.next_token()?;
+                types.push(self.arena, self.node(Self::parse_ts_type_reference)?);
+            }
+            Some(self.finish(start, types.as_list()))
+        } else {
+            None
+        };
+
+        self.expect(Token::BraceOpen)?;
+
+        let body = GrowableList::new();
+        while self.lexer.token != Token::BraceClose {
+            if self.lexer.token == Token::Semicolon {
+                self.next_token()?;
+                continue;
+            }
+            body.push(self.arena, self.with_in(Self::parse_class_member)?);
+        }
+        self.next_token()?;
+
+        Ok(Class {
+            modifiers,
+            id,
+            type_params,
+            super_class,
+            super_type_args,
+            implements,
+            body: body.as_list(),
+        })
+    }
+
+    fn parse_class_member(&mut self) -> Result<Node<'a, ClassMember<'a>>, Error> {
+        // constructor() {}
+        // static async *key() {}
+        // get key() {}
+        // #key = value;
+        // static { }
+        // private readonly key?: Type = value;
+        // [key: string]: Type;
+        let start = self.lexer.start();
+
+        let modifiers = GrowableList::new();
+        let mut is_static = false;
+        while self.lexer.token == Token::Identifier && self.is_member_modifier() {
+            if self.is_keyword("static") && !is_static {
+                is_static = true;
+                self.next_token()?;
+
+                if modifiers.as_list().is_empty() && self.lexer.token == Token::BraceOpen {
+                    let block = self.parse_function_body(false)?;
+                    return Ok(self.finish_node(start, ClassMember::StaticBlock(block.item)));
+                }
+                continue;
+            }
+
+            let modifier = match self.ts_modifier() {
+                Some(modifier) => modifier,
+                None => break,
+            };
+            let (modifier_start, modifier_end) = self.lexer.loc();
+            self.next_token()?;
+            modifiers.push(self.arena, Loc::new(modifier_start, modifier_end, modifier));
+        }
+
+        if self.typescript && self.lexer.token == Token::BracketOpen && self.is_ts_index_signature() {
+            let signature = self.parse_ts_index_signature(modifiers.as_list(), is_static)?;
+            self.consume_semicolon()?;
+            return Ok(self.finish_node(start, ClassMember::TsIndexSignature(signature)));
+        }
+
+        let mut kind = MethodKind::Method;
+        let mut is_async = false;
+        let mut is_generator = false;
+
+        if self.is_method_modifier() {
+            if self.is_keyword("get") {
+                kind = MethodKind::Get;
+            } else if self.is_keyword("set") {
+                kind = MethodKind::Set;
+            } else {
+                is_async = true;
+            }
+            self.next_token()?;
+        }
+
+        if self.lexer.token == Token::Mul {
+            is_generator = true;
+            self.next_token()?;
+        }
+
+        let is_plain = kind == MethodKind::Method && !is_async && !is_generator;
+        let is_constructor = is_plain && !is_static && self.is_keyword("constructor");
+        let key = self.parse_property_key()?;
+
+        let optional = if self.typescript && self.lexer.token == Token::Question {
+            let optional = self.token_loc();
+            self.next_token()?;
+            Some(optional)
+        } else {
+            None
+        };
+
+        if self.lexer.token == Token::ParenOpen || (self.typescript && self.lexer.token == Token::Less) {
+            if is_constructor {
+                kind = MethodKind::Constructor;
+            }
+
+            let function_start = self.lexer.start();
+            let (type_params, params, return_type) = self.parse_function_head(true)?;
+
+            // Overloads and abstract methods have no body.
+            if self.typescript && self.lexer.token != Token::BraceOpen {
+                self.consume_semicolon()?;
+                let value = self.finish(function_start, TsDeclareFunction {
+                    id: None,
+                    is_async,
+                    is_generator,
+                    type_params,
+                    params,
+                    return_type,
+                });
+                return Ok(self.finish_node(start, ClassMember::TsMethodSignature { modifiers: modifiers.as_list(), is_static, kind, key, optional, value }));
+            }
+
+            let body = self.parse_function_body(is_generator)?;
+            let value = self.finish(function_start, Function {
+                id: None,
+                is_async,
+                is_generator,
+                type_params,
+                params,
+                return_type,
+                body,
+            });
+
+            return Ok(self.finish_node(start, ClassMember::Method { modifiers: modifiers.as_list(), is_static, kind, key, optional, value }));
+        }
+
+        if !is_plain || is_constructor {
+            return Err(self.unexpected());
+        }
+
+        let annotation = self.parse_ts_type_annotation(optional.map(|optional| optional.start), true)?;
+
+        let value = if self.lexer.token == Token::Assign {
+            self.next_token()?;
+
+            let in_generator = self.in_generator;
+            self.in_generator = false;
+            let value = self.parse_assignment_expression();
+            self.in_generator = in_generator;
+
+            Some(value?)
+        } else {
+            None
+        };
+        self.consume_semicolon()?;
+
+        Ok(self.finish_node(start, ClassMember::Property { modifiers: modifiers.as_list(), is_static, key, annotation, value }))
+    }
+
+    fn ts_modifier(&self) -> Option<TsModifier> {
+        if !self.typescript {
+            return None;
+        }
+
+        let modifier = match () {
+            _ if self.is_keyword("public") => TsModifier::Public,
+            _ if self.is_keyword("private") => TsModifier::Private,
+            _ if self.is_keyword("protected") => TsModifier::Protected,
+            _ if self.is_keyword("readonly") => TsModifier::Readonly,
+            _ if self.is_keyword("abstract") => TsModifier::Abstract,
+            _ if self.is_keyword("override") => TsModifier::Override,
+            _ if self.is_keyword("declare") => TsModifier::Declare,
+            _ => return None,
+        };
+
+        Some(modifier)
+    }
+
+    /// `public`, `private`, `protected`, `readonly` and `override` when a
+    /// parameter name follows, `(readonly: boolean)` is a parameter.
+    fn parameter_modifier(&self) -> Option<TsModifier> {
+        match self.ts_modifier() {
+            Some(TsModifier::Abstract) | Some(TsModifier::Declare) | None => None,
+            Some(_) if self.peek().0 != Token::Identifier => None,
+            modifier => modifier,
+        }
+    }
+
+    // constructor(private readonly name: Type = value)
+    fn parse_ts_parameter_property(&mut self) -> Result<Node<'a, Pattern<'a>>, Error> {
+        let start = self.lexer.start();
+        let modifiers = GrowableList::new();
+        while let Some(modifier) = self.parameter_modifier() {
+            modifiers.push(self.arena, self.parse_ts_modifier(modifier)?);
+        }
+        let parameter = self.parse_binding_element()?;
+
+        Ok(self.finish_node(start, Pattern::TsParameterProperty(TsParameterProperty { modifiers: modifiers.as_list(), parameter })))
+    }
+
+    /// `static` and the TypeScript modifiers are only modifiers when a property key follows.
+    fn is_member_modifier(&self) -> bool {
+        !matches!(self.peek().0, Token::ParenOpen | Token::Assign | Token::Semicolon | Token::BraceClose
+            | Token::Colon | Token::Question | Token::Less | Token::Not | Token::EndOfProgram)
+    }
+
+    // Modules
+
+    fn parse_import_declaration(&mut self) -> Result<Node<'a, Statement<'a>>, Error> {
+        // import "source";
+        // import name, { imported as local } from "source";
+        // import name, * as local from "source";
+        // import type { A } from "source";
+        let start = self.lexer.start();
+        self.next_token()?;
+
+        let is_type = self.typescript && self.is_keyword("type") && match self.peek().0 {
+            Token::BraceOpen | Token::Mul => true,
+            Token::Identifier => {
+                let mut lexer = self.lexer.clone();
+                lexer.consume().is_ok() && !lexer.token_text_eq("from")
+            },
+            _ => false,
+        };
+        if is_type {
+            self.next_token()?;
+        }
+
+        let specifiers = GrowableList::new();
+        if self.lexer.token != Token::LiteralString {
+            if self.lexer.token == Token::Identifier {
+                let local = self.parse_binding_identifier()?;
+                specifiers.push(self.arena, self.alloc(Loc::new(local.start, local.end, ImportSpecifier::Default(local))));
+            }
+
+            let has_more = specifiers.as_list().is_empty() || self.lexer.token == Token::Comma;
+            if !specifiers.as_list().is_empty() && has_more {
+                self.next_token()?;
+            }
+
+            if has_more {
+                match self.lexer.token {
+                    Token::Mul => {
+                        let namespace_start = self.lexer.start();
+                        self.next_token()?;
+                        self.expect_keyword("as")?;
+                        let local = self.parse_binding_identifier()?;
+                        specifiers.push(self.arena, self.finish_node(namespace_start, ImportSpecifier::Namespace(local)));
+                    },
+                    Token::BraceOpen => {
+                        self.next_token()?;
+
+                        while self.lexer.token != Token::BraceClose {
+                            let specifier_start = self.lexer.start();
+                            let is_type = self.is_ts_type_specifier()?;
+                            let imported = self.parse_module_export_name()?;
+                            let local = if self.is_keyword("as") {
+                                self.next_token()?;
+                                self.parse_binding_identifier()?
+                            } else if !imported.item.starts_with(['"', '\'']) {
+                                imported
+                            } else {
+                                return Err(self.unexpected());
+                            };
+                            specifiers.push(self.arena, self.finish_node(specifier_start, ImportSpecifier::Named { is_type, imported, local }));
+
+                            if self.lexer.token == Token::Comma {
+                                self.next_token()?;
+                            } else if self.lexer.token != Token::BraceClose {
+                                return Err(self.expected_one_of(&[Token::Comma, Token::BraceClose]));
+                            }
+                        }
+                        self.next_token()?;
+                    },
+                    _ => return Err(self.unexpected()),
+                }
+            }
+
+            self.expect_keyword("from")?;
+        }
+
+        let source = self.parse_module_source()?;
+        self.consume_semicolon()?;
+
+        Ok(self.finish_node(start, Statement::Import(ImportDeclaration { is_type, specifiers: specifiers.as_list(), source })))
+    }
+
+    fn parse_export_declaration(&mut self) -> Result<Node<'a, Statement<'a>>, Error> {
+        // export default expr;
+        // export * as exported from "source";
+        // export { local as exported } from "source";
+        // export const name = value;
+        let start = self.lexer.start();
+        self.next_token()?;
+
+        if self.is_keyword("default") {
+            self.next_token()?;
+
+            let declaration = if self.is_keyword("function") || self.is_async_function() {
+                let is_async = self.is_keyword("async");
+                if is_async {
+                    self.next_token()?;
+                }
+                let (id, is_generator) = self.parse_function_start()?;
+                match self.parse_function_declaration(id, is_async, is_generator)? {
+                    Statement::Function(function) => ExportDefaultDeclaration::Function(function),
+                    Statement::TsDeclareFunction(function) => ExportDefaultDeclaration::TsDeclareFunction(function),
+                    _ => return Err(Error::UnexpectedToken),
+                }
+            } else if self.is_keyword("class") {
+                ExportDefaultDeclaration::Class(self.parse_class(List::empty())?)
+            } else if self.typescript && self.is_keyword("abstract") && self.peek_is_keyword("class") {
+                let modifiers = List::from(self.arena, self.parse_ts_modifier(TsModifier::Abstract)?);
+                ExportDefaultDeclaration::Class(self.parse_class(modifiers)?)
+            } else if self.typescript && self.is_keyword("interface") {
+                ExportDefaultDeclaration::TsInterface(self.parse_ts_interface()?)
+            } else {
+                let expr = self.parse_assignment_expression()?;
+                self.consume_semicolon()?;
+                ExportDefaultDeclaration::Expression(expr)
+            };
+
+            return Ok(self.finish_node(start, Statement::ExportDefault(declaration)));
+        }
+
+        // export type { A }, export type * from "source"
+        let is_type = self.typescript && self.is_keyword("type")
+            && matches!(self.peek().0, Token::BraceOpen | Token::Mul);
+        if is_type {
+            self.next_token()?;
+        }
+
+        if self.lexer.token == Token::Mul {
+            self.next_token()?;
+            let exported = if self.is_keyword("as") {
+                self.next_token()?;
+                Some(self.parse_module_export_name()?)
+            } else {
+                None
+            };
+            self.expect_keyword("from")?;
+            let source = self.parse_module_source()?;
+            self.consume_semicolon()?;
+
+            return Ok(self.finish_node(start, Statement::ExportAll(ExportAllDeclaration { is_type, exported, source })));
+        }
+
+        if self.lexer.token == Token::BraceOpen {
+            self.next_token()?;
+
+            let specifiers = GrowableList::new();
+            while self.lexer.token != Token::BraceClose {
+                let specifier_start = self.lexer.start();
+                let is_type = self.is_ts_type_specifier()?;
+                let local = self.parse_module_export_name()?;
+                let exported = if self.is_keyword("as") {
+                    self.next_token()?;
+                    self.parse_module_export_name()?
+                } else {
+                    local
+                };
+                specifiers.push(self.arena, self.finish_node(specifier_start, ExportSpecifier { is_type, local, exported }));
+
+                if self.lexer.token == Token::Comma {
+                    self.next_token()?;
+                } else if self.lexer.token != Token::BraceClose {
+                    return Err(self.expected_one_of(&[Token::Comma, Token::BraceClose]));
+                }
+            }
+            self.next_token()?;
+
+            let source = if self.is_keyword("from") {
+                self.next_token()?;
+                Some(self.parse_module_source()?)
+            } else {
+                None
+            };
+            self.consume_semicolon()?;
+
+            return Ok(self.finish_node(start, Statement::ExportNamed(ExportNamedDeclaration {
+                is_type,
+                declaration: None,
+                specifiers: specifiers.as_list(),
+                source,
+            })));
+        }
+
+        if is_type {
+            return Err(self.unexpected());
+        }
+
+        let declaration = self.parse_statement()?;
+        match declaration.item {
+            Statement::Variable(_) | Statement::Function(_) | Statement::Class(_)
+            | Statement::TsInterface(_) | Statement::TsTypeAlias(_) | Statement::TsEnum(_)
+            | Statement::TsModule(_) | Statement::TsDeclareFunction(_) | Statement::TsDeclare(_) => { },
+            _ => return Err(Error::UnexpectedToken),
+        }
+
+        Ok(self.finish_node(start, Statement::ExportNamed(ExportNamedDeclaration {
+            is_type: false,
+            declaration: Some(declaration),
+            specifiers: List::empty(),
+            source: None,
+        })))
+    }
+
+    /// `{ type A }`, `type` is a modifier unless it is the name, `{ type }`
+    /// and `{ type as A }`.
+    fn is_ts_type_specifier(&mut self) -> Result<bool, Error> {
+        if !self.typescript || !self.is_keyword("type") {
+            return Ok(false);
+        }
+
+        let mut lexer = self.lexer.clone();
+        let is_type = lexer.consume().is_ok()
+            && matches!(lexer.token, Token::Identifier | Token::LiteralString)
+            && !lexer.token_text_eq("as");
+        if is_type {
+            self.next_token()?;
+        }
+
+        Ok(is_type)
+    }
+
+    // name, "string name"
+    fn parse_module_export_name(&mut self) -> Result<Loc<&'a str>, Error> {
+        match self.lexer.token {
+            Token::Identifier | Token::LiteralString => {
+                let name = self.token_loc();
+                self.next_token()?;
+                Ok(name)
+            },
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_module_source(&mut self) -> Result<Loc<Literal>, Error> {
+        if self.lexer.token != Token::LiteralString {
+            return Err(self.unexpected());
+        }
+
+        let source = Loc::new(self.lexer.start(), self.lexer.end(), Literal::String);
+        self.next_token()?;
+
+        Ok(source)
+    }
+
+    fn parse_rest_element(&mut self) -> Result<Node<'a, Pattern<'a>>, Error> {
+        let start = self.lexer.start();
+        self.expect(Token::Spread)?;
+        let target = self.parse_binding_target()?;
+        let rest = self.finish_node(start, Pattern::Rest(target));
+
+        self.parse_ts_binding_annotation(rest, false, false)
+    }
+
+    fn parse_binding_target(&mut self) -> Result<Node<'a, Pattern<'a>>, Error> {
+        self.nested(Self::parse_binding_target_inner)
+    }
+
+    fn parse_binding_target_inner(&mut self) -> Result<Node<'a, Pattern<'a>>, Error> {
+        let start = self.lexer.start();
+
+        match self.lexer.token {
+            Token::Identifier => {
+                let ident = self.parse_binding_identifier()?;
+                Ok(self.alloc(Loc::new(ident.start, ident.end, Pattern::Identifier(ident.item))))
+            },
+            Token::BracketOpen => {
+                // [ a, , b = 1, ...c ]
+                self.next_token()?;
+
+                let elements = GrowableList::new();
+                while self.lexer.token != Token::BracketClose {
+                    if self.lexer.token == Token::Comma {
+                        self.next_token()?;
+                        elements.push(self.arena, None);
+                        continue;
+                    }
+
+                    if self.lexer.token == Token::Spread {
+                        elements.push(self.arena, Some(self.parse_rest_element()?));
+                        if self.lexer.token != Token::BracketClose {
+                            return Err(self.unexpected());
+                        }
+                        break;
+                    }
+
+                    elements.push(self.arena, Some(self.parse_binding_element()?));
+
+                    if self.lexer.token == Token::Comma {
+                        self.next_token()?;
+                    } else if self.lexer.token != Token::BracketClose {
+                        return Err(self.expected_one_of(&[Token::Comma, Token::BracketClose]));
+                    }
+                }
+                self.next_token()?;
+
+                Ok(self.finish_node(start, Pattern::Array(ArrayPattern { elements: elements.as_list() })))
+            },
+            Token::BraceOpen => {
+                // { a, b: c, d = 1, ...e }
+                self.next_token()?;
+
+                let properties = GrowableList::new();
+                while self.lexer.token != Token::BraceClose {
+                    let property_start = self.lexer.start();
+
+                    let property = if self.lexer.token == Token::Spread {
+                        self.next_token()?;
+                        let ident = self.parse_binding_identifier()?;
+                        PatternProperty::Rest(self.alloc(Loc::new(ident.start, ident.end, Pattern::Identifier(ident.item))))
+                    } else {
+                        match self.parse_property_key()? {
+                            key if self.lexer.token == Token::Colon => {
+                                self.next_token()?;
+                                PatternProperty::Property { key, value: self.parse_binding_element()? }
+                            },
+                            PropertyKey::Identifier(name) => {
+                                let init = if self.lexer.token == Token::Assign {
+                                    self.next_token()?;
+                                    Some(self.parse_assignment_expression()?)
+                                } else {
+                                    None
+                                };
+                                PatternProperty::Shorthand { name, init }
+                            },
+                            _ => return Err(self.unexpected()),
+                        }
+                    };
+
+                    properties.push(self.arena, self.finish_node(property_start, property));
+
+                    if self.lexer.token == Token::Comma {
+                        self.next_token()?;
+                    } else if self.lexer.token != Token::BraceClose {
+                        return Err(self.expected_one_of(&[Token::Comma, Token::BraceClose]));
+                    }
+                }
+                self.next_token()?;
+
+                Ok(self.finish_node(start, Pattern::Object(ObjectPattern { properties: properties.as_list() })))
+            },
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    // target, target = default, target?: Type = default
+    fn parse_binding_element(&mut self) -> Result<Node<'a, Pattern<'a>>, Error> {
+        let start = self.lexer.start();
+        let target = self.parse_binding_target()?;
+        let target = self.parse_ts_binding_annotation(target, true, false)?;
+
+        if self.lexer.token != Token::Assign {
+            return Ok(target);
+        }
+
+        self.next_token()?;
+        let right = self.parse_assignment_expression()?;
+
+        Ok(self.finish_node(start, Pattern::Assignment(AssignmentPattern {
+            left: target,
+            right,
+        })))
+    }
+
+    /// Reinterprets the left side of `=` as a pattern, `[a, b] = [b, a]`.
+    fn expression_to_pattern(&self, expr: Node<'a, Expression<'a>>, allow_destructuring: bool) -> Result<Node<'a, Pattern<'a>>, Error> {
+        let Loc { start, end, item } = *expr;
+
+        let pattern = match item {
+            Expression::Identifier(name) => Pattern::Identifier(name),
+            Expression::StaticMember(_) | Expression::ComputedMember(_) => Pattern::Expression(expr),
+            // (a as any) = b, a! = b
+            Expression::TsAs(_) | Expression::TsSatisfies(_) | Expression::TsNonNull(_) => {
+                Pattern::Expression(expr)
+            },
+            Expression::Parenthesized(inner) => {
+                return match inner.item {
+                    Expression::Identifier(_) | Expression::StaticMember(_) | Expression::ComputedMember(_)
+                    | Expression::TsAs(_) | Expression::TsSatisfies(_) | Expression::TsNonNull(_) => {
+                        self.expression_to_pattern(inner, false)
+                    },
+                    _ => Err(Error::UnexpectedToken),
+                };
+            },
+            Expression::Array(array) if allow_destructuring => {
+                let elements = GrowableList::new();
+                for element in array.elements {
+                    elements.push(self.arena, match *element {
+                        Some(element) => Some(self.expression_to_binding_element(element)?),
+                        None => None,
+                    });
+                }
+                Pattern::Array(ArrayPattern { elements: elements.as_list() })
+            },
+            Expression::Object(object) if allow_destructuring => {
+                let properties = GrowableList::new();
+                for property in object.properties {
+                    let Loc { start, end, item } = **property;
+                    let property = match item {
+                        Property::Init { key, value } => {
+                            PatternProperty::Property { key, value: self.expression_to_binding_element(value)? }
+                        },
+                        Property::Shorthand(name) => PatternProperty::Shorthand { name, init: None },
+                        Property::Spread(argument) => {
+                            PatternProperty::Rest(self.expression_to_pattern(argument, false)?)
+                        },
+                        Property::Method { .. } => return Err(Error::UnexpectedToken),
+                    };
+                    properties.push(self.arena, self.alloc(Loc::new(start, end, property)));
+                }
+                Pattern::Object(ObjectPattern { properties: properties.as_list() })
+            },
+            _ => return Err(Error::UnexpectedToken),
+        };
+
+        Ok(self.alloc(Loc::new(start, end, pattern)))
+    }
+
+    fn expression_to_binding_element(&self, expr: Node<'a, Expression<'a>>) -> Result<Node<'a, Pattern<'a>>, Error> {
+        let Loc { start, end, item } = *expr;
+
+        match item {
+            Expression::Assignment(AssignmentExpression { operator: AssignmentOperator::Assign, left, right }) => {
+                Ok(self.alloc(Loc::new(start, end, Pattern::Assignment(AssignmentPattern { left, right }))))
+            },
+            Expression::Spread(argument) => {
+                let target = self.expression_to_pattern(argument, true)?;
+                Ok(self.alloc(Loc::new(start, end, Pattern::Rest(target))))
+            },
+            _ => self.expression_to_pattern(expr, true),
+        }
+    }
+
+    // Statements
+
+    /// A statement or, at the top level of the module, an import or export.
+    fn parse_module_item(&mut self) -> Result<Node<'a, Statement<'a>>, Error> {
+        if self.is_keyword("import") && !matches!(self.peek().0, Token::ParenOpen | Token::Dit) {
+            return self.parse_import_declaration();
+        }
+
+        if self.is_keyword("export") {
+            return self.parse_export_declaration();
+        }
+
+        self.parse_statement()
+    }
+
+    pub(crate) fn parse_statement(&mut self) -> Result<Node<'a, Statement<'a>>, Error> {
+        self.nested(Self::parse_statement_inner)
+    }
+
+    fn parse_statement_inner(&mut self) -> Result<Node<'a, Statement<'a>>, Error> {
+        let start = self.lexer.start();
+
+        let stmt = match self.lexer.token {
+            Token::BraceOpen => Statement::Block(self.parse_block_statement()?.item),
+            Token::Semicolon => {
+                self.next_token()?;
+                Statement::Empty
+            },
+            Token::Identifier => self.parse_keyword_statement()?,
+            _ => self.parse_expression_statement()?,
+        };
+
+        Ok(self.finish_node(start, stmt))
+    }
+
+    fn parse_keyword_statement(&mut self) -> Result<Statement<'a>, Error> {
+        let is_const_enum = self.typescript && self.is_keyword("const") && self.peek_is_keyword("enum");
+        if self.is_keyword("var") || (self.is_keyword("const") && !is_const_enum) || self.is_let_declaration() {
+            let declaration = self.parse_variable_declaration()?;
+            self.consume_semicolon()?;
+            return Ok(Statement::Variable(declaration));
+        }
+
+        if self.is_keyword("function") || self.is_async_function() {
+            let is_async = self.is_keyword("async");
+            if is_async {
+                self.next_token()?;
+            }
+
+            let (id, is_generator) = self.parse_function_start()?;
+            if id.is_none() {
+                return Err(Error::UnexpectedToken);
+            }
+            return self.parse_function_declaration(id, is_async, is_generator);
+        }
+
+        if self.is_keyword("class") {
+            let class = self.parse_class(List::empty())?;
+            if class.id.is_none() {
+                return Err(Error::UnexpectedToken);
+            }
+            return Ok(Statement::Class(class));
+        }
+
+        if self.typescript {
+            if let Some(stmt) = self.try_parse_ts_declaration()? {
+                return Ok(stmt);
+            }
+        }
+
+        if self.is_keyword("if") {
+            self.next_token()?;
+            let test = self.parse_paren_expression()?;
+            let consequent = self.parse_statement()?;
+            let alternate = if self.is_keyword("else") {
+                self.next_token()?;
+                Some(self.parse_statement()?)
+            } else {
+                None
+            };
+
+            return Ok(Statement::If(IfStatement {
+                test,
+                consequent,
+                alternate,
+            }));
+        }
+
+        if self.is_keyword("for") {
+            return self.parse_for_statement();
+        }
+
+        if self.is_keyword("while") {
+            self.next_token()?;
+            let test = self.parse_paren_expression()?;
+            let body = self.parse_statement()?;
+            return Ok(Statement::While(WhileStatement { test, body }));
+        }
+
+        if self.is_keyword("do") {
+            self.next_token()?;
+            let body = self.parse_statement()?;
+            self.expect_keyword("while")?;
+            let test = self.parse_paren_expression()?;
+            // The semicolon after `do ... while ()` is always optional.
+            if self.lexer.token == Token::Semicolon {
+                self.next_token()?;
+            }
+            return Ok(Statement::DoWhile(DoWhileStatement { body, test }));
+        }
+
+        if self.is_keyword("return") {
+            self.next_token()?;
+            let argument = if self.can_insert_semicolon() {
+                None
+            } else {
+                Some(self.parse_expression()?)
+            };
+            self.consume_semicolon()?;
+            return Ok(Statement::Return(argument));
+        }
+
+        if self.is_keyword("break") || self.is_keyword("continue") {
+            let is_break = self.is_keyword("break");
+            self.next_token()?;
+            let label = if self.lexer.token == Token::Identifier && !self.lexer.newline_before {
+                Some(self.parse_binding_identifier()?)
+            } else {
+                None
+            };
+            self.consume_semicolon()?;
+            return Ok(if is_break { Statement::Break(label) } else { Statement::Continue(label) });
+        }
+
+        if self.is_keyword("throw") {
+            self.next_token()?;
+            if self.lexer.newline_before {
+                return Err(Error::UnexpectedToken);
+            }
+            let argument = self.parse_expression()?;
+            self.consume_semicolon()?;
+            return Ok(Statement::Throw(argument));
+        }
+
+        if self.is_keyword("try") {
+            return self.parse_try_statement();
+        }
+
+        if self.is_keyword("switch") {
+            return self.parse_switch_statement();
+        }
+
+        if self.is_keyword("debugger") {
+            self.next_token()?;
+            self.consume_semicolon()?;
+            return Ok(Statement::Debugger);
+        }
+
+        if self.is_keyword("with") {
+            self.next_token()?;
+            let object = self.parse_paren_expression()?;
+            let body = self.parse_statement()?;
+            return Ok(Statement::With(WithStatement { object, body }));
+        }
+
+        // label: body
+        if !self.is_reserved_word() && self.peek().0 == Token::Colon {
+            let label = self.parse_binding_identifier()?;
+            self.next_token()?;
+            let body = self.parse_statement()?;
+            return Ok(Statement::Labeled(LabeledStatement { label, body }));
+        }
+
+        self.parse_expression_statement()
+    }
+
+    /// A function declaration or, in TSX, an overload without a body.
+    fn parse_function_declaration(&mut self, id: Option<Loc<&'a str>>, is_async: bool, is_generator: bool) -> Result<Statement<'a>, Error> {
+        let (type_params, params, return_type) = self.parse_function_head(true)?;
+
+        if self.typescript && self.lexer.token != Token::BraceOpen {
+            self.consume_semicolon()?;
+            return Ok(Statement::TsDeclareFunction(TsDeclareFunction {
+                id,
+                is_async,
+                is_generator,
+                type_params,
+                params,
+                return_type,
+            }));
+        }
+
+        let body = self.parse_function_body(is_generator)?;
+        Ok(Statement::Function(Function {
+            id,
+            is_async,
+            is_generator,
+            type_params,
+            params,
+            return_type,
+            body,
+        }))
+    }
+
+    fn parse_expression_statement(&mut self) -> Result<Statement<'a>, Error> {
+        let expr = self.parse_expression()?;
+        self.consume_semicolon()?;
+
+        Ok(Statement::Expression(expr))
+    }
+
+    /// A semicolon may be inserted before the current token.
+    #[inline]
+    fn can_insert_semicolon(&self) -> bool {
+        match self.lexer.token {
+            Token::Semicolon | Token::BraceClose | Token::EndOfProgram => true,
+            _ => self.lexer.newline_before,
+        }
+    }
+
+    // ( expr )
+    fn parse_paren_expression(&mut self) -> Result<Node<'a, Expression<'a>>, Error> {
+        self.expect(Token::ParenOpen)?;
+        let expr = self.with_in(Self::parse_expression)?;
+        self.expect(Token::ParenClose)?;
+
+        Ok(expr)
+    }
+
+    fn parse_for_statement(&mut self) -> Result<Statement<'a>, Error> {
+        // for (init; test; update) body
+        // for (left in right) body
+        // for await (left of right) body
+        self.next_token()?;
+
+        let is_await = self.is_keyword("await");
+        if is_await {
+            self.next_token()?;
+        }
+        self.expect(Token::ParenOpen)?;
+
+        let init_start = self.lexer.start();
+        let init = if self.lexer.token == Token::Semicolon {
+            None
+        } else if self.is_keyword("var") || self.is_keyword("const") || self.is_let_declaration() {
+            let declaration = self.without_in(Self::parse_variable_declaration)?;
+            Some(ForInit::Variable(self.finish(init_start, declaration)))
+        } else {
+            Some(ForInit::Expression(self.without_in(Self::parse_expression)?))
+        };
+
+        let is_in = self.is_keyword("in");
+        if is_in || self.is_keyword("of") {
+            let left = match init {
+                Some(ForInit::Variable(declaration)) => {
+                    if declaration.declarations.only_element().is_none_or(|declarator| declarator.init.is_some()) {
+                        return Err(Error::UnexpectedToken);
+                    }
+                    ForHead::Variable(declaration)
+                },
+                Some(ForInit::Expression(expr)) => ForHead::Pattern(self.expression_to_pattern(expr, true)?),
+                None => return Err(Error::UnexpectedToken),
+            };
+            self.next_token()?;
+
+            let right = if is_in {
+                self.with_in(Self::parse_expression)?
+            } else {
+                self.with_in(Self::parse_assignment_expression)?
+            };
+            self.expect(Token::ParenClose)?;
+            let body = self.parse_statement()?;
+
+            return match (is_in, is_await) {
+                (true, false) => Ok(Statement::ForIn(ForInStatement { left, right, body })),
+                (true, true) => Err(Error::UnexpectedToken),
+                (false, _) => Ok(Statement::ForOf(ForOfStatement { is_await, left, right, body })),
+            };
+        }
+
+        if is_await {
+            return Err(self.unexpected());
+        }
+
+        self.expect(Token::Semicolon)?;
+        let test = if self.lexer.token == Token::Semicolon {
+            None
+        } else {
+            Some(self.with_in(Self::parse_expression)?)
+        };
+        self.expect(Token::Semicolon)?;
+        let update = if self.lexer.token == Token::ParenClose {
+            None
+        } else {
+            Some(self.with_in(Self::parse_expression)?)
+        };
+        self.expect(Token::ParenClose)?;
+        let body = self.parse_statement()?;
+
+        Ok(Statement::For(ForStatement { init, test, update, body }))
+    }
+
+    fn parse_try_statement(&mut self) -> Result<Statement<'a>, Error> {
+        // try { } catch (e) { } finally { }
+        self.next_token()?;
+        let block = self.parse_block_statement()?;
+
+        let handler = if self.is_keyword("catch") {
+            let start = self.lexer.start();
+            self.next_token()?;
+
+            let param = if self.lexer.token == Token::ParenOpen {
+                self.next_token()?;
+                let param = self.parse_binding_target()?;
+                let param = self.parse_ts_binding_annotation(param, false, false)?;
+                self.expect(Token::ParenClose)?;
+                Some(param)
+            } else {
+                None
+            };
+            let body = self.parse_block_statement()?;
+
+            Some(self.finish(start, CatchClause { param, body }))
+        } else {
+            None
+        };
+
+        let finalizer = if self.is_keyword("finally") {
+            self.next_token()?;
+            Some(self.parse_block_statement()?)
+        } else {
+            None
+        };
+
+        if handler.is_none() && finalizer.is_none() {
+            return Err(self.unexpected());
+        }
+
+        Ok(Statement::Try(TryStatement { block, handler, finalizer }))
+    }
+
+    fn parse_switch_statement(&mut self) -> Result<Statement<'a>, Error> {
+        // switch (discriminant) { case test: ... default: ... }
+        self.next_token()?;
+        let discriminant = self.parse_paren_expression()?;
+        self.expect(Token::BraceOpen)?;
+
+        let cases = GrowableList::new();
+        while self.lexer.token != Token::BraceClose {
+            let start = self.lexer.start();
+
+            let test = if self.is_keyword("case") {
+                self.next_token()?;
+                Some(self.with_in(Self::parse_expression)?)
+            } else if self.is_keyword("default") {
+                self.next_token()?;
+                None
+            } else {
+                return Err(self.unexpected());
+            };
+            self.expect(Token::Colon)?;
+
+            let consequent = GrowableList::new();
+            while self.lexer.token != Token::BraceClose && !self.is_keyword("case") && !self.is_keyword("default") {
+                if self.lexer.token == Token::EndOfProgram {
+                    return Err(self.expected(Token::BraceClose.to_string()));
+                }
+                consequent.push(self.arena, self.parse_statement()?);
+            }
+
+            cases.push(self.arena, self.finish_node(start, SwitchCase { test, consequent: consequent.as_list() }));
+        }
+        self.next_token()?;
+
+        Ok(Statement::Switch(SwitchStatement { discriminant, cases: cases.as_list() }))
+    }
+
+    /// `let` is an identifier unless a binding follows it.
+    fn is_let_declaration(&self) -> bool {
+        self.is_keyword("let") && matches!(self.peek().0, Token::Identifier | Token::BracketOpen | Token::BraceOpen)
+    }
+
+    fn parse_block_statement(&mut self) -> Result<Loc<BlockStatement<'a>>, Error> {
+        let start = self.lexer.start();
+        self.expect(Token::BraceOpen)?;
+
+        let body = GrowableList::new();
+        while self.lexer.token != Token::BraceClose {
+            if self.lexer.token == Token::EndOfProgram {
+                return Err(self.expected(Token::BraceClose.to_string()));
+            }
+            body.push(self.arena, self.parse_statement()?);
+        }
+        self.next_token()?;
+
+        Ok(self.finish(start, BlockStatement { body: body.as_list() }))
+    }
+
+    fn parse_variable_declaration(&mut self) -> Result<VariableDeclaration<'a>, Error> {
+        // var a = 1, b
+        let kind = if self.is_keyword("var") {
+            VariableKind::Var
+        } else if self.is_keyword("let") {
+            VariableKind::Let
+        } else {
+            VariableKind::Const
+        };
+        self.next_token()?;
+
+        let declarations = GrowableList::new();
+        loop {
+            let start = self.lexer.start();
+            let id = self.parse_binding_target()?;
+            let id = self.parse_ts_binding_annotation(id, false, true)?;
+            let init = if self.lexer.token == Token::Assign {
+                self.next_token()?;
+                Some(self.parse_assignment_expression()?)
+            } else {
+                None
+            };
+            declarations.push(self.arena, self.finish_node(start, VariableDeclarator { id, init }));
+
+            if self.lexer.token != Token::Comma {
+                break;
+            }
+            self.next_token()?;
+        }
+
+        Ok(VariableDeclaration { kind, declarations: declarations.as_list() })
+    }
+}
+
+
+// TypeScript
+// https://github.com/microsoft/TypeScript/blob/main/doc/spec-ARCHIVED.md
+//
+// Only parsed in `Syntax::Tsx`, every `parse_ts_*_opt` helper is a no-op otherwise.
+
+const TS_KEYWORD_TYPES: &[&str] = &[
+    "any", "unknown", "string", "number", "bigint", "boolean", "symbol", "object",
+    "never", "void", "undefined", "this", "intrinsic",
+];
+
+impl<'a> Parser<'a> {
+    /// The next token is `keyword` on the same line, `declare const`.
+    fn peek_is_keyword(&self, keyword: &str) -> bool {
+        let mut lexer = self.lexer.clone();
+        lexer.consume().is_ok() && !lexer.newline_before && lexer.token == Token::Identifier
+            && lexer.token_text_eq(keyword)
+    }
+
+    #[inline]
+    fn parse_ts_modifier(&mut self, modifier: TsModifier) -> Result<Loc<TsModifier>, Error> {
+        let (start, end) = self.lexer.loc();
+        self.next_token()?;
+        Ok(Loc::new(start, end, modifier))
+    }
+
+    /// The closing `>` of type parameters and arguments, `>>` and `>=` are split.
+    fn expect_ts_greater(&mut self) -> Result<(), Error> {
+        match self.lexer.token {
+            Token::Greater => self.next_token(),
+            Token::ShiftRight | Token::UnsignedShiftRight | Token::GreaterEqual
+            | Token::ShiftRightAssign | Token::UnsignedShiftRightAssign => {
+                let start = self.lexer.start();
+                self.last_end = start + 1;
+                self.lexer.reset(start + 1);
+                match self.consume_with(Lexer::consume) {
+                    Err(Error::EndOfProgram) => Ok(()),
+                    ret => ret,
+                }
+            },
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    #[inline]
+    fn parse_ts_type_params_opt(&mut self) -> Result<Option<Node<'a, TsTypeParameters<'a>>>, Error> {
+        if self.typescript && self.lexer.token == Token::Less {
+            self.parse_ts_type_params().map(|type_params| Some(self.alloc(type_params)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // <const T, in out U extends Constraint = Default,>
+    fn parse_ts_type_params(&mut self) -> Result<Loc<TsTypeParameters<'a>>, Error> {
+        let start = self.lexer.start();
+        self.expect(Token::Less)?;
+
+        let params = GrowableList::new();
+        loop {
+            let param_start = self.lexer.start();
+
+            let modifiers = GrowableList::new();
+            while (self.is_keyword("const") || self.is_keyword("in") || self.is_keyword("out"))
+                && self.peek().0 == Token::Identifier
+            {
+                modifiers.push(self.arena, self.token_loc());
+                self.next_token()?;
+            }
+
+            let name = self.parse_binding_identifier()?;
+            let constraint = if self.is_keyword("extends") {
+                self.next_token()?;
+                Some(self.parse_ts_type()?)
+            } else {
+                None
+            };
+            let default = if self.lexer.token == Token::Assign {
+                self.next_token()?;
+                Some(self.parse_ts_type()?)
+            } else {
+                None
+            };
+            params.push(self.arena, self.finish_node(param_start, TsTypeParameter { modifiers: modifiers.as_list(), name, constraint, default }));
+
+            if self.lexer.token != Token::Comma {
+                break;
+            }
+            self.next_token()?;
+            if self.lexer.token == Token::Greater {
+                break;
+            }
+        }
+        self.expect_ts_greater()?;
+
+        Ok(self.finish(start, TsTypeParameters { params: params.as_list() }))
+    }
+
+    // <A, B>
+    fn parse_ts_type_args(&mut self) -> Result<Loc<TsTypeArguments<'a>>, Error> {
+        let start = self.lexer.start();
+        self.expect(Token::Less)?;
+
+        let params = GrowableList::new();
+        params.push(self.arena, self.node(Self::parse_ts_type)?);
+        while self.lexer.token == Token::Comma {
+            self.next_token()?;
+            params.push(self.arena, self.node(Self::parse_ts_type)?);
+        }
+        self.expect_ts_greater()?;
+
+        Ok(self.finish(start, TsTypeArguments { params: params.as_list() }))
+    }
+
+    /// `f<T>(a)` and ``tag<T>`a` ``, anything else following the `>` makes
+    /// the `<` a less-than operator.
+    fn try_parse_ts_type_args_in_expression(&mut self) -> Option<Node<'a, TsTypeArguments<'a>>> {
+        if !self.typescript || self.lexer.token != Token::Less {
+            return None;
+        }
+
+        let checkpoint = self.checkpoint();
+        match self.parse_ts_type_args() {
+            Ok(type_args) if matches!(self.lexer.token, Token::ParenOpen | Token::TemplateString | Token::TemplateHead) => {
+                Some(self.alloc(type_args))
+            },
+            _ => {
+                self.rewind(checkpoint);
+                None
+            },
+        }
+    }
+
+    /// `pattern?: Type`, `pattern!: Type`, the annotation is kept in a `Pattern::Typed`.
+    fn parse_ts_binding_annotation(&mut self, pattern: Node<'a, Pattern<'a>>, allow_optional: bool, allow_definite: bool) -> Result<Node<'a, Pattern<'a>>, Error> {
+        if !self.typescript {
+            return Ok(pattern);
+        }
+
+        let optional = if allow_optional && self.lexer.token == Token::Question {
+            let start = self.lexer.start();
+            self.next_token()?;
+            Some(start)
+        } else {
+            None
+        };
+
+        match self.parse_ts_type_annotation(optional, allow_definite)? {
+            Some(annotation) => Ok(self.alloc(Loc::new(pattern.start, annotation.end, Pattern::Typed(TypedPattern {
+                pattern,
+                annotation,
+            })))),
+            None => Ok(pattern),
+        }
+    }
+
+    /// `!: Type` and `: Type`, `optional` is the start of an already consumed `?`.
+    fn parse_ts_type_annotation(&mut self, optional: Option<usize>, allow_definite: bool) -> Result<Option<Loc<TsTypeAnnotation<'a>>>, Error> {
+        if !self.typescript {
+            return Ok(None);
+        }
+
+        let start = optional.unwrap_or_else(|| self.lexer.start());
+        let definite = allow_definite && optional.is_none() && self.lexer.token == Token::Not;
+        if definite {
+            self.next_token()?;
+            if self.lexer.token != Token::Colon {
+                return Err(self.unexpected());
+            }
+        }
+
+        let ty = if self.lexer.token == Token::Colon {
+            self.next_token()?;
+            Some(self.node(Self::parse_ts_type)?)
+        } else if optional.is_none() {
+            return Ok(None);
+        } else {
+            None
+        };
+
+        Ok(Some(self.finish(start, TsTypeAnnotation { optional: optional.is_some(), definite, ty })))
+    }
+
+    /// `): Type {`, the span starts at the colon.
+    fn parse_ts_return_type_annotation(&mut self) -> Result<Option<Node<'a, TsTypeAnnotation<'a>>>, Error> {
+        if !self.typescript || self.lexer.token != Token::Colon {
+            return Ok(None);
+        }
+
+        let start = self.lexer.start();
+        self.next_token()?;
+        let ty = self.parse_ts_return_type()?;
+
+        Ok(Some(self.finish_node(start, TsTypeAnnotation { optional: false, definite: false, ty: Some(self.alloc(ty)) })))
+    }
+
+    // Type, value is Type, asserts value, asserts value is Type
+    fn parse_ts_return_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let start = self.lexer.start();
+
+        let asserts = self.is_keyword("asserts") && {
+            let (token, newline_before) = self.peek();
+            token == Token::Identifier && !newline_before
+        };
+        if asserts {
+            self.next_token()?;
+        }
+
+        let is_predicate = asserts || (self.lexer.token == Token::Identifier && self.peek_is_keyword("is"));
+        if !is_predicate {
+            return self.parse_ts_type();
+        }
+
+        let name = self.token_loc();
+        self.next_token()?;
+
+        let ty = if self.is_keyword("is") && !self.lexer.newline_before {
+            self.next_token()?;
+            Some(self.node(Self::parse_ts_type)?)
+        } else {
+            None
+        };
+
+        Ok(self.finish(start, TsType::Predicate(TsTypePredicate { asserts, name, ty })))
+    }
+
+    pub(crate) fn parse_ts_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        self.nested(Self::parse_ts_type_inner)
+    }
+
+    fn parse_ts_type_inner(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        if let Some(function) = self.try_parse_ts_function_type()? {
+            return Ok(function);
+        }
+
+        let start = self.lexer.start();
+        let check = self.parse_ts_non_conditional_type()?;
+
+        // Check extends Extends ? True : False
+        if !self.is_keyword("extends") || self.lexer.newline_before {
+            return Ok(check);
+        }
+        self.next_token()?;
+        let extends = self.parse_ts_non_conditional_type()?;
+        self.expect(Token::Question)?;
+        let true_type = self.parse_ts_type()?;
+        self.expect(Token::Colon)?;
+        let false_type = self.parse_ts_type()?;
+
+        Ok(self.finish(start, TsType::Conditional(TsConditionalType {
+            check: self.alloc(check),
+            extends: self.alloc(extends),
+            true_type: self.alloc(true_type),
+            false_type: self.alloc(false_type),
+        })))
+    }
+
+    // <T>(a: A) => B, new (a: A) => B, abstract new () => B
+    fn try_parse_ts_function_type(&mut self) -> Result<Option<Loc<TsType<'a>>>, Error> {
+        let start = self.lexer.start();
+
+        let is_constructor = self.is_keyword("new")
+            || (self.is_keyword("abstract") && self.peek_is_keyword("new"));
+        if is_constructor {
+            if self.is_keyword("abstract") {
+                self.next_token()?;
+            }
+            self.next_token()?;
+        } else if self.lexer.token != Token::Less && self.lexer.token != Token::ParenOpen {
+            return Ok(None);
+        }
+
+        // (A | B)[] is a parenthesized type.
+        let checkpoint = self.checkpoint();
+        let head = self.parse_ts_type_params_opt()
+            .and_then(|type_params| Ok((type_params, self.parse_formal_params()?)));
+        let (type_params, params) = match head {
+            Ok(head) if self.lexer.token == Token::Arrow => head,
+            Err(e) if is_constructor || self.lexer.token == Token::Less => return Err(e),
+            _ if is_constructor => return Err(self.unexpected()),
+            _ => {
+                self.rewind(checkpoint);
+                return Ok(None);
+            },
+        };
+        self.next_token()?;
+        let return_type = self.parse_ts_return_type()?;
+
+        Ok(Some(self.finish(start, TsType::Function(TsFunctionType {
+            is_constructor,
+            type_params,
+            params,
+            return_type: self.alloc(return_type),
+        }))))
+    }
+
+    #[inline]
+    fn parse_ts_non_conditional_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        self.parse_ts_union_type()
+    }
+
+    // | A | B
+    fn parse_ts_union_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let start = self.lexer.start();
+        let leading = self.lexer.token == Token::BitOr;
+        if leading {
+            self.next_token()?;
+        }
+
+        let first = self.parse_ts_intersection_type()?;
+        if self.lexer.token != Token::BitOr {
+            return Ok(if leading { self.finish(start, first.item) } else { first });
+        }
+
+        let types = GrowableList::new();
+        types.push(self.arena, self.alloc(first));
+        while self.lexer.token == Token::BitOr {
+            self.next_token()?;
+            types.push(self.arena, self.node(Self::parse_ts_intersection_type)?);
+        }
+
+        Ok(self.finish(start, TsType::Union(types.as_list())))
+    }
+
+    // & A & B
+    fn parse_ts_intersection_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let start = self.lexer.start();
+        let leading = self.lexer.token == Token::BitAnd;
+        if leading {
+            self.next_token()?;
+        }
+
+        let first = self.parse_ts_type_operator()?;
+        if self.lexer.token != Token::BitAnd {
+            return Ok(if leading { self.finish(start, first.item) } else { first });
+        }
+
+        let types = GrowableList::new();
+        types.push(self.arena, self.alloc(first));
+        while self.lexer.token == Token::BitAnd {
+            self.next_token()?;
+            types.push(self.arena, self.node(Self::parse_ts_type_operator)?);
+        }
+
+        Ok(self.finish(start, TsType::Intersection(types.as_list())))
+    }
+
+    // keyof T, unique symbol, readonly T[], infer U extends C
+    fn parse_ts_type_operator(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        self.nested(Self::parse_ts_type_operator_inner)
+    }
+
+    fn parse_ts_type_operator_inner(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let start = self.lexer.start();
+
+        let operator = match () {
+            _ if self.is_keyword("keyof") => TsTypeOperatorKind::Keyof,
+            _ if self.is_keyword("unique") => TsTypeOperatorKind::Unique,
+            _ if self.is_keyword("readonly") => TsTypeOperatorKind::Readonly,
+            _ if self.is_keyword("infer") => return self.parse_ts_infer_type(),
+            _ => return self.parse_ts_postfix_type(),
+        };
+
+        // `keyof` alone is a type reference.
+        if matches!(self.peek().0, Token::Comma | Token::Greater | Token::ParenClose | Token::BracketClose
+            | Token::Semicolon | Token::Assign | Token::BraceClose | Token::Dit)
+        {
+            return self.parse_ts_postfix_type();
+        }
+        self.next_token()?;
+        let ty = self.parse_ts_type_operator()?;
+
+        Ok(self.finish(start, TsType::Operator(TsTypeOperator { operator, ty: self.alloc(ty) })))
+    }
+
+    fn parse_ts_infer_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let start = self.lexer.start();
+        self.next_token()?;
+        let name = self.parse_binding_identifier()?;
+
+        // T extends [infer U extends string] ? U : never, a `?` after the
+        // constraint makes the `extends` part of a conditional type.
+        let mut constraint = None;
+        if self.is_keyword("extends") {
+            let checkpoint = self.checkpoint();
+            self.next_token()?;
+            let ty = self.parse_ts_non_conditional_type()?;
+            if self.lexer.token == Token::Question {
+                self.rewind(checkpoint);
+            } else {
+                constraint = Some(self.alloc(ty));
+            }
+        }
+
+        Ok(self.finish(start, TsType::Infer(TsInferType { name, constraint })))
+    }
+
+    // T[], T[K]
+    fn parse_ts_postfix_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let start = self.lexer.start();
+        let mut ty = self.parse_ts_primary_type()?;
+
+        while self.lexer.token == Token::BracketOpen && !self.lexer.newline_before {
+            self.next_token()?;
+            if self.lexer.token == Token::BracketClose {
+                self.next_token()?;
+                ty = self.finish(start, TsType::Array(self.alloc(ty)));
+            } else {
+                let index = self.parse_ts_type()?;
+                self.expect(Token::BracketClose)?;
+                ty = self.finish(start, TsType::IndexedAccess(TsIndexedAccessType {
+                    object: self.alloc(ty),
+                    index: self.alloc(index),
+                }));
+            }
+        }
+
+        Ok(ty)
+    }
+
+    fn parse_ts_primary_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let (start, end) = self.lexer.loc();
+
+        let ty = match self.lexer.token {
+            Token::ParenOpen => {
+                self.next_token()?;
+                let ty = self.parse_ts_type()?;
+                self.expect(Token::ParenClose)?;
+                return Ok(self.finish(start, TsType::Parenthesized(self.alloc(ty))));
+            },
+            Token::BracketOpen => return self.parse_ts_tuple_type(),
+            Token::BraceOpen if self.is_ts_mapped_type() => return self.parse_ts_mapped_type(),
+            Token::BraceOpen => {
+                let members = self.parse_ts_type_members()?;
+                return Ok(self.finish(start, TsType::TypeLiteral(members)));
+            },
+            Token::TemplateString | Token::TemplateHead => return self.parse_ts_template_literal_type(),
+            Token::LiteralString => TsType::Literal(Literal::String),
+            Token::LiteralNumber => TsType::Literal(Literal::Number),
+            // -1
+            Token::Sub => {
+                self.next_token()?;
+                if self.lexer.token != Token::LiteralNumber {
+                    return Err(self.unexpected());
+                }
+                TsType::Literal(Literal::Number)
+            },
+            Token::Identifier if self.is_keyword("null") => TsType::Literal(Literal::Null),
+            Token::Identifier if self.is_keyword("true") => TsType::Literal(Literal::True),
+            Token::Identifier if self.is_keyword("false") => TsType::Literal(Literal::False),
+            Token::Identifier if self.is_keyword("typeof") => {
+                self.next_token()?;
+                // typeof import("source"), the query is implied by the import type.
+                if self.is_keyword("import") {
+                    let import = self.parse_ts_import_type()?;
+                    return Ok(self.finish(start, import.item));
+                }
+                let name = self.parse_ts_entity_name()?;
+                let type_args = self.parse_ts_type_args_opt()?;
+                return Ok(self.finish(start, TsType::Query(TsTypeQuery { name, type_args })));
+            },
+            Token::Identifier if self.is_keyword("import") => return self.parse_ts_import_type(),
+            Token::Identifier if self.peek().0 != Token::Dit
+                && TS_KEYWORD_TYPES.iter().any(|keyword| self.lexer.token_text_eq(keyword)) =>
+            {
+                TsType::Keyword
+            },
+            Token::Identifier => return self.parse_ts_type_reference(),
+            _ => return Err(self.unexpected()),
+        };
+        self.next_token()?;
+
+        Ok(Loc::new(start, self.last_end.max(end), ty))
+    }
+
+    // Name.Sub<Args>
+    fn parse_ts_type_reference(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let start = self.lexer.start();
+        let name = self.parse_ts_entity_name()?;
+        let type_args = self.parse_ts_type_args_opt()?;
+
+        Ok(self.finish(start, TsType::Reference(TsTypeReference { name, type_args })))
+    }
+
+    /// Type arguments directly following a type name, `Array<T>`.
+    #[inline]
+    fn parse_ts_type_args_opt(&mut self) -> Result<Option<Loc<TsTypeArguments<'a>>>, Error> {
+        if self.lexer.token == Token::Less && !self.lexer.newline_before {
+            self.parse_ts_type_args().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    // a.b.c
+    fn parse_ts_entity_name(&mut self) -> Result<List<'a, Loc<&'a str>>, Error> {
+        if self.lexer.token != Token::Identifier {
+            return Err(self.unexpected());
+        }
+
+        let name = GrowableList::new();
+        name.push(self.arena, self.parse_identifier_name()?);
+        while self.lexer.token == Token::Dit {
+            self.next_token()?;
+            name.push(self.arena, self.parse_identifier_name()?);
+        }
+
+        Ok(name.as_list())
+    }
+
+    // import("source").Name<Args>
+    fn parse_ts_import_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let start = self.lexer.start();
+        self.next_token()?;
+        self.expect(Token::ParenOpen)?;
+        let argument = self.parse_module_source()?;
+        self.expect(Token::ParenClose)?;
+
+        let qualifier = GrowableList::new();
+        while self.lexer.token == Token::Dit {
+            self.next_token()?;
+            qualifier.push(self.arena, self.parse_identifier_name()?);
+        }
+        let type_args = self.parse_ts_type_args_opt()?;
+
+        Ok(self.finish(start, TsType::Import(TsImportType { argument, qualifier: qualifier.as_list(), type_args })))
+    }
+
+    // [A, b?: B, ...C]
+    fn parse_ts_tuple_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let start = self.lexer.start();
+        self.expect(Token::BracketOpen)?;
+
+        let elements = GrowableList::new();
+        while self.lexer.token != Token::BracketClose {
+            let element_start = self.lexer.start();
+
+            let rest = self.lexer.token == Token::Spread;
+            if rest {
+                self.next_token()?;
+            }
+
+            // name: Type, name?: Type
+            let is_labeled = self.lexer.token == Token::Identifier && {
+                let mut lexer = self.lexer.clone();
+                let mut is_labeled = lexer.consume().is_ok();
+                if is_labeled && lexer.token == Token::Question {
+                    is_labeled = lexer.consume().is_ok();
+                }
+                is_labeled && lexer.token == Token::Colon
+            };
+
+            let mut optional = false;
+            let label = if is_labeled {
+                let label = self.parse_identifier_name()?;
+                if self.lexer.token == Token::Question {
+                    optional = true;
+                    self.next_token()?;
+                }
+                self.expect(Token::Colon)?;
+                Some(label)
+            } else {
+                None
+            };
+
+            let ty = self.parse_ts_type()?;
+            if label.is_none() && self.lexer.token == Token::Question {
+                optional = true;
+                self.next_token()?;
+            }
+            elements.push(self.arena, self.finish_node(element_start, TsTupleElement { label, optional, rest, ty }));
+
+            if self.lexer.token == Token::Comma {
+                self.next_token()?;
+            } else if self.lexer.token != Token::BracketClose {
+                return Err(self.expected_one_of(&[Token::Comma, Token::BracketClose]));
+            }
+        }
+        self.next_token()?;
+
+        Ok(self.finish(start, TsType::Tuple(elements.as_list())))
+    }
+
+    // `a${ A }b`
+    fn parse_ts_template_literal_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let start = self.lexer.start();
+        let quasis = GrowableList::new();
+        let types = GrowableList::new();
+
+        loop {
+            let (token_start, token_end) = self.lexer.loc();
+            match self.lexer.token {
+                Token::TemplateString | Token::TemplateTail => {
+                    quasis.push(self.arena, Loc::new(token_start + 1, token_end - 1, TemplateElement { tail: true }));
+                    self.next_token()?;
+                    break;
+                },
+                Token::TemplateHead | Token::TemplateMiddle => {
+                    quasis.push(self.arena, Loc::new(token_start + 1, token_end - 2, TemplateElement { tail: false }));
+                },
+                _ => return Err(self.unexpected()),
+            }
+
+            self.next_token()?;
+            types.push(self.arena, self.node(Self::parse_ts_type)?);
+            self.consume_with(Lexer::consume_template)?;
+        }
+
+        Ok(self.finish(start, TsType::Template(TsTemplateLiteralType { quasis: quasis.as_list(), types: types.as_list() })))
+    }
+
+    /// `{ [K in`, `{ readonly [K in` and `{ -readonly [K in` start a mapped type.
+    fn is_ts_mapped_type(&self) -> bool {
+        let mut lexer = self.lexer.clone();
+        if lexer.consume().is_err() {
+            return false;
+        }
+        if matches!(lexer.token, Token::Add | Token::Sub) && lexer.consume().is_err() {
+            return false;
+        }
+        if lexer.token == Token::Identifier && lexer.token_text_eq("readonly") && lexer.consume().is_err() {
+            return false;
+        }
+
+        if lexer.token != Token::BracketOpen || lexer.consume().is_err() || lexer.token != Token::Identifier {
+            return false;
+        }
+
+        lexer.consume().is_ok() && lexer.token == Token::Identifier && lexer.token_text_eq("in")
+    }
+
+    // +, -
+    fn parse_ts_mapped_modifier(&mut self) -> Result<TsMappedModifier, Error> {
+        let modifier = match self.lexer.token {
+            Token::Add => TsMappedModifier::Add,
+            Token::Sub => TsMappedModifier::Remove,
+            _ => return Ok(TsMappedModifier::Present),
+        };
+        self.next_token()?;
+
+        Ok(modifier)
+    }
+
+    // { readonly [K in keyof T as NewKey]?: T[K] }
+    fn parse_ts_mapped_type(&mut self) -> Result<Loc<TsType<'a>>, Error> {
+        let start = self.lexer.start();
+        self.expect(Token::BraceOpen)?;
+
+        let readonly = if self.lexer.token == Token::BracketOpen {
+            None
+        } else {
+            let modifier = self.parse_ts_mapped_modifier()?;
+            self.expect_keyword("readonly")?;
+            Some(modifier)
+        };
+
+        self.expect(Token::BracketOpen)?;
+        let name = self.parse_binding_identifier()?;
+        self.expect_keyword("in")?;
+        let constraint = self.parse_ts_type()?;
+        let name_type = if self.is_keyword("as") {
+            self.next_token()?;
+            Some(self.node(Self::parse_ts_type)?)
+        } else {
+            None
+        };
+        self.expect(Token::BracketClose)?;
+
+        let optional = if matches!(self.lexer.token, Token::Add | Token::Sub | Token::Question) {
+            let modifier = self.parse_ts_mapped_modifier()?;
+            self.expect(Token::Question)?;
+            Some(modifier)
+        } else {
+            None
+        };
+
+        let ty = if self.lexer.token == Token::Colon {
+            self.next_token()?;
+            Some(self.node(Self::parse_ts_type)?)
+        } else {
+            None
+        };
+
+        if matches!(self.lexer.token, Token::Semicolon | Token::Comma) {
+            self.next_token()?;
+        }
+        self.expect(Token::BraceClose)?;
+
+        Ok(self.finish(start, TsType::Mapped(TsMappedType {
+            readonly,
+            name,
+            constraint: self.alloc(constraint),
+            name_type,
+            optional,
+            ty,
+        })))
+    }
+
+    // { a: A; b(): B, [key: string]: C }
+    fn parse_ts_type_members(&mut self) -> Result<NodeList<'a, TsTypeMember<'a>>, Error> {
+        self.expect(Token::BraceOpen)?;
+
+        let members = GrowableList::new();
+        while self.lexer.token != Token::BraceClose {
+            members.push(self.arena, self.node(Self::parse_ts_type_member)?);
+
+            match self.lexer.token {
+                Token::Semicolon | Token::Comma => self.next_token()?,
+                Token::BraceClose => { },
+                _ if self.lexer.newline_before => { },
+                _ => return Err(self.unexpected()),
+            }
+        }
+        self.next_token()?;
+
+        Ok(members.as_list())
+    }
+
+    fn parse_ts_type_member(&mut self) -> Result<Loc<TsTypeMember<'a>>, Error> {
+        let start = self.lexer.start();
+
+        // (a: A): B, <T>(a: T): B
+        if matches!(self.lexer.token, Token::ParenOpen | Token::Less) {
+            let signature = self.parse_ts_signature()?;
+            return Ok(self.finish(start, TsTypeMember::Call(signature)));
+        }
+
+        // new (a: A): B
+        if self.is_keyword("new") && matches!(self.peek().0, Token::ParenOpen | Token::Less) {
+            self.next_token()?;
+            let signature = self.parse_ts_signature()?;
+            return Ok(self.finish(start, TsTypeMember::Construct(signature)));
+        }
+
+        let is_modifier = |parser: &Self| !matches!(parser.peek().0, Token::ParenOpen | Token::Colon
+            | Token::Question | Token::Semicolon | Token::Comma | Token::BraceClose | Token::Less);
+
+        let modifiers = GrowableList::new();
+        if self.is_keyword("readonly") && is_modifier(self) {
+            modifiers.push(self.arena, self.parse_ts_modifier(TsModifier::Readonly)?);
+        }
+
+        // [key: string]: Type
+        if self.lexer.token == Token::BracketOpen && self.is_ts_index_signature() {
+            let signature = self.parse_ts_index_signature(modifiers.as_list(), false)?;
+            return Ok(self.finish(start, TsTypeMember::Index(signature)));
+        }
+
+        let mut kind = MethodKind::Method;
+        if (self.is_keyword("get") || self.is_keyword("set")) && is_modifier(self) {
+            kind = if self.is_keyword("get") { MethodKind::Get } else { MethodKind::Set };
+            self.next_token()?;
+        }
+
+        let key = self.parse_property_key()?;
+        let optional = self.lexer.token == Token::Question;
+        if optional {
+            self.next_token()?;
+        }
+
+        if kind != MethodKind::Method || matches!(self.lexer.token, Token::ParenOpen | Token::Less) {
+            let signature = self.parse_ts_signature()?;
+            return Ok(self.finish(start, TsTypeMember::Method { kind, key, optional, signature }));
+        }
+
+        let ty = if self.lexer.token == Token::Colon {
+            self.next_token()?;
+            Some(self.parse_ts_type()?)
+        } else {
+            None
+        };
+
+        Ok(self.finish(start, TsTypeMember::Property { readonly: !modifiers.as_list().is_empty(), key, optional, ty }))
+    }
+
+    /// `[key:` starts an index signature, `[key]` is a computed key.
+    fn is_ts_index_signature(&self) -> bool {
+        let mut lexer = self.lexer.clone();
+        lexer.consume().is_ok() && lexer.token == Token::Identifier
+            && lexer.consume().is_ok() && lexer.token == Token::Colon
+    }
+
+    // [key: string]: Type
+    fn parse_ts_index_signature(&mut self, modifiers: List<'a, Loc<TsModifier>>, is_static: bool) -> Result<TsIndexSignature<'a>, Error> {
+        self.expect(Token::BracketOpen)?;
+
+        let params = GrowableList::new();
+        while self.lexer.token != Token::BracketClose {
+            let name = self.parse_binding_identifier()?;
+            let param = self.alloc(Loc::new(name.start, name.end, Pattern::Identifier(name.item)));
+            params.push(self.arena, self.parse_ts_binding_annotation(param, false, false)?);
+
+            if self.lexer.token == Token::Comma {
+                self.next_token()?;
+            } else if self.lexer.token != Token::BracketClose {
+                return Err(self.expected_one_of(&[Token::Comma, Token::BracketClose]));
+            }
+        }
+        self.next_token()?;
+        self.expect(Token::Colon)?;
+        let ty = self.parse_ts_type()?;
+
+        Ok(TsIndexSignature { modifiers, is_static, params: params.as_list(), ty })
+    }
+
+    // <T>(a: A): B
+    fn parse_ts_signature(&mut self) -> Result<TsSignature<'a>, Error> {
+        let type_params = self.parse_ts_type_params_opt()?;
+        let params = self.parse_formal_params()?;
+        let return_type = if self.lexer.token == Token::Colon {
+            self.next_token()?;
+            Some(self.parse_ts_return_type()?)
+        } else {
+            None
+        };
+
+        Ok(TsSignature { type_params, params, return_type })
+    }
+
+    // Declarations
+
+    /// `interface`, `type`, `enum`, `namespace`, `abstract class` and
+    /// `declare ...`, the current token is an identifier.
+    fn try_parse_ts_declaration(&mut self) -> Result<Option<Statement<'a>>, Error> {
+        let (token, newline_before) = self.peek();
+        if newline_before {
+            return Ok(None);
+        }
+
+        let stmt = match token {
+            Token::Identifier if self.is_keyword("interface") => Statement::TsInterface(self.parse_ts_interface()?),
+            Token::Identifier if self.is_keyword("type") => Statement::TsTypeAlias(self.parse_ts_type_alias()?),
+            Token::Identifier if self.is_keyword("enum") => Statement::TsEnum(self.parse_ts_enum(false)?),
+            Token::Identifier if self.is_keyword("const") && self.peek_is_keyword("enum") => {
+                self.next_token()?;
+                Statement::TsEnum(self.parse_ts_enum(true)?)
+            },
+            Token::Identifier if self.is_keyword("namespace") => Statement::TsModule(self.parse_ts_module()?),
+            Token::LiteralString | Token::Identifier if self.is_keyword("module") => {
+                Statement::TsModule(self.parse_ts_module()?)
+            },
+            Token::Identifier if self.is_keyword("abstract") && self.peek_is_keyword("class") => {
+                let modifiers = List::from(self.arena, self.parse_ts_modifier(TsModifier::Abstract)?);
+                Statement::Class(self.parse_class(modifiers)?)
+            },
+            Token::Identifier if self.is_keyword("declare") => {
+                self.next_token()?;
+                let start = self.lexer.start();
+                let declaration = self.parse_ts_ambient_declaration()?;
+                Statement::TsDeclare(self.finish_node(start, declaration))
+            },
+            _ => return Ok(None),
+        };
+
+        Ok(Some(stmt))
+    }
+
+    // declare const a: A;
+    // declare function f(): void;
+    // declare enum E { }
+    // declare module "source" { }
+    fn parse_ts_ambient_declaration(&mut self) -> Result<Statement<'a>, Error> {
+        if self.is_keyword("var") || self.is_keyword("let") || self.is_keyword("const") {
+            if self.is_keyword("const") && self.peek_is_keyword("enum") {
+                self.next_token()?;
+                return self.parse_ts_enum(true).map(Statement::TsEnum);
+            }
+
+            let declaration = self.parse_variable_declaration()?;
+            self.consume_semicolon()?;
+            return Ok(Statement::Variable(declaration));
+        }
+
+        if self.is_keyword("function") || self.is_async_function() {
+            let is_async = self.is_keyword("async");
+            if is_async {
+                self.next_token()?;
+            }
+
+            let (id, is_generator) = self.parse_function_start()?;
+            let (type_params, params, return_type) = self.parse_function_head(true)?;
+            self.consume_semicolon()?;
+            return Ok(Statement::TsDeclareFunction(TsDeclareFunction {
+                id,
+                is_async,
+                is_generator,
+                type_params,
+                params,
+                return_type,
+            }));
+        }
+
+        if self.is_keyword("class") {
+            return self.parse_class(List::empty()).map(Statement::Class);
+        }
+
+        if self.is_keyword("enum") {
+            return self.parse_ts_enum(false).map(Statement::TsEnum);
+        }
+
+        if self.is_keyword("namespace") || self.is_keyword("module") || self.is_keyword("global") {
+            return self.parse_ts_module().map(Statement::TsModule);
+        }
+
+        match self.try_parse_ts_declaration()? {
+            Some(Statement::TsDeclare(_)) | None => Err(self.unexpected()),
+            Some(stmt) => Ok(stmt),
+        }
+    }
+
+    // interface Name<T> extends A, B { members }
+    fn parse_ts_interface(&mut self) -> Result<TsInterfaceDeclaration<'a>, Error> {
+        self.next_token()?;
+        let id = self.parse_binding_identifier()?;
+        let type_params = self.parse_ts_type_params_opt()?;
+
+        let extends = GrowableList::new();
+        if self.is_keyword("extends") {
+            self.next_token()?;
+            extends.push(self.arena, self.node(Self::parse_ts_type_reference)?);
+            while self.lexer.token == Token::Comma {
+                self.next_token()?;
+                extends.push(self.arena, self.node(Self::parse_ts_type_reference)?);
+            }
+        }
+        let body = self.parse_ts_type_members()?;
+
+        Ok(TsInterfaceDeclaration { id, type_params, extends: extends.as_list(), body })
+    }
+
+    // type Name<T> = Type;
+    fn parse_ts_type_alias(&mut self) -> Result<TsTypeAliasDeclaration<'a>, Error> {
+        self.next_token()?;
+        let id = self.parse_binding_identifier()?;
+        let type_params = self.parse_ts_type_params_opt()?;
+        self.expect(Token::Assign)?;
+        let ty = self.parse_ts_type()?;
+        self.consume_semicolon()?;
+
+        Ok(TsTypeAliasDeclaration { id, type_params, ty })
+    }
+
+    // enum Name { A = 1, "b" }
+    fn parse_ts_enum(&mut self, is_const: bool) -> Result<TsEnumDeclaration<'a>, Error> {
+        self.expect_keyword("enum")?;
+        let id = self.parse_binding_identifier()?;
+        self.expect(Token::BraceOpen)?;
+
+        let members = GrowableList::new();
+        while self.lexer.token != Token::BraceClose {
+            let start = self.lexer.start();
+            let id = self.parse_module_export_name()?;
+            let init = if self.lexer.token == Token::Assign {
+                self.next_token()?;
+                Some(self.parse_assignment_expression()?)
+            } else {
+                None
+            };
+            members.push(self.arena, self.finish_node(start, TsEnumMember { id, init }));
+
+            if self.lexer.token == Token::Comma {
+                self.next_token()?;
+            } else if self.lexer.token != Token::BraceClose {
+                return Err(self.expected_one_of(&[Token::Comma, Token::BraceClose]));
+            }
+        }
+        self.next_token()?;
+
+        Ok(TsEnumDeclaration { is_const, id, members: members.as_list() })
+    }
+
+    // namespace A.B { }, module "source" { }, global { }
+    fn parse_ts_module(&mut self) -> Result<TsModuleDeclaration<'a>, Error> {
+        let is_global = self.is_keyword("global");
+        let id = if is_global {
+            List::from(self.arena, self.parse_identifier_name()?)
+        } else {
+            self.next_token()?;
+            if self.lexer.token == Token::LiteralString {
+                List::from(self.arena, self.parse_module_export_name()?)
+            } else {
+                self.parse_ts_entity_name()?
+            }
+        };
+
+        if self.lexer.token != Token::BraceOpen {
+            self.consume_semicolon()?;
+            return Ok(TsModuleDeclaration { id, body: None });
+        }
+
+        self.next_token()?;
+        let body = GrowableList::new();
+        while self.lexer.token != Token::BraceClose {
+            if self.lexer.token == Token::EndOfProgram {
+                return Err(self.expected(Token::BraceClose.to_string()));
+            }
+            body.push(self.arena, self.parse_module_item()?);
+        }
+        self.next_token()?;
+
+        Ok(TsModuleDeclaration { id, body: Some(body.as_list()) })
+    }
+}
+
+
+#[inline]
+fn assignment_operator(token: Token) -> Option<AssignmentOperator> {
+    let operator = match token {
+        Token::Assign => AssignmentOperator::Assign,
+        Token::AddAssign => AssignmentOperator::AddAssign,
+        Token::SubAssign => AssignmentOperator::SubAssign,
+        Token::MulAssign => AssignmentOperator::MulAssign,
+        Token::DivAssign => AssignmentOperator::DivAssign,
+        Token::RemAssign => AssignmentOperator::RemAssign,
+        Token::ExpAssign => AssignmentOperator::ExpAssign,
+        Token::ShiftLeftAssign => AssignmentOperator::ShiftLeftAssign,
+        Token::ShiftRightAssign => AssignmentOperator::ShiftRightAssign,
+        Token::UnsignedShiftRightAssign => AssignmentOperator::UnsignedShiftRightAssign,
+        Token::BitAndAssign => AssignmentOperator::BitAndAssign,
+        Token::BitOrAssign => AssignmentOperator::BitOrAssign,
+        Token::BitXorAssign => AssignmentOperator::BitXorAssign,
+        Token::AndAssign => AssignmentOperator::AndAssign,
+        Token::OrAssign => AssignmentOperator::OrAssign,
+        Token::NullishAssign => AssignmentOperator::NullishAssign,
+        _ => return None,
+    };
+
+    Some(operator)
+}
+
+/// `++` and `--` only apply to identifiers and member expressions.
+#[inline]
+fn check_simple_assignment_target<'a>(expr: &Node<'a, Expression<'a>>) -> Result<(), Error> {
+    match expr.item {
+        Expression::Identifier(_) | Expression::StaticMember(_) | Expression::ComputedMember(_) => Ok(()),
+        Expression::Parenthesized(ref inner) | Expression::TsNonNull(ref inner) => check_simple_assignment_target(inner),
+        Expression::TsAs(ref expr) | Expression::TsSatisfies(ref expr) => check_simple_assignment_target(&expr.expression),
+        _ => Err(Error::UnexpectedToken),
+    }
+}
+
+
+/// Span of a JSX element name, `a:b`, `A.B.C`.
+fn elem_name_span<'a>(name: &ElementName<'a>) -> (usize, usize) {
+    match *name {
+        ElementName::Identifier(ref ident) => (ident.start, ident.end),
+        ElementName::NamespacedName(ref namespaced) => (namespaced.ns.start, namespaced.name.end),
+        ElementName::MemberExpression(ref member) => {
+            let start = member.members.first_element().map_or(0, |first| first.start);
+            (start, member.members.iter().last().map_or(start, |last| last.end))
+        },
+    }
+}
+
+/// Running out of source in the middle of an element is not a clean end.
+#[inline]
+fn unexpected_end_of_program(e: Error) -> Error {
+    match e {
+        Error::EndOfProgram => Error::UnexpectedEndOfProgram,
+        _ => e,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use toolshed::Arena;
+
+    use crate::ast::Statement;
+    use crate::parser::ParseOptions;
+
+    fn errors(source: &str) -> Vec<String> {
+        match crate::parse(source, &Arena::new(), ParseOptions::default()) {
+            Ok(_) => Vec::new(),
+            Err(diagnostics) => diagnostics.errors.iter().map(|error| error.to_string()).collect(),
+        }
+    }
+
+    /// Source of every top-level statement, skipped ones in `!(...)`.
+    fn statements(source: &str) -> Vec<String> {
+        let arena = Arena::new();
+        let (program, _) = crate::parse_recovering(source, &arena, ParseOptions::default());
+        program.body.iter().map(|statement| {
+            let text = source[statement.start..statement.end].trim();
+            match statement.item {
+                Statement::Error => format!("!({})", text),
+                _ => text.to_string(),
+            }
+        }).collect()
+    }
+
+    #[test]
+    fn invalid_chars_in_tag() {
+        assert_eq!(errors("<div @click=\"go\" />"), ["1:6: unexpected `@`"]);
+        assert_eq!(errors("<div #id />"), ["1:6: unexpected `#`"]);
+        assert_eq!(errors("<div 123 />"), ["1:6: unexpected `1`"]);
+        assert!(errors("<div\n\ta=\"1\"\u{a0}b />").is_empty());
+        assert_eq!(errors("<div title=\"\u{1f600}\" @ />"), ["1:17: unexpected `@`"]);
+    }
+
+    #[test]
+    fn hyphen_in_member_expression() {
+        assert_eq!(errors("<ui.date-picker />"), ["1:5: `date-picker` cannot be part of a member expression, it contains a `-`"]);
+        assert_eq!(errors("<a-b.c />").len(), 1);
+        assert!(errors("<date-picker />").is_empty());
+    }
+
+    #[test]
+    fn nested_parentheses_without_arrows() {
+        // Every level used to be parsed twice, as parameters and as an expression.
+        let nested = format!("x = {}a{};", "(a = ".repeat(30), ")".repeat(30));
+        assert!(errors(&nested).is_empty());
+
+        let arrow = format!("x = ({}a{}) => a;", "a = (a = ".repeat(15), ")".repeat(15));
+        assert!(errors(&arrow).is_empty());
+        assert!(errors("x = (a = (b) => b, c = (d));").is_empty());
+    }
+
+    #[test]
+    fn recover_on_tokens() {
+        assert_eq!(errors("x = (;\nif (a < b) { y() }"), ["1:6: unexpected `;`"]);
+        assert_eq!(statements("x = (;\nif (a < b) { y() }"), ["!(x = (;)", "if (a < b) { y() }"]);
+        assert_eq!(
+            statements("const b = (;\nconst s = \"a < b\";\nconst t = 1;"),
+            ["!(const b = (;)", "const s = \"a < b\";", "const t = 1;"],
+        );
+        assert_eq!(statements("x = <a =/>;\ny = 1;\nz = <b/>;"), ["!(x = <a =/>;)", "y = 1;", "z = <b/>;"]);
+        assert_eq!(statements("x = f(1 2\nconst y = 1;"), ["!(x = f(1 2)", "const y = 1;"]);
+        assert_eq!(statements("x = 1 2 (\nconst y = 1;"), ["!(x = 1 2 ()", "const y = 1;"]);
+        assert_eq!(statements("x = 1 2 {\nconst y = 1; }\nlet z;"), ["!(x = 1 2 {\nconst y = 1; })", "let z;"]);
+        assert_eq!(statements("x = a b;\n}\ny;"), ["!(x = a b;)", "!(})", "y;"]);
+    }
+
+    #[test]
+    fn recover_jsx_children() {
+        let source = "x = <div>{a b < c}<p>ok</p>{\"}\" d}<i =/>text</div>;\ny = 1;";
+        assert_eq!(errors(source), [
+            "1:13: unexpected `b`, expected `}`",
+            "1:33: unexpected `d`, expected `}`",
+            "1:38: unexpected `=`",
+        ]);
+        assert_eq!(statements(source)[1], "y = 1;");
+        assert_eq!(errors("<div>{a b</div>"), ["1:9: unexpected `b`, expected `}`"]);
+        assert_eq!(errors("<div><b a={1 2}>'}'</b>ok</div>"), ["1:14: unexpected `2`"]);
+    }
+
+    #[test]
+    fn greater_and_brace_in_text() {
+        assert_eq!(errors("<a>x > y</a>"), ["1:6: unexpected `>`, did you mean `&gt;` or `{'>'}`?"]);
+        assert_eq!(errors("<a>\n  }{1}\n</a>"), ["2:3: unexpected `}`, did you mean `&rbrace;` or `{'}'}`?"]);
+        assert_eq!(errors("<><b>>></b>{1}}</>").len(), 3);
+        assert!(errors("<a>x {'>'} &gt; {'}'} &rbrace;</a>").is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use crate::ast::Loc;
+use crate::lex::is_line_terminator;
+
+
+/// Start of every line in a source, so a byte index maps to a line and column in O(log n).
+///
+/// Lines break at `\n`, `\r`, `\r\n` (one break), U+2028 and U+2029.
+/// Lines and columns are 0-based. Columns count UTF-16 code units like
+/// JavaScript strings, Babel's `loc`, source maps and the Language Server
+/// Protocol do, every column the crate reports is one.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+    /// Byte index after every non-ASCII char, with how many more bytes than
+    /// UTF-16 code units the source has up to there. Empty for ASCII sources.
+    utf16_shifts: Vec<(usize, usize)>,
+    len: usize,
+}
+
+impl LineIndex {
+    pub fn new(code: &str) -> Self {
+        let mut line_starts = vec![0];
+        let mut utf16_shifts = Vec::new();
+        let mut shift = 0;
+
+        for (index, ch) in code.char_indices() {
+            // `\r\n` breaks the line at the `\n`.
+            if is_line_terminator(ch) && !(ch == '\r' && code.as_bytes().get(index + 1) == Some(&b'\n')) {
+                line_starts.push(index + ch.len_utf8());
+            }
+            if !ch.is_ascii() {
+                shift += ch.len_utf8() - ch.len_utf16();
+                utf16_shifts.push((index + ch.len_utf8(), shift));
+            }
+        }
+
+        LineIndex { line_starts, utf16_shifts, len: code.len() }
+    }
+
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Byte index of the first char of `line`.
+    #[inline]
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line).cloned()
+    }
+
+    /// Offset of the char at byte `index` in UTF-16 code units.
+    pub fn utf16_offset(&self, index: usize) -> usize {
+        let index = index.min(self.len);
+        match self.utf16_shifts.partition_point(|&(end, _)| end <= index) {
+            0 => index,
+            after => index - self.utf16_shifts[after - 1].1,
+        }
+    }
+
+    /// Line and column of the char at byte `index`, the end of the source is a valid index.
+    pub fn line_column(&self, index: usize) -> (usize, usize) {
+        let index = index.min(self.len);
+        let line = match self.line_starts.binary_search(&index) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+
+        (line, self.utf16_offset(index) - self.utf16_offset(self.line_starts[line]))
+    }
+
+    /// Line and column of both ends of `loc`.
+    #[inline]
+    pub fn loc<T>(&self, loc: &Loc<T>) -> ((usize, usize), (usize, usize)) {
+        (self.line_column(loc.start), self.line_column(loc.end))
+    }
+}
+
+
+// Source Map v3
+// https://sourcemaps.info/spec.html
+
+/// Output written at byte `generated` comes from byte `original` of the source.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Mapping {
+    pub generated: usize,
+    pub original: usize,
+    /// Index in `SourceMapBuilder::names`.
+    pub name: Option<usize>,
+}
+
+/// Collects the mappings while the output is written, in output order.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct SourceMapBuilder {
+    mappings: Vec<Mapping>,
+    names: Vec<String>,
+    /// Index of every name in `names`.
+    name_indices: HashMap<String, usize>,
+}
+
+impl SourceMapBuilder {
+    pub fn new() -> Self {
+        SourceMapBuilder::default()
+    }
+
+    #[inline]
+    pub fn add(&mut self, generated: usize, original: usize) {
+        self.mappings.push(Mapping { generated, original, name: None });
+    }
+
+    /// Maps an identifier, `name` is its original text.
+    pub fn add_named(&mut self, generated: usize, original: usize, name: &str) {
+        let index = match self.name_indices.get(name) {
+            Some(&index) => index,
+            None => {
+                self.names.push(name.to_string());
+                self.name_indices.insert(name.to_string(), self.names.len() - 1);
+                self.names.len() - 1
+            },
+        };
+        self.mappings.push(Mapping { generated, original, name: Some(index) });
+    }
+
+    /// Moves the positions generated at `from` or later `offset` bytes down,
+    /// text was inserted at `from`.
+    pub fn shift(&mut self, from: usize, offset: usize) {
+        for mapping in self.mappings.iter_mut().filter(|mapping| mapping.generated >= from) {
+            mapping.generated += offset;
+        }
+    }
+
+    /// Encodes the mappings from `output` back to `source`, named `source_name`.
+    pub fn build(mut self, source_name: &str, source: &str, output: &str) -> SourceMap {
+        let source_lines = LineIndex::new(source);
+        let output_lines = LineIndex::new(output);
+
+        // The first mapping of a position wins.
+        self.mappings.sort_by_key(|mapping| mapping.generated);
+        self.mappings.dedup_by_key(|mapping| mapping.generated);
+
+        let mut mappings = String::new();
+        let mut last_line = 0;
+        let mut last = [0i64; 5];
+        for (index, mapping) in self.mappings.iter().enumerate() {
+            let (line, column) = output_lines.line_column(mapping.generated);
+            let (original_line, original_column) = source_lines.line_column(mapping.original);
+
+            if line != last_line {
+                for _ in last_line..line {
+                    mappings.push(';');
+                }
+                last_line = line;
+                last[0] = 0;
+            } else if index > 0 {
+                mappings.push(',');
+            }
+
+            // generated column, source, original line, original column, name
+            let mut fields = vec![column as i64, 0, original_line as i64, original_column as i64];
+            if let Some(name) = mapping.name {
+                fields.push(name as i64);
+            }
+            for (field, value) in fields.into_iter().enumerate() {
+                write_vlq(&mut mappings, value - last[field]);
+                last[field] = value;
+            }
+        }
+
+        SourceMap {
+            file: None,
+            sources: vec![source_name.to_string()],
+            sources_content: vec![source.to_string()],
+            names: self.names,
+            mappings,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SourceMap {
+    /// Name of the generated file.
+    pub file: Option<String>,
+    pub sources: Vec<String>,
+    pub sources_content: Vec<String>,
+    pub names: Vec<String>,
+    /// Base64 VLQ segments, `;` between generated lines.
+    pub mappings: String,
+}
+
+impl SourceMap {
+    /// Contents of a `.map` file.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"version\":3");
+        if let Some(ref file) = self.file {
+            json.push_str(",\"file\":");
+            write_json_string(&mut json, file);
+        }
+        json.push_str(",\"sources\":");
+        write_json_array(&mut json, &self.sources);
+        json.push_str(",\"sourcesContent\":");
+        write_json_array(&mut json, &self.sources_content);
+        json.push_str(",\"names\":");
+        write_json_array(&mut json, &self.names);
+        json.push_str(",\"mappings\":");
+        write_json_string(&mut json, &self.mappings);
+        json.push('}');
+
+        json
+    }
+
+    /// `//# sourceMappingURL=data:...` with the whole map, appended to the output.
+    pub fn to_inline_comment(&self) -> String {
+        format!(
+            "//# sourceMappingURL=data:application/json;charset=utf-8;base64,{}",
+            base64(self.to_json().as_bytes()),
+        )
+    }
+
+    /// `//# sourceMappingURL=app.js.map` pointing at a separate `.map` file.
+    pub fn url_comment(url: &str) -> String {
+        format!("//# sourceMappingURL={}", url)
+    }
+}
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Sign in the lowest bit, then 5 bits per digit with a continuation bit.
+fn write_vlq(output: &mut String, value: i64) {
+    let mut vlq = if value < 0 { ((-value) << 1) | 1 } else { value << 1 } as u64;
+
+    loop {
+        let mut digit = vlq & 0b11111;
+        vlq >>= 5;
+        if vlq > 0 {
+            digit |= 0b100000;
+        }
+        output.push(BASE64_CHARS[digit as usize] as char);
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (idx, b)| n | (*b as u32) << (16 - idx * 8));
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                output.push(BASE64_CHARS[(n >> (18 - idx * 6)) as usize & 0b111111] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+
+    output
+}
+
+pub(crate) fn write_json_string(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+fn write_json_array(output: &mut String, values: &[String]) {
+    output.push('[');
+    for (idx, value) in values.iter().enumerate() {
+        if idx > 0 {
+            output.push(',');
+        }
+        write_json_string(output, value);
+    }
+    output.push(']');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::Loc;
+
+    use super::{ write_vlq, base64, LineIndex, SourceMapBuilder, };
+
+    fn vlq(value: i64) -> String {
+        let mut output = String::new();
+        write_vlq(&mut output, value);
+        output
+    }
+
+    #[test]
+    fn vlq_values() {
+        assert_eq!(vlq(0), "A");
+        assert_eq!(vlq(1), "C");
+        assert_eq!(vlq(-1), "D");
+        assert_eq!(vlq(15), "e");
+        assert_eq!(vlq(16), "gB");
+        assert_eq!(vlq(-16), "hB");
+        assert_eq!(vlq(123), "2H");
+    }
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"M"), "TQ==");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"Man"), "TWFu");
+    }
+
+    #[test]
+    fn line_index_breaks() {
+        let code = "a\r\nb\rc\u{2028}\u{1f600}d";
+        let lines = LineIndex::new(code);
+        assert_eq!(lines.line_count(), 4);
+        assert_eq!(lines.line_column(3), (1, 0));
+        assert_eq!(lines.line_column(5), (2, 0));
+        assert_eq!(lines.loc(&Loc::new(3, 5, ())), ((1, 0), (2, 0)));
+        assert_eq!(lines.line_column(code.len() - 1), (3, 2));
+        assert_eq!(lines.utf16_offset(code.len()), 10);
+    }
+
+    #[test]
+    fn names_and_utf16_columns() {
+        let mut builder = SourceMapBuilder::new();
+        builder.add_named(1, 0, "\u{e9}");
+        builder.add_named(4, 3, "b");
+        builder.add_named(6, 5, "b");
+        let map = builder.build("a.js", "\u{e9} b b", "(\u{e9} b b)");
+        assert_eq!(map.names, ["\u{e9}", "b"]);
+        assert_eq!(map.mappings, "CAAAA,EAAEC,EAAEA");
+    }
+}
@@ -1,143 +1,2866 @@
-use crate::error::Error;
-use crate::lex::{ Token, Lexer, };
+use toolshed::Arena;
+use crate::list::List;
+
+use std::collections::HashMap;
+
+use crate::error::{ Error, ParseError, };
+use crate::parser::{ Parser, Syntax, };
+use crate::source_map::{ SourceMap, SourceMapBuilder, LineIndex, };
+use crate::fold::Fold;
+use crate::visit::{ self, Visit, };
+use crate::lex::is_line_terminator;
+use crate::ast::{
+    Loc, Node, NodeList, Program, Comment,
+    ElementExpression, FragmentExpression, ElementName,
+    Attribute, NormalAttribute, NormalAttributeName, NormalAttributeInitializer,
+    Child, Expression, Literal, TemplateLiteral, Statement, Pattern, PatternProperty, Property, PropertyKey,
+    MethodKind, ArrowBody, UnaryOperator, UpdateOperator, BinaryOperator,
+    Function, Class, ClassMember, VariableDeclaration, VariableKind, IfStatement, ForInit, ForHead,
+    ImportDeclaration, ImportSpecifier, ExportNamedDeclaration, ExportDefaultDeclaration, TsModifier,
+    TsEnumDeclaration,
+};
+use crate::estree::number_value;
 
 
 // React.createElement()
 // React.createElement(React.Fragment)
-pub static REACT_TARGET: (&'static str, &'static str) = ("React.createElement", "React.Fragment");
+pub static REACT_TARGET: (&str, &str) = ("React.createElement", "React.Fragment");
 
 /*
 
-React.createElement("div", { ...props, ...props2, e: 3, }, null);
-React.createElement(App, { ...props, ...props2, e: 3, }, null);
+Classic runtime:
+
+<div {...props} e={3}></div>
+    React.createElement("div", { ...props, e: 3 });
+
+<App {...props} e={3}><Page /></App>
+    React.createElement(App, { ...props, e: 3 }, React.createElement(Page, null));
 
-React.createElement("App",
-                    { ...props, ...props2, e: 3, },
-                    React.createElement("App", { ...props, ...props2, e: 3, }, null),
-);
+<><Page /></>
+    React.createElement(React.Fragment, null, React.createElement(Page, null));
 
-React.createElement("App",
-                    { ...props, ...props2, e: 3, },
-                    [
-                        React.createElement("App", { ...props, ...props2, e: 3, }, null)
-                    ],
-);
+Automatic runtime:
+
+<App key="a" e={3}><Page /></App>
+    _jsx(App, { e: 3, children: _jsx(Page, {}) }, "a");
+
+<><Page /><Page /></>
+    _jsxs(_Fragment, { children: [_jsx(Page, {}), _jsx(Page, {})] });
+
+<div {...props} key="a" />
+    _createElement("div", { ...props, key: "a" });
 
 */
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub struct Node {
-    pub start: usize,
-    pub end: usize,
-    pub token: Token,
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Runtime {
+    /// `React.createElement(type, props, ...children)`
+    Classic,
+    /// `_jsx(type, { ...props, children }, key)` imported from `react/jsx-runtime`
+    Automatic,
 }
 
 #[derive(Debug, PartialEq, Clone)]
-pub enum Leaf {
-    Token(Node),
-    Element(Element),
-    Fragment(Fragment),
+pub struct TransformOptions {
+    pub runtime: Runtime,
+    /// Function called for every element, `React.createElement` by default.
+    pub pragma: String,
+    /// Component passed as the type of fragments, `React.Fragment` by default.
+    pub pragma_frag: String,
+    /// Module the automatic runtime is imported from, `react` by default.
+    pub import_source: String,
+    /// Use `jsxDEV` from `jsx-dev-runtime` and pass `__source` to every element.
+    pub development: bool,
+    /// File name reported in `__source`.
+    pub filename: Option<String>,
+    /// `Syntax::Tsx` parses TypeScript and strips it from the output.
+    pub syntax: Syntax,
+    /// Merge spread props with `Object.assign({}, props, { a: 1 })` instead of
+    /// object spread, for targets before ES2018.
+    pub object_assign: bool,
+    /// Build `Output::source_map`, named after `filename`.
+    pub source_map: bool,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Element {
-    pub nodes: Vec<Leaf>,
+impl Default for TransformOptions {
+    fn default() -> Self {
+        TransformOptions {
+            runtime: Runtime::Classic,
+            pragma: REACT_TARGET.0.to_string(),
+            pragma_frag: REACT_TARGET.1.to_string(),
+            import_source: "react".to_string(),
+            development: false,
+            filename: None,
+            syntax: Syntax::Jsx,
+            object_assign: false,
+            source_map: false,
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Fragment {
-    pub nodes: Vec<Leaf>,
+/// Result of `jsx::transform`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Output {
+    pub code: String,
+    /// Maps `code` back to the source when `TransformOptions::source_map` is set.
+    pub source_map: Option<SourceMap>,
+}
+
+/// File level pragma comments, `/** @jsx h */`, `/** @jsxFrag Fragment */`,
+/// `/** @jsxRuntime automatic */` and `/** @jsxImportSource preact */`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Pragmas {
+    pub jsx: Option<String>,
+    pub jsx_frag: Option<String>,
+    pub jsx_runtime: Option<String>,
+    pub jsx_import_source: Option<String>,
 }
 
+impl Pragmas {
+    /// Collects the pragmas from the comments of `program`, parsed from `code`,
+    /// later comments win.
+    pub fn read(code: &str, program: &Program) -> Self {
+        let mut pragmas = Pragmas::default();
+
+        for comment in program.comments.iter() {
+            // `//` and `/*`, and the `*/` of a block comment.
+            let end = match comment.item {
+                Comment::Line => comment.end,
+                Comment::Block => comment.end - 2,
+            };
+            pragmas.read_comment(code.get(comment.start + 2..end).unwrap_or(""));
+        }
 
+        pragmas
+    }
 
-pub fn handle_fragment(lexer: &mut Lexer) -> Option<Fragment> {
-    assert_eq!(lexer.token, Token::FragmentOpen);
+    fn read_comment(&mut self, comment: &str) {
+        // @name value
+        let mut words = comment
+            .split(|c: char| c.is_whitespace() || c == '*')
+            .filter(|word| !word.is_empty())
+            .peekable();
 
-    let mut nodes: Vec<Leaf> = Vec::new();
+        while let Some(word) = words.next() {
+            let name = match word.strip_prefix('@') {
+                Some(name) => name,
+                None => continue,
+            };
 
-    nodes.push( Leaf::Token(Node { start: lexer.start(), end: lexer.end(), token: lexer.token }) );
+            let slot = match name {
+                "jsx" => &mut self.jsx,
+                "jsxFrag" => &mut self.jsx_frag,
+                "jsxRuntime" => &mut self.jsx_runtime,
+                "jsxImportSource" => &mut self.jsx_import_source,
+                _ => continue,
+            };
 
-    loop {
-        if let Err(e) = lexer.consume() {
-            return None;
+            if let Some(value) = words.peek() {
+                if !value.starts_with('@') {
+                    *slot = Some(value.to_string());
+                    words.next();
+                }
+            }
         }
+    }
+}
+
+
+/// Options of one file after the pragma comments have been applied.
+struct Config {
+    runtime: Runtime,
+    pragma: String,
+    pragma_frag: String,
+    import_source: String,
+    development: bool,
+    filename: Option<String>,
+    object_assign: bool,
+}
+
+impl Config {
+    fn new(code: &str, options: &TransformOptions, pragmas: Pragmas) -> Result<Self, ParseError> {
+        let runtime = match pragmas.jsx_runtime.as_deref() {
+            None => options.runtime,
+            Some("classic") => Runtime::Classic,
+            Some("automatic") => Runtime::Automatic,
+            Some(value) => {
+                let expected = vec!["`classic`".to_string(), "`automatic`".to_string()];
+                let message = format!("unknown @jsxRuntime `{}`, expected `classic` or `automatic`", value);
+                return Err(ParseError::new(code, Error::UnexpectedToken, (0, 0), expected, Some(message)));
+            },
+        };
 
+        Ok(Config {
+            runtime,
+            pragma: pragmas.jsx.unwrap_or_else(|| options.pragma.clone()),
+            pragma_frag: pragmas.jsx_frag.unwrap_or_else(|| options.pragma_frag.clone()),
+            import_source: pragmas.jsx_import_source.unwrap_or_else(|| options.import_source.clone()),
+            development: options.development,
+            filename: options.filename.clone(),
+            object_assign: options.object_assign,
+        })
     }
+}
+
+/// State shared by the whole file while nested expressions are transformed.
+struct Context {
+    config: Config,
+    /// Lines of the source, for `__source`.
+    lines: LineIndex,
+
+    uses_jsx: bool,
+    uses_jsxs: bool,
+    uses_fragment: bool,
+    uses_create_element: bool,
 
-    Some( Fragment {
-        nodes: nodes
-    })
+    /// Mappings of the output written so far, when a source map was asked for.
+    source_map: Option<SourceMapBuilder>,
 }
 
-pub fn handle_elem(lexer: &mut Lexer) -> Option<Element> {
-    assert_eq!(lexer.token, Token::ElementOpen);
+impl Context {
+    fn new(config: Config, code: &str) -> Self {
+        Context {
+            config,
+            lines: LineIndex::new(code),
+            uses_jsx: false,
+            uses_jsxs: false,
+            uses_fragment: false,
+            uses_create_element: false,
+            source_map: None,
+        }
+    }
+
+    /// 1-based line and column of the char at `index`, the column counts
+    /// UTF-16 code units like Babel's.
+    fn position(&self, index: usize) -> (usize, usize) {
+        let (line, column) = self.lines.line_column(index);
+        (line + 1, column + 1)
+    }
+
+    fn write_imports(&self, output: &mut String) {
+        let runtime_source = if self.config.development {
+            format!("{}/jsx-dev-runtime", self.config.import_source)
+        } else {
+            format!("{}/jsx-runtime", self.config.import_source)
+        };
+
+        let mut specifiers: Vec<&str> = Vec::new();
+        if self.config.development {
+            if self.uses_jsx || self.uses_jsxs {
+                specifiers.push("jsxDEV as _jsxDEV");
+            }
+        } else {
+            if self.uses_jsx {
+                specifiers.push("jsx as _jsx");
+            }
+            if self.uses_jsxs {
+                specifiers.push("jsxs as _jsxs");
+            }
+        }
+        if self.uses_fragment {
+            specifiers.push("Fragment as _Fragment");
+        }
 
-    let mut nodes: Vec<Leaf> = Vec::new();
+        if !specifiers.is_empty() {
+            output.push_str(&format!("import {{ {} }} from ", specifiers.join(", ")));
+            write_string_literal(output, &runtime_source);
+            output.push_str(";\n");
+        }
 
-    nodes.push( Leaf::Token(Node { start: lexer.start(), end: lexer.end(), token: lexer.token }) );
+        if self.uses_create_element {
+            output.push_str("import { createElement as _createElement } from ");
+            write_string_literal(output, &self.config.import_source);
+            output.push_str(";\n");
+        }
 
-    // Name
-    lexer.consume().ok()?;
-    if lexer.token != Token::Identifier {
-        return None;
+        if self.config.development && (self.uses_jsx || self.uses_jsxs) {
+            if let Some(ref filename) = self.config.filename {
+                output.push_str("var _jsxFileName = ");
+                write_string_literal(output, filename);
+                output.push_str(";\n");
+            }
+        }
     }
+}
 
-    // Attrs
-    lexer.consume().ok()?;
-    
 
-    Some( Element {
-        nodes: nodes
-    })
+/// The first argument of the generated call.
+#[derive(Clone, Copy)]
+enum ElemType<'a> {
+    Name(ElementName<'a>),
+    Fragment,
+}
+
+/// A rewritten span of the copied source.
+enum Edit<'a> {
+    Element(Node<'a, ElementExpression<'a>>),
+    Fragment(Node<'a, FragmentExpression<'a>>),
+    /// TypeScript syntax, `: string`, `as const`, `interface A {}`.
+    Remove,
+    /// `enum A { B }`, an object built by a function.
+    Enum(TsEnumDeclaration<'a>),
+    /// `namespace A { const b = 1; }`, which has no output.
+    Namespace,
+    /// Nothing in the source, `this.a = a;` for `constructor(private a)`.
+    ParameterProperties(NodeList<'a, Pattern<'a>>),
+}
+
+struct Generator<'a, 'b> {
+    code: &'a str,
+    output: &'b mut String,
+    context: &'b mut Context,
+    /// The program as parsed, when a fold changed it.
+    originals: Option<&'b Originals<'a>>,
 }
 
-pub fn transform(input: &str, output: &mut String) {
-    let code = input.chars().collect::<Vec<char>>();
-    let mut lexer = Lexer::new(&code);
+impl<'a, 'b> Generator<'a, 'b> {
+    #[inline]
+    fn write_source(&mut self, start: usize, end: usize) {
+        if let Some(ref mut source_map) = self.context.source_map {
+            // Every copied line starts with a mapping.
+            let generated = self.output.len();
+            source_map.add(generated, start);
+            for (offset, _) in self.code[start..end].match_indices('\n') {
+                if start + offset + 1 < end {
+                    source_map.add(generated + offset + 1, start + offset + 1);
+                }
+            }
+        }
+        self.output.push_str(&self.code[start..end]);
+    }
 
-    let mut tree: Vec<Leaf> = Vec::new();
+    /// Maps the output written next to `index` of the source.
+    #[inline]
+    fn add_mapping(&mut self, index: usize) {
+        if let Some(ref mut source_map) = self.context.source_map {
+            source_map.add(self.output.len(), index);
+        }
+    }
 
-    println!("parse ...");
-    loop {
-        match lexer.consume() {
-            Ok(_) => {
-                assert!(lexer.token != Token::EndOfProgram);
-                assert!(lexer.token != Token::UnexpectedToken);
+    /// Writes `expr` where an expression binding at least as tight as
+    /// `precedence` is expected, in parentheses if it binds weaker.
+    fn write_expression(&mut self, expr: &Loc<Expression<'a>>, precedence: u8) -> Result<(), ParseError> {
+        let parenthesized = expression_precedence(expr) < precedence;
+        if parenthesized {
+            self.output.push('(');
+        }
 
-                match lexer.token {
-                    Token::FragmentOpen => {
-                        if let Some(fragment) = handle_fragment(&mut lexer) {
-                            tree.push(Leaf::Fragment(fragment));
-                        }
-                    },
-                    Token::ElementOpen => {
-                        if let Some(elem) = handle_elem(&mut lexer) {
-                            tree.push(Leaf::Element(elem));
-                        }
+        match self.original_expression(expr) {
+            Some(original) => {
+                // The expression is copied verbatim, only the JSX it embeds is rewritten.
+                let mut nested = Vec::new();
+                collect_expression(original, &mut nested);
+                self.write_with_nested(original.start, original.end, nested)?;
+            },
+            None => self.print_expression(expr)?,
+        }
+
+        if parenthesized {
+            self.output.push(')');
+        }
+
+        Ok(())
+    }
+
+    fn write_statement(&mut self, stmt: &Loc<Statement<'a>>) -> Result<(), ParseError> {
+        match self.original_statement(stmt) {
+            Some(original) => {
+                let mut nested = Vec::new();
+                collect_statement(original, &mut nested);
+                self.write_with_nested(original.start, original.end, nested)
+            },
+            None => self.print_statement(stmt),
+        }
+    }
+
+    /// The node parsed at the span of `expr` if the folds left it as it was,
+    /// its source can be copied.
+    fn original_expression<'n>(&self, expr: &'n Loc<Expression<'a>>) -> Option<&'n Loc<Expression<'a>>> {
+        match self.originals {
+            None => Some(expr),
+            Some(originals) => originals.expressions.get(&(expr.start, expr.end))
+                .filter(|original| original.item == expr.item)
+                .copied(),
+        }
+    }
+
+    fn original_statement<'n>(&self, stmt: &'n Loc<Statement<'a>>) -> Option<&'n Loc<Statement<'a>>> {
+        match self.originals {
+            None => Some(stmt),
+            Some(originals) => originals.statements.get(&(stmt.start, stmt.end))
+                .filter(|original| original.item == stmt.item)
+                .copied(),
+        }
+    }
+
+    /// Copies `start..end` of the source with the spans in `nested` rewritten.
+    fn write_with_nested(&mut self, start: usize, end: usize, mut nested: Vec<Loc<Edit<'a>>>) -> Result<(), ParseError> {
+        nested.sort_by_key(|edit| edit.start);
+
+        let mut last_index = start;
+        for edit in nested.iter() {
+            self.write_source(last_index, edit.start);
+            last_index = edit.end;
+            match edit.item {
+                Edit::Element(elem) => self.write_elem(edit.start, elem)?,
+                Edit::Fragment(fragment) => self.write_fragment(edit.start, fragment)?,
+                Edit::Remove => last_index = self.skip_removed_whitespace(last_index, end),
+                Edit::Enum(ref declaration) => self.write_enum(declaration)?,
+                Edit::Namespace => return Err(unsupported(self.code, edit, "TypeScript namespaces are not supported")),
+                Edit::ParameterProperties(params) => {
+                    let separator = self.parameter_properties_separator(edit.start);
+                    self.write_parameter_properties(params, &separator);
+                    if separator == " " && self.code[edit.start..].trim_start().starts_with('}') {
+                        self.output.push(' ');
+                    }
+                },
+            }
+        }
+        self.write_source(last_index, end);
+
+        Ok(())
+    }
+
+    /// `private readonly a` becomes `a` rather than `  a`, and a line left
+    /// empty by the removal, `interface A {}`, is dropped.
+    fn skip_removed_whitespace(&mut self, mut index: usize, end: usize) -> usize {
+        if !self.output.is_empty() && !self.output.ends_with(char::is_whitespace) {
+            return index;
+        }
+
+        let bytes = self.code.as_bytes();
+        while index < end && (bytes[index] == b' ' || bytes[index] == b'\t') {
+            index += 1;
+        }
+
+        let line = self.output.rsplit('\n').next().unwrap_or("");
+        if !line.chars().all(|c| c == ' ' || c == '\t') {
+            return index;
+        }
+        let newline = match bytes.get(index..end) {
+            Some([b'\r', b'\n', ..]) => 2,
+            Some([b'\n', ..]) => 1,
+            _ => return index,
+        };
+        let len = self.output.len() - line.len();
+        self.output.truncate(len);
+
+        index + newline
+    }
+
+    // var A;
+    // (function (A) {
+    //   A[A["B"] = 0] = "B";
+    //   A["C"] = "c";
+    // })(A || (A = {}));
+    fn write_enum(&mut self, declaration: &TsEnumDeclaration<'a>) -> Result<(), ParseError> {
+        let indent = self.line_indent();
+        let id = declaration.id;
+        self.output.push_str("var ");
+        self.write_name(id.start, id.item);
+        self.output.push_str(&format!(";\n{}(function ({}) {{", indent, id.item));
+
+        // Members without an initializer count up from the last number.
+        let mut next = Some(0.0);
+        for member in declaration.members.iter() {
+            self.output.push_str(&format!("\n{}  ", indent));
+            self.add_mapping(member.start);
+            let mut key = String::new();
+            if member.id.item.starts_with(['"', '\'']) {
+                key.push_str(member.id.item);
+            } else {
+                write_string_literal(&mut key, member.id.item);
+            }
+
+            let value = match member.init {
+                None => match next {
+                    Some(value) => format!("{}", value),
+                    None => {
+                        let message = format!("enum member {} needs an initializer", key);
+                        return Err(unsupported(self.code, &member.id, &message));
                     },
-                    Token::EndOfProgram | Token::UnexpectedToken => {
-                        // Should return error.
-                        unreachable!();
+                },
+                Some(init) => {
+                    next = enum_number(self.code, init);
+                    match init.item {
+                        _ if next.is_some() => self.code[init.start..init.end].to_string(),
+                        Expression::Literal(Literal::String) => {
+                            self.output.push_str(&format!("{}[{}] = {};", id.item, key, &self.code[init.start..init.end]));
+                            continue;
+                        },
+                        _ => {
+                            let message = format!("enum member {} is computed, only numbers and strings are supported", key);
+                            return Err(unsupported(self.code, init, &message));
+                        },
+                    }
+                },
+            };
+            next = next.map(|value| value + 1.0);
+            self.output.push_str(&format!("{0}[{0}[{1}] = {2}] = {1};", id.item, key, value));
+        }
+
+        self.output.push_str(&format!("\n{}}})({1} || ({1} = {{}}));", indent, id.item));
+
+        Ok(())
+    }
+
+    /// `this.a = a;` for each parameter property in `params`, each after `separator`.
+    fn write_parameter_properties(&mut self, params: NodeList<'a, Pattern<'a>>, separator: &str) {
+        for param in params.iter() {
+            if let Some(name) = parameter_property_name(param) {
+                self.output.push_str(separator);
+                self.add_mapping(param.start);
+                self.output.push_str(&format!("this.{0} = {0};", name));
+            }
+        }
+    }
+
+    /// A new line with the indentation of the statements of a multiline
+    /// constructor body, or a space.
+    fn parameter_properties_separator(&self, index: usize) -> String {
+        let rest = &self.code[index..];
+        if !rest.trim_start_matches([' ', '\t']).starts_with(is_line_terminator) {
+            return " ".to_string();
+        }
+
+        let indent = self.line_indent();
+        if !self.output.ends_with('{') {
+            // After `super()`.
+            return format!("\n{}", indent);
+        }
+        match rest.lines().skip(1).find(|line| !line.trim().is_empty()) {
+            Some(line) if !line.trim_start().starts_with('}') => {
+                format!("\n{}", &line[..line.len() - line.trim_start().len()])
+            },
+            _ => format!("\n{}  ", indent),
+        }
+    }
+
+    /// `name`, mapped to `start` of the source.
+    fn write_name(&mut self, start: usize, name: &str) {
+        if let Some(ref mut source_map) = self.context.source_map {
+            source_map.add_named(self.output.len(), start, name);
+        }
+        self.output.push_str(name);
+    }
+
+    fn write_elem_name(&mut self, name: &ElementName) -> Result<(), ParseError> {
+        match name {
+            ElementName::Identifier(ident) => {
+                // Lowercase names are intrinsic (host) elements, `custom-element`
+                // is one too and no JavaScript identifier anyway.
+                if ident.item.starts_with(|c: char| c.is_ascii_lowercase()) || ident.item.contains('-') {
+                    if let Some(ref mut source_map) = self.context.source_map {
+                        source_map.add_named(self.output.len(), ident.start, ident.item);
+                    }
+                    write_string_literal(self.output, ident.item);
+                } else {
+                    self.write_name(ident.start, ident.item);
+                }
+            },
+            ElementName::NamespacedName(name_spaced_name) => {
+                let full_name = format!("{}:{}", name_spaced_name.ns.item, name_spaced_name.name.item);
+                self.add_mapping(name_spaced_name.ns.start);
+                write_string_literal(self.output, &full_name);
+            },
+            ElementName::MemberExpression(member_expr) => {
+                for (idx, member) in member_expr.members.iter().enumerate() {
+                    // A fold can leave `<ui.date-picker>`, the parser rejects it.
+                    if member.item.contains('-') {
+                        let message = format!("`{}` cannot be part of a member expression, it contains a `-`", member.item);
+                        let span = (member.start, member.end);
+                        return Err(ParseError::new(self.code, Error::UnexpectedToken, span, Vec::new(), Some(message)));
+                    }
+                    if idx > 0 {
+                        self.output.push('.');
+                    }
+                    self.add_mapping(member.start);
+                    self.output.push_str(member.item);
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    fn write_elem_type(&mut self, elem_type: ElemType) -> Result<(), ParseError> {
+        match elem_type {
+            ElemType::Name(ref name) => self.write_elem_name(name)?,
+            ElemType::Fragment => {
+                match self.context.config.runtime {
+                    Runtime::Classic => self.output.push_str(&self.context.config.pragma_frag),
+                    Runtime::Automatic => {
+                        self.context.uses_fragment = true;
+                        self.output.push_str("_Fragment");
                     },
-                    _ => {
-                        continue;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    fn write_attr_name(&mut self, name: &NormalAttributeName) {
+        match name {
+            NormalAttributeName::Identifier(ident) => {
+                // `aria-label` is no identifier, the key is quoted.
+                self.add_mapping(ident.start);
+                if ident.item.contains('-') {
+                    write_string_literal(self.output, ident.item);
+                } else {
+                    self.output.push_str(ident.item);
+                }
+            },
+            NormalAttributeName::NamespacedName(name_spaced_name) => {
+                let full_name = format!("{}:{}", name_spaced_name.ns.item, name_spaced_name.name.item);
+                self.add_mapping(name_spaced_name.ns.start);
+                write_string_literal(self.output, &full_name);
+            },
+        }
+    }
+
+    fn write_attr_value(&mut self, init: &Option<NormalAttributeInitializer<'a>>) -> Result<(), ParseError> {
+        match init {
+            None => {
+                self.output.push_str("true");
+            },
+            Some(init @ NormalAttributeInitializer::LiteralString(loc_token)) => {
+                self.add_mapping(loc_token.start);
+                write_string_literal(self.output, &init.cooked_string().unwrap_or_default());
+            },
+            Some(NormalAttributeInitializer::AssignmentExpression(loc_expr)) => {
+                self.write_expression(loc_expr, ASSIGNMENT)?;
+            },
+            Some(NormalAttributeInitializer::ElementExpression(loc_elem)) => {
+                self.write_elem(loc_elem.start, loc_elem)?;
+            },
+            Some(NormalAttributeInitializer::FragmentExpression(loc_fragment)) => {
+                self.write_fragment(loc_fragment.start, loc_fragment)?;
+            },
+        }
+
+        Ok(())
+    }
+
+    fn write_attr(&mut self, attr: &Attribute<'a>) -> Result<(), ParseError> {
+        match attr {
+            Attribute::Spread(expr) => {
+                self.output.push_str("...");
+                self.write_expression(expr, ASSIGNMENT)?;
+            },
+            Attribute::Normal(normal_attr) => {
+                self.write_attr_name(&normal_attr.name);
+                self.output.push_str(": ");
+                self.write_attr_value(&normal_attr.init)?;
+            },
+        }
+
+        Ok(())
+    }
+
+    /// `{ a: 1, children: b }`, `children` is the `children` prop of the automatic runtime.
+    fn write_object(&mut self, attrs: &[&Attribute<'a>], children: Option<&[&Child<'a>]>) -> Result<(), ParseError> {
+        self.output.push('{');
+
+        let mut is_empty = true;
+        for attr in attrs {
+            self.output.push_str(if is_empty { " " } else { ", " });
+            self.write_attr(attr)?;
+            is_empty = false;
+        }
+
+        if let Some(children) = children.filter(|children| !children.is_empty()) {
+            self.output.push_str(if is_empty { " " } else { ", " });
+            self.output.push_str("children: ");
+            if is_static_children(children) {
+                self.output.push('[');
+                for (idx, child) in children.iter().enumerate() {
+                    if idx > 0 {
+                        self.output.push_str(", ");
                     }
+                    self.write_child(child)?;
+                }
+                self.output.push(']');
+            } else {
+                self.write_child(children[0])?;
+            }
+            is_empty = false;
+        }
+
+        self.output.push_str(if is_empty { "}" } else { " }" });
+
+        Ok(())
+    }
+
+    /// The props object, `{ a: 1, ...b }`, or `Object.assign({ a: 1 }, b)` with
+    /// `TransformOptions::object_assign`.
+    fn write_props(&mut self, attrs: &[&Attribute<'a>], mut children: Option<&[&Child<'a>]>) -> Result<(), ParseError> {
+        let is_spread = |attr: &&Attribute| matches!(attr, Attribute::Spread(_));
+        if !self.context.config.object_assign || !attrs.iter().any(is_spread) {
+            return self.write_object(attrs, children);
+        }
+
+        // Object.assign({}, a, { b: 1 }, c)
+        self.output.push_str("Object.assign(");
+        let mut is_first = true;
+        if attrs.first().is_some_and(is_spread) {
+            self.output.push_str("{}");
+            is_first = false;
+        }
+
+        let mut rest = attrs;
+        while let Some(attr) = rest.first() {
+            if !is_first {
+                self.output.push_str(", ");
+            }
+            is_first = false;
+
+            if let Attribute::Spread(expr) = attr {
+                self.write_expression(expr, ASSIGNMENT)?;
+                rest = &rest[1..];
+            } else {
+                let len = rest.iter().position(is_spread).unwrap_or(rest.len());
+                // The children go in the last object.
+                let object_children = if len == rest.len() { children.take() } else { None };
+                self.write_object(&rest[..len], object_children)?;
+                rest = &rest[len..];
+            }
+        }
+
+        if let Some(children) = children.filter(|children| !children.is_empty()) {
+            self.output.push_str(", ");
+            self.write_object(&[], Some(children))?;
+        }
+        self.output.push(')');
+
+        Ok(())
+    }
+
+    fn write_attrs(&mut self, attrs: &[&Attribute<'a>]) -> Result<(), ParseError> {
+        if attrs.is_empty() {
+            self.output.push_str("null");
+            return Ok(());
+        }
+
+        self.write_props(attrs, None)
+    }
+
+    fn write_child(&mut self, child: &Child<'a>) -> Result<(), ParseError> {
+        match child {
+            Child::Text(loc_text) => {
+                self.add_mapping(loc_text.start);
+                write_string_literal(self.output, &loc_text.cooked());
+            },
+            Child::Element(loc_elem) => {
+                self.write_elem(loc_elem.start, loc_elem)?;
+            },
+            Child::Fragment(loc_fragment) => {
+                self.write_fragment(loc_fragment.start, loc_fragment)?;
+            },
+            Child::ChildExpression(expr) => {
+                self.write_expression(expr, ASSIGNMENT)?;
+            },
+            Child::Spread(expr) => {
+                self.output.push_str("...");
+                self.write_expression(expr, ASSIGNMENT)?;
+            },
+            Child::EmptyExpression(_) | Child::Error(_) => {},
+        }
+
+        Ok(())
+    }
+
+    /// `pragma(type, props, ...children)`
+    fn write_create_element(&mut self, callee: &str, elem_type: ElemType<'a>, attrs: &[&Attribute<'a>], children: &[&Child<'a>]) -> Result<(), ParseError> {
+        self.output.push_str(callee);
+        self.output.push('(');
+        self.write_elem_type(elem_type)?;
+        self.output.push_str(", ");
+        self.write_attrs(attrs)?;
+        for child in children {
+            self.output.push_str(", ");
+            self.write_child(child)?;
+        }
+        self.output.push(')');
+
+        Ok(())
+    }
+
+    /// `_jsx(type, { ...props, children }, key)`
+    fn write_jsx(&mut self, start: usize, elem_type: ElemType<'a>, attrs: &[&Attribute<'a>], children: &[&Child<'a>]) -> Result<(), ParseError> {
+        // `{...children}` has no counterpart in the props, Babel fails on it too.
+        for child in children {
+            if let Child::Spread(expr) = child {
+                return Err(unsupported(self.code, expr, "Spread children are not supported in React"));
+            }
+        }
+
+        let key_index = attrs.iter().position(|attr| self.is_key_attr(attr));
+
+        // `key` after a spread has to be resolved at runtime, React falls
+        // back to `createElement` for those.
+        if let Some(key_index) = key_index {
+            if attrs[..key_index].iter().any(|attr| matches!(attr, Attribute::Spread(_))) {
+                self.context.uses_create_element = true;
+                return self.write_create_element("_createElement", elem_type, attrs, children);
+            }
+        }
+
+        let is_static_children = is_static_children(children);
+        if is_static_children {
+            self.context.uses_jsxs = true;
+        } else {
+            self.context.uses_jsx = true;
+        }
+
+        let callee = if self.context.config.development {
+            "_jsxDEV"
+        } else if is_static_children {
+            "_jsxs"
+        } else {
+            "_jsx"
+        };
+
+        self.output.push_str(callee);
+        self.output.push('(');
+        self.write_elem_type(elem_type)?;
+        self.output.push_str(", ");
+
+        let props = attrs.iter()
+            .enumerate()
+            .filter(|&(idx, _)| Some(idx) != key_index)
+            .map(|(_, attr)| *attr)
+            .collect::<Vec<&Attribute<'a>>>();
+        self.write_props(&props, Some(children))?;
+
+        match key_index {
+            Some(key_index) => {
+                self.output.push_str(", ");
+                if let Attribute::Normal(ref normal_attr) = *attrs[key_index] {
+                    self.write_attr_value(&normal_attr.init)?;
                 }
             },
-            Err(Error::EndOfProgram) => {
-                break;
+            None => {
+                if self.context.config.development {
+                    self.output.push_str(", undefined");
+                }
             },
-            Err(e) => {
-                println!("{:?}", e);
-                break;
+        }
+
+        if self.context.config.development {
+            self.output.push_str(if is_static_children { ", true, " } else { ", false, " });
+
+            if self.context.config.filename.is_some() {
+                let (line, column) = self.context.position(start);
+                self.output.push_str(&format!(
+                    "{{ fileName: _jsxFileName, lineNumber: {}, columnNumber: {} }}",
+                    line,
+                    column,
+                ));
+            } else {
+                self.output.push_str("undefined");
             }
+
+            self.output.push_str(", this");
+        }
+
+        self.output.push(')');
+
+        Ok(())
+    }
+
+    fn is_key_attr(&self, attr: &Attribute<'a>) -> bool {
+        match attr {
+            Attribute::Normal(NormalAttribute { name: NormalAttributeName::Identifier(ident), .. }) => ident.item == "key",
+            _ => false,
+        }
+    }
+
+    fn write_call(&mut self, start: usize, elem_type: ElemType<'a>, attrs: List<'a, Attribute<'a>>, children: List<'a, Child<'a>>) -> Result<(), ParseError> {
+        let attrs = attrs.iter().collect::<Vec<&Attribute<'a>>>();
+        // `{ }`, `{/* comment */}` and whitespace between lines produce no child.
+        let children = children.iter()
+            .filter(|child| match child {
+                Child::Text(loc_text) => !loc_text.cooked().is_empty(),
+                Child::EmptyExpression(_) | Child::Error(_) => false,
+                _ => true,
+            })
+            .collect::<Vec<&Child<'a>>>();
+
+        self.add_mapping(start);
+        match self.context.config.runtime {
+            Runtime::Classic => {
+                let pragma = self.context.config.pragma.clone();
+                self.write_create_element(&pragma, elem_type, &attrs, &children)
+            },
+            Runtime::Automatic => {
+                self.write_jsx(start, elem_type, &attrs, &children)
+            },
         }
     }
 
-    println!("transform ...");
-    for leaf in tree {
-        
+    fn write_elem(&mut self, start: usize, elem: &ElementExpression<'a>) -> Result<(), ParseError> {
+        let children = elem.children.unwrap_or_else(List::empty);
+
+        self.write_call(start, ElemType::Name(elem.name), elem.attrs, children)
+    }
+
+    fn write_fragment(&mut self, start: usize, fragment: &FragmentExpression<'a>) -> Result<(), ParseError> {
+        self.write_call(start, ElemType::Fragment, List::empty(), fragment.children)
+    }
+
+    fn write_program(&mut self, program: &Program<'a>) -> Result<(), ParseError> {
+        if self.originals.is_none() {
+            // Everything around the JSX is copied verbatim.
+            let mut nested = Vec::new();
+            for stmt in program.body.iter() {
+                collect_statement(stmt, &mut nested);
+            }
+
+            return self.write_with_nested(0, self.code.len(), nested);
+        }
+
+        let last_index = self.write_statements(0, program.body, "", List::empty())?;
+        let end = self.code.len();
+        let last_index = last_index.max(self.previous_statement_end(end));
+        if self.is_trivia(last_index, end) {
+            self.write_source(last_index, end);
+        }
+
+        Ok(())
     }
 }
 
+
+// Prints the nodes a fold changed. Nodes left as parsed are still copied from
+// the source, so only the path from the program to the change loses its
+// formatting, comments between statements are kept where the source has them.
+
+impl<'a, 'b> Generator<'a, 'b> {
+    /// Whitespace at the start of the last line written.
+    fn line_indent(&self) -> String {
+        let line = self.output.rsplit('\n').next().unwrap_or("");
+        line.chars().take_while(|&c| c == ' ' || c == '\t').collect()
+    }
+
+    /// End of the last statement parsed before `index`, the source between it
+    /// and the next statement is comments and whitespace.
+    fn previous_statement_end(&self, index: usize) -> usize {
+        let ends = match self.originals {
+            Some(originals) => &originals.statement_ends,
+            None => return 0,
+        };
+
+        match ends.binary_search(&index) {
+            Ok(idx) => ends[idx],
+            Err(0) => 0,
+            Err(idx) => ends[idx - 1],
+        }
+    }
+
+    /// `start..end` of the source holds nothing but whitespace, comments and the `#!` line.
+    fn is_trivia(&self, start: usize, end: usize) -> bool {
+        let mut rest = match self.code.get(start..end) {
+            Some(rest) => rest,
+            None => return false,
+        };
+        if start == 0 && rest.starts_with("#!") {
+            rest = rest.find(is_line_terminator).map_or("", |idx| &rest[idx..]);
+        }
+
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '\u{feff}');
+            if rest.is_empty() {
+                return true;
+            }
+
+            if let Some(comment) = rest.strip_prefix("//") {
+                rest = comment.find(is_line_terminator).map_or("", |idx| &comment[idx..]);
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                match comment.find("*/") {
+                    Some(idx) => rest = &comment[idx + 2..],
+                    None => return false,
+                }
+            } else {
+                return false;
+            }
+        }
+    }
+
+    /// Writes `body` starting at `start` of the source, each statement after the
+    /// comments and whitespace that precede it in the source, or on a new line
+    /// indented by `indent`. Returns the end of the last statement written in
+    /// source order.
+    fn write_statements(
+        &mut self,
+        start: usize,
+        body: NodeList<'a, Statement<'a>>,
+        indent: &str,
+        params: NodeList<'a, Pattern<'a>>,
+    ) -> Result<usize, ParseError> {
+        let mut last_index = start;
+        let separator = format!("\n{}", indent);
+        if !body.iter().any(|stmt| is_super_call(stmt)) {
+            self.write_parameter_properties(params, &separator);
+        }
+
+        for stmt in body.iter() {
+            let before = self.output.len();
+            let gap_start = last_index.max(self.previous_statement_end(stmt.start));
+            if gap_start <= stmt.start && self.is_trivia(gap_start, stmt.start) {
+                self.write_source(gap_start, stmt.start);
+            } else if !self.output.is_empty() {
+                self.output.push('\n');
+                self.output.push_str(indent);
+            }
+
+            let after = self.output.len();
+            self.write_statement(stmt)?;
+            if self.output.len() <= after {
+                // TypeScript only, its line goes too.
+                self.output.truncate(before.min(self.output.len()));
+            } else if stmt.start >= last_index {
+                last_index = stmt.end;
+            }
+            if is_super_call(stmt) {
+                self.write_parameter_properties(params, &separator);
+            }
+        }
+
+        Ok(last_index)
+    }
+
+    /// `{ body }`, `start..end` spans the braces in the source.
+    fn write_block(&mut self, start: usize, end: usize, body: NodeList<'a, Statement<'a>>) -> Result<(), ParseError> {
+        self.write_body(start, end, body, List::empty())
+    }
+
+    /// `{ body }` of a function, `this.a = a;` for the parameter properties
+    /// in `params` opens it or follows `super()`.
+    fn write_body(
+        &mut self,
+        start: usize,
+        end: usize,
+        body: NodeList<'a, Statement<'a>>,
+        params: NodeList<'a, Pattern<'a>>,
+    ) -> Result<(), ParseError> {
+        let indent = self.line_indent();
+        self.output.push('{');
+
+        let body_start = self.output.len();
+        let last_index = self.write_statements(start + 1, body, &format!("{}  ", indent), params)?;
+        let end = end.saturating_sub(1);
+        let last_index = last_index.max(self.previous_statement_end(end));
+        if self.is_trivia(last_index, end) {
+            self.write_source(last_index, end);
+        } else if self.output.len() > body_start {
+            self.output.push('\n');
+            self.output.push_str(&indent);
+        }
+        self.output.push('}');
+
+        Ok(())
+    }
+
+    fn write_literal(&mut self, literal: &Loc<Literal>) {
+        match literal.item {
+            Literal::Null => self.output.push_str("null"),
+            Literal::True => self.output.push_str("true"),
+            Literal::False => self.output.push_str("false"),
+            // The text of the other literals is their source.
+            _ => self.write_source(literal.start, literal.end),
+        }
+    }
+
+    fn print_template(&mut self, template: &TemplateLiteral<'a>) -> Result<(), ParseError> {
+        self.output.push('`');
+        let mut expressions = template.expressions.iter();
+        for quasi in template.quasis.iter() {
+            self.write_source(quasi.start, quasi.end);
+            if let Some(expr) = expressions.next() {
+                self.output.push_str("${");
+                self.write_expression(expr, SEQUENCE)?;
+                self.output.push('}');
+            }
+        }
+        self.output.push('`');
+
+        Ok(())
+    }
+
+    fn print_property_key(&mut self, key: &PropertyKey<'a>) -> Result<(), ParseError> {
+        match *key {
+            PropertyKey::Identifier(name) | PropertyKey::PrivateName(name) => self.write_name(name.start, name.item),
+            PropertyKey::Literal(ref literal) => self.write_literal(literal),
+            PropertyKey::Computed(expr) => {
+                self.output.push('[');
+                self.write_expression(expr, ASSIGNMENT)?;
+                self.output.push(']');
+            },
+        }
+
+        Ok(())
+    }
+
+    /// `(a, b = 1)`, the `this` parameter of TypeScript is left out.
+    fn print_params(&mut self, params: NodeList<'a, Pattern<'a>>) -> Result<(), ParseError> {
+        self.output.push('(');
+        let params = params.iter().filter(|param| match param.item {
+            Pattern::Typed(ref typed) => !is_this_param(typed.pattern),
+            _ => true,
+        });
+        for (idx, param) in params.enumerate() {
+            if idx > 0 {
+                self.output.push_str(", ");
+            }
+            self.print_pattern(param)?;
+        }
+        self.output.push(')');
+
+        Ok(())
+    }
+
+    fn print_function(&mut self, function: &Function<'a>) -> Result<(), ParseError> {
+        if function.is_async {
+            self.output.push_str("async ");
+        }
+        self.output.push_str("function");
+        if function.is_generator {
+            self.output.push('*');
+        }
+        if let Some(id) = function.id {
+            self.output.push(' ');
+            self.write_name(id.start, id.item);
+        }
+        self.print_params(function.params)?;
+        self.output.push(' ');
+        self.write_block(function.body.start, function.body.end, function.body.body)
+    }
+
+    /// `static async *key() {}`, `get key() {}`
+    fn print_method(&mut self, is_static: bool, kind: MethodKind, key: &PropertyKey<'a>, function: &Function<'a>) -> Result<(), ParseError> {
+        if is_static {
+            self.output.push_str("static ");
+        }
+        match kind {
+            MethodKind::Get => self.output.push_str("get "),
+            MethodKind::Set => self.output.push_str("set "),
+            MethodKind::Constructor | MethodKind::Method => {},
+        }
+        if function.is_async {
+            self.output.push_str("async ");
+        }
+        if function.is_generator {
+            self.output.push('*');
+        }
+        self.print_property_key(key)?;
+        self.print_params(function.params)?;
+        self.output.push(' ');
+        let params = if kind == MethodKind::Constructor { function.params } else { List::empty() };
+        self.write_body(function.body.start, function.body.end, function.body.body, params)
+    }
+
+    fn print_class(&mut self, class: &Class<'a>) -> Result<(), ParseError> {
+        self.output.push_str("class");
+        if let Some(id) = class.id {
+            self.output.push(' ');
+            self.write_name(id.start, id.item);
+        }
+        if let Some(super_class) = class.super_class {
+            self.output.push_str(" extends ");
+            self.write_expression(super_class, CALL)?;
+        }
+        self.output.push_str(" {");
+
+        let indent = self.line_indent();
+        let body_start = self.output.len();
+        for member in class.body.iter() {
+            let before = self.output.len();
+            self.output.push('\n');
+            self.output.push_str(&indent);
+            self.output.push_str("  ");
+
+            let after = self.output.len();
+            self.print_class_member(member)?;
+            if self.output.len() == after {
+                self.output.truncate(before);
+            }
+        }
+        if self.output.len() > body_start {
+            self.output.push('\n');
+            self.output.push_str(&indent);
+        }
+        self.output.push('}');
+
+        Ok(())
+    }
+
+    fn print_class_member(&mut self, member: &Loc<ClassMember<'a>>) -> Result<(), ParseError> {
+        match member.item {
+            ClassMember::Method { is_static, kind, ref key, ref value, .. } => {
+                self.print_method(is_static, kind, key, value)?;
+            },
+            // declare a: string; abstract b: number;
+            ClassMember::Property { ref modifiers, .. } if modifiers.iter()
+                .any(|modifier| modifier.item == TsModifier::Declare || modifier.item == TsModifier::Abstract) => {},
+            ClassMember::Property { is_static, ref key, value, .. } => {
+                if is_static {
+                    self.output.push_str("static ");
+                }
+                self.print_property_key(key)?;
+                if let Some(value) = value {
+                    self.output.push_str(" = ");
+                    self.write_expression(value, ASSIGNMENT)?;
+                }
+                self.output.push(';');
+            },
+            ClassMember::StaticBlock(ref block) => {
+                self.output.push_str("static ");
+                self.write_block(member.start, member.end, block.body)?;
+            },
+            ClassMember::TsMethodSignature { .. } | ClassMember::TsIndexSignature(_) => {},
+        }
+
+        Ok(())
+    }
+
+    fn print_property(&mut self, property: &Loc<Property<'a>>) -> Result<(), ParseError> {
+        match property.item {
+            Property::Init { ref key, value } => {
+                self.print_property_key(key)?;
+                self.output.push_str(": ");
+                self.write_expression(value, ASSIGNMENT)?;
+            },
+            Property::Shorthand(name) => self.write_name(name.start, name.item),
+            Property::Method { kind, ref key, ref value } => self.print_method(false, kind, key, value)?,
+            Property::Spread(expr) => {
+                self.output.push_str("...");
+                self.write_expression(expr, ASSIGNMENT)?;
+            },
+        }
+
+        Ok(())
+    }
+
+    fn print_pattern(&mut self, pattern: &Loc<Pattern<'a>>) -> Result<(), ParseError> {
+        match pattern.item {
+            Pattern::Identifier(name) => self.write_name(pattern.start, name),
+            Pattern::Object(ref object) => {
+                if object.properties.is_empty() {
+                    self.output.push_str("{}");
+                    return Ok(());
+                }
+
+                self.output.push_str("{ ");
+                for (idx, property) in object.properties.iter().enumerate() {
+                    if idx > 0 {
+                        self.output.push_str(", ");
+                    }
+                    match property.item {
+                        PatternProperty::Property { ref key, value } => {
+                            self.print_property_key(key)?;
+                            self.output.push_str(": ");
+                            self.print_pattern(value)?;
+                        },
+                        PatternProperty::Shorthand { name, init } => {
+                            self.write_name(name.start, name.item);
+                            if let Some(init) = init {
+                                self.output.push_str(" = ");
+                                self.write_expression(init, ASSIGNMENT)?;
+                            }
+                        },
+                        PatternProperty::Rest(rest) => {
+                            self.output.push_str("...");
+                            self.print_pattern(rest)?;
+                        },
+                    }
+                }
+                self.output.push_str(" }");
+            },
+            Pattern::Array(ref array) => {
+                self.output.push('[');
+                for (idx, element) in array.elements.iter().enumerate() {
+                    if idx > 0 {
+                        self.output.push_str(", ");
+                    }
+                    if let Some(element) = element {
+                        self.print_pattern(element)?;
+                    }
+                }
+                // `[a, ,]`, a hole at the end needs its own comma.
+                if let Some(None) = array.elements.iter().last() {
+                    self.output.push(',');
+                }
+                self.output.push(']');
+            },
+            Pattern::Assignment(ref assignment) => {
+                self.print_pattern(assignment.left)?;
+                self.output.push_str(" = ");
+                self.write_expression(assignment.right, ASSIGNMENT)?;
+            },
+            Pattern::Rest(rest) => {
+                self.output.push_str("...");
+                self.print_pattern(rest)?;
+            },
+            Pattern::Expression(expr) => self.write_expression(expr, CALL)?,
+            Pattern::Typed(ref typed) => self.print_pattern(typed.pattern)?,
+            Pattern::TsParameterProperty(ref property) => self.print_pattern(property.parameter)?,
+        }
+
+        Ok(())
+    }
+
+    fn print_expression(&mut self, expr: &Loc<Expression<'a>>) -> Result<(), ParseError> {
+        self.add_mapping(expr.start);
+
+        match expr.item {
+            Expression::This => self.output.push_str("this"),
+            Expression::Super => self.output.push_str("super"),
+            Expression::Identifier(name) => self.write_name(expr.start, name),
+            Expression::Literal(literal) => self.write_literal(&Loc::new(expr.start, expr.end, literal)),
+            Expression::Template(ref template) => self.print_template(template)?,
+            Expression::TaggedTemplate(ref tagged) => {
+                self.write_expression(tagged.tag, CALL)?;
+                self.print_template(&tagged.quasi)?;
+            },
+            Expression::Array(ref array) => {
+                self.output.push('[');
+                for (idx, element) in array.elements.iter().enumerate() {
+                    if idx > 0 {
+                        self.output.push_str(", ");
+                    }
+                    if let Some(element) = element {
+                        self.write_expression(element, ASSIGNMENT)?;
+                    }
+                }
+                if let Some(None) = array.elements.iter().last() {
+                    self.output.push(',');
+                }
+                self.output.push(']');
+            },
+            Expression::Object(ref object) => {
+                if object.properties.is_empty() {
+                    self.output.push_str("{}");
+                } else {
+                    self.output.push_str("{ ");
+                    for (idx, property) in object.properties.iter().enumerate() {
+                        if idx > 0 {
+                            self.output.push_str(", ");
+                        }
+                        self.print_property(property)?;
+                    }
+                    self.output.push_str(" }");
+                }
+            },
+            Expression::Function(ref function) => self.print_function(function)?,
+            Expression::Class(ref class) => self.print_class(class)?,
+            Expression::Arrow(ref arrow) => {
+                if arrow.is_async {
+                    self.output.push_str("async ");
+                }
+                self.print_params(arrow.params)?;
+                self.output.push_str(" => ");
+                match arrow.body {
+                    // `() => ({})`, a brace would open a block.
+                    ArrowBody::Expression(body) if leftmost(body) == Leftmost::Brace => {
+                        self.output.push('(');
+                        self.write_expression(body, ASSIGNMENT)?;
+                        self.output.push(')');
+                    },
+                    ArrowBody::Expression(body) => self.write_expression(body, ASSIGNMENT)?,
+                    ArrowBody::Block(ref block) => self.write_block(block.start, block.end, block.body)?,
+                }
+            },
+            Expression::Parenthesized(inner) => {
+                self.output.push('(');
+                self.write_expression(inner, SEQUENCE)?;
+                self.output.push(')');
+            },
+            Expression::Unary(ref unary) => {
+                self.output.push_str(unary.operator.as_str());
+                let sign = match unary.operator {
+                    UnaryOperator::Minus => Some(UpdateOperator::Decrement),
+                    UnaryOperator::Plus => Some(UpdateOperator::Increment),
+                    UnaryOperator::Typeof | UnaryOperator::Void | UnaryOperator::Delete => {
+                        self.output.push(' ');
+                        None
+                    },
+                    UnaryOperator::Not | UnaryOperator::BitNot => None,
+                };
+                // `- -a` and `- --a` are no `--a`.
+                let is_same_sign = match unary.argument.item {
+                    Expression::Unary(ref argument) => argument.operator == unary.operator,
+                    Expression::Update(ref argument) => argument.prefix && Some(argument.operator) == sign,
+                    _ => false,
+                };
+                if sign.is_some() && is_same_sign {
+                    self.output.push(' ');
+                }
+                self.write_expression(unary.argument, UNARY)?;
+            },
+            Expression::Update(ref update) => {
+                if update.prefix {
+                    self.output.push_str(update.operator.as_str());
+                    self.write_expression(update.argument, CALL)?;
+                } else {
+                    self.write_expression(update.argument, CALL)?;
+                    self.output.push_str(update.operator.as_str());
+                }
+            },
+            Expression::Binary(ref binary) => {
+                let precedence = binary_precedence(binary.operator);
+                let (left, right) = match binary.operator {
+                    // `(-a) ** b`, a unary operand on the left is an error.
+                    BinaryOperator::Exp => (UNARY + 1, precedence),
+                    _ => (precedence, precedence + 1),
+                };
+                self.write_expression(binary.left, operand_precedence(binary.operator, binary.left, left))?;
+                self.output.push(' ');
+                self.output.push_str(binary.operator.as_str());
+                self.output.push(' ');
+                self.write_expression(binary.right, operand_precedence(binary.operator, binary.right, right))?;
+            },
+            Expression::Conditional(ref conditional) => {
+                self.write_expression(conditional.test, CONDITIONAL + 1)?;
+                self.output.push_str(" ? ");
+                self.write_expression(conditional.consequent, ASSIGNMENT)?;
+                self.output.push_str(" : ");
+                self.write_expression(conditional.alternate, ASSIGNMENT)?;
+            },
+            Expression::Assignment(ref assignment) => {
+                self.print_pattern(assignment.left)?;
+                self.output.push(' ');
+                self.output.push_str(assignment.operator.as_str());
+                self.output.push(' ');
+                self.write_expression(assignment.right, ASSIGNMENT)?;
+            },
+            Expression::Sequence(expressions) => {
+                for (idx, expr) in expressions.iter().enumerate() {
+                    if idx > 0 {
+                        self.output.push_str(", ");
+                    }
+                    self.write_expression(expr, ASSIGNMENT)?;
+                }
+            },
+            Expression::Call(ref call) => {
+                self.write_expression(call.callee, CALL)?;
+                if call.optional {
+                    self.output.push_str("?.");
+                }
+                self.print_arguments(call.arguments)?;
+            },
+            Expression::New(ref new) => {
+                self.output.push_str("new ");
+                // `new (f())()` calls what `f` returns.
+                let precedence = if has_call(new.callee) { PRIMARY } else { CALL };
+                self.write_expression(new.callee, precedence)?;
+                self.print_arguments(new.arguments)?;
+            },
+            Expression::StaticMember(ref member) => {
+                // `1.toString` is a number followed by `toString`.
+                let is_number = member.object.item == Expression::Literal(Literal::Number);
+                self.write_expression(member.object, if is_number { PRIMARY } else { CALL })?;
+                self.output.push_str(if member.optional { "?." } else { "." });
+                self.write_name(member.property.start, member.property.item);
+            },
+            Expression::ComputedMember(ref member) => {
+                self.write_expression(member.object, CALL)?;
+                self.output.push_str(if member.optional { "?.[" } else { "[" });
+                self.write_expression(member.property, SEQUENCE)?;
+                self.output.push(']');
+            },
+            Expression::Spread(argument) => {
+                self.output.push_str("...");
+                self.write_expression(argument, ASSIGNMENT)?;
+            },
+            Expression::Await(argument) => {
+                self.output.push_str("await ");
+                self.write_expression(argument, UNARY)?;
+            },
+            Expression::Yield(ref yield_expr) => {
+                self.output.push_str(if yield_expr.delegate { "yield*" } else { "yield" });
+                if let Some(argument) = yield_expr.argument {
+                    self.output.push(' ');
+                    self.write_expression(argument, ASSIGNMENT)?;
+                }
+            },
+            Expression::MetaProperty(ref meta_property) => {
+                self.write_name(meta_property.meta.start, meta_property.meta.item);
+                self.output.push('.');
+                self.write_name(meta_property.property.start, meta_property.property.item);
+            },
+            Expression::Import(source) => {
+                self.output.push_str("import(");
+                self.write_expression(source, ASSIGNMENT)?;
+                self.output.push(')');
+            },
+            Expression::Element(elem) => self.write_elem(expr.start, elem)?,
+            Expression::Fragment(fragment) => self.write_fragment(expr.start, fragment)?,
+            // The precedence of `a as T` is the one of `a`.
+            Expression::TsAs(ref as_expr) | Expression::TsSatisfies(ref as_expr) => {
+                self.write_expression(as_expr.expression, SEQUENCE)?;
+            },
+            Expression::TsNonNull(inner) => self.write_expression(inner, SEQUENCE)?,
+        }
+
+        Ok(())
+    }
+
+    fn print_arguments(&mut self, arguments: NodeList<'a, Expression<'a>>) -> Result<(), ParseError> {
+        self.output.push('(');
+        for (idx, argument) in arguments.iter().enumerate() {
+            if idx > 0 {
+                self.output.push_str(", ");
+            }
+            self.write_expression(argument, ASSIGNMENT)?;
+        }
+        self.output.push(')');
+
+        Ok(())
+    }
+
+    fn print_variable(&mut self, declaration: &VariableDeclaration<'a>) -> Result<(), ParseError> {
+        self.output.push_str(match declaration.kind {
+            VariableKind::Var => "var ",
+            VariableKind::Let => "let ",
+            VariableKind::Const => "const ",
+        });
+        for (idx, declarator) in declaration.declarations.iter().enumerate() {
+            if idx > 0 {
+                self.output.push_str(", ");
+            }
+            self.print_pattern(declarator.id)?;
+            if let Some(init) = declarator.init {
+                self.output.push_str(" = ");
+                self.write_expression(init, ASSIGNMENT)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_for_head(&mut self, head: &ForHead<'a>) -> Result<(), ParseError> {
+        match *head {
+            ForHead::Variable(ref declaration) => self.print_variable(declaration),
+            ForHead::Pattern(pattern) => self.print_pattern(pattern),
+        }
+    }
+
+    fn print_statement(&mut self, stmt: &Loc<Statement<'a>>) -> Result<(), ParseError> {
+        self.add_mapping(stmt.start);
+
+        match stmt.item {
+            Statement::Empty => self.output.push(';'),
+            Statement::Debugger => self.output.push_str("debugger;"),
+            Statement::Expression(expr) => {
+                // `{`, `function` and `class` would start a declaration.
+                let parenthesized = leftmost(expr) != Leftmost::Other;
+                if parenthesized {
+                    self.output.push('(');
+                }
+                self.write_expression(expr, SEQUENCE)?;
+                if parenthesized {
+                    self.output.push(')');
+                }
+                self.output.push(';');
+            },
+            Statement::Block(ref block) => self.write_block(stmt.start, stmt.end, block.body)?,
+            Statement::Variable(ref declaration) => {
+                self.print_variable(declaration)?;
+                self.output.push(';');
+            },
+            Statement::Function(ref function) => self.print_function(function)?,
+            Statement::Class(ref class) => self.print_class(class)?,
+            Statement::Return(argument) => {
+                self.output.push_str("return");
+                if let Some(argument) = argument {
+                    self.output.push(' ');
+                    self.write_expression(argument, SEQUENCE)?;
+                }
+                self.output.push(';');
+            },
+            Statement::If(ref if_stmt) => {
+                self.output.push_str("if (");
+                self.write_expression(if_stmt.test, SEQUENCE)?;
+                self.output.push_str(") ");
+                // The `else` would go to an inner `if` without one.
+                let is_dangling = if_stmt.alternate.is_some()
+                    && matches!(if_stmt.consequent.item, Statement::If(IfStatement { alternate: None, .. }));
+                if is_dangling {
+                    self.output.push_str("{ ");
+                    self.write_statement(if_stmt.consequent)?;
+                    self.output.push_str(" }");
+                } else {
+                    self.write_statement(if_stmt.consequent)?;
+                }
+                if let Some(alternate) = if_stmt.alternate {
+                    self.output.push_str(" else ");
+                    self.write_statement(alternate)?;
+                }
+            },
+            Statement::Switch(ref switch) => {
+                self.output.push_str("switch (");
+                self.write_expression(switch.discriminant, SEQUENCE)?;
+                self.output.push_str(") {");
+                let indent = self.line_indent();
+                for case in switch.cases.iter() {
+                    self.output.push('\n');
+                    self.output.push_str(&indent);
+                    match case.test {
+                        Some(test) => {
+                            self.output.push_str("  case ");
+                            self.write_expression(test, SEQUENCE)?;
+                            self.output.push(':');
+                        },
+                        None => self.output.push_str("  default:"),
+                    }
+                    self.write_statements(case.start, case.consequent, &format!("{}    ", indent), List::empty())?;
+                }
+                self.output.push('\n');
+                self.output.push_str(&indent);
+                self.output.push('}');
+            },
+            Statement::While(ref while_stmt) => {
+                self.output.push_str("while (");
+                self.write_expression(while_stmt.test, SEQUENCE)?;
+                self.output.push_str(") ");
+                self.write_statement(while_stmt.body)?;
+            },
+            Statement::DoWhile(ref do_while) => {
+                self.output.push_str("do ");
+                self.write_statement(do_while.body)?;
+                self.output.push_str(" while (");
+                self.write_expression(do_while.test, SEQUENCE)?;
+                self.output.push_str(");");
+            },
+            Statement::For(ref for_stmt) => {
+                self.output.push_str("for (");
+                match for_stmt.init {
+                    Some(ForInit::Variable(ref declaration)) => self.print_variable(declaration)?,
+                    Some(ForInit::Expression(expr)) => self.write_expression(expr, SEQUENCE)?,
+                    None => {},
+                }
+                self.output.push(';');
+                if let Some(test) = for_stmt.test {
+                    self.output.push(' ');
+                    self.write_expression(test, SEQUENCE)?;
+                }
+                self.output.push(';');
+                if let Some(update) = for_stmt.update {
+                    self.output.push(' ');
+                    self.write_expression(update, SEQUENCE)?;
+                }
+                self.output.push_str(") ");
+                self.write_statement(for_stmt.body)?;
+            },
+            Statement::ForIn(ref for_in) => {
+                self.output.push_str("for (");
+                self.print_for_head(&for_in.left)?;
+                self.output.push_str(" in ");
+                self.write_expression(for_in.right, SEQUENCE)?;
+                self.output.push_str(") ");
+                self.write_statement(for_in.body)?;
+            },
+            Statement::ForOf(ref for_of) => {
+                self.output.push_str(if for_of.is_await { "for await (" } else { "for (" });
+                self.print_for_head(&for_of.left)?;
+                self.output.push_str(" of ");
+                self.write_expression(for_of.right, ASSIGNMENT)?;
+                self.output.push_str(") ");
+                self.write_statement(for_of.body)?;
+            },
+            Statement::Break(label) | Statement::Continue(label) => {
+                self.output.push_str(if matches!(stmt.item, Statement::Break(_)) { "break" } else { "continue" });
+                if let Some(label) = label {
+                    self.output.push(' ');
+                    self.write_name(label.start, label.item);
+                }
+                self.output.push(';');
+            },
+            Statement::Throw(argument) => {
+                self.output.push_str("throw ");
+                self.write_expression(argument, SEQUENCE)?;
+                self.output.push(';');
+            },
+            Statement::Try(ref try_stmt) => {
+                self.output.push_str("try ");
+                self.write_block(try_stmt.block.start, try_stmt.block.end, try_stmt.block.body)?;
+                if let Some(ref handler) = try_stmt.handler {
+                    self.output.push_str(" catch ");
+                    if let Some(param) = handler.param {
+                        self.output.push('(');
+                        self.print_pattern(param)?;
+                        self.output.push_str(") ");
+                    }
+                    self.write_block(handler.body.start, handler.body.end, handler.body.body)?;
+                }
+                if let Some(ref finalizer) = try_stmt.finalizer {
+                    self.output.push_str(" finally ");
+                    self.write_block(finalizer.start, finalizer.end, finalizer.body)?;
+                }
+            },
+            Statement::Labeled(ref labeled) => {
+                self.write_name(labeled.label.start, labeled.label.item);
+                self.output.push_str(": ");
+                self.write_statement(labeled.body)?;
+            },
+            Statement::With(ref with) => {
+                self.output.push_str("with (");
+                self.write_expression(with.object, SEQUENCE)?;
+                self.output.push_str(") ");
+                self.write_statement(with.body)?;
+            },
+            Statement::Import(ref import) => self.print_import(import),
+            Statement::ExportNamed(ref export) => {
+                match export.declaration {
+                    Some(declaration) if is_type_declaration(declaration) => {},
+                    Some(declaration) => {
+                        self.output.push_str("export ");
+                        self.write_statement(declaration)?;
+                    },
+                    None => self.print_export_specifiers(export),
+                }
+            },
+            Statement::ExportDefault(ref export) => {
+                match *export {
+                    ExportDefaultDeclaration::Function(ref function) => {
+                        self.output.push_str("export default ");
+                        self.print_function(function)?;
+                    },
+                    ExportDefaultDeclaration::Class(ref class) => {
+                        self.output.push_str("export default ");
+                        self.print_class(class)?;
+                    },
+                    ExportDefaultDeclaration::Expression(expr) => {
+                        self.output.push_str("export default ");
+                        // `export default (function () {})()`
+                        let parenthesized = matches!(leftmost(expr), Leftmost::Function | Leftmost::Class);
+                        if parenthesized {
+                            self.output.push('(');
+                        }
+                        self.write_expression(expr, ASSIGNMENT)?;
+                        if parenthesized {
+                            self.output.push(')');
+                        }
+                        self.output.push(';');
+                    },
+                    ExportDefaultDeclaration::TsDeclareFunction(_) | ExportDefaultDeclaration::TsInterface(_) => {},
+                }
+            },
+            Statement::ExportAll(ref export) => {
+                if !export.is_type {
+                    self.output.push_str("export * ");
+                    if let Some(exported) = export.exported {
+                        self.output.push_str("as ");
+                        self.write_name(exported.start, exported.item);
+                        self.output.push(' ');
+                    }
+                    self.output.push_str("from ");
+                    self.write_literal(&export.source);
+                    self.output.push(';');
+                }
+            },
+            Statement::TsEnum(ref declaration) => self.write_enum(declaration)?,
+            Statement::TsModule(_) if !is_type_declaration(stmt) => {
+                return Err(unsupported(self.code, stmt, "TypeScript namespaces are not supported"));
+            },
+            Statement::TsInterface(_) | Statement::TsTypeAlias(_) | Statement::TsModule(_)
+            | Statement::TsDeclareFunction(_) | Statement::TsDeclare(_) | Statement::Error => {},
+        }
+
+        Ok(())
+    }
+
+    /// `import a, { b as c } from "d";` without the type imports.
+    fn print_import(&mut self, import: &ImportDeclaration<'a>) {
+        let specifiers = import.specifiers.iter()
+            .filter(|specifier| !matches!(specifier.item, ImportSpecifier::Named { is_type: true, .. }))
+            .collect::<Vec<_>>();
+        if import.is_type || (specifiers.is_empty() && !import.specifiers.is_empty()) {
+            return;
+        }
+
+        self.output.push_str("import ");
+        let mut named = Vec::new();
+        for specifier in specifiers.iter() {
+            match specifier.item {
+                ImportSpecifier::Default(local) => {
+                    self.write_name(local.start, local.item);
+                    self.output.push_str(", ");
+                },
+                ImportSpecifier::Namespace(local) => {
+                    self.output.push_str("* as ");
+                    self.write_name(local.start, local.item);
+                    self.output.push_str(", ");
+                },
+                ImportSpecifier::Named { imported, local, .. } if imported.item == local.item => named.push(local.item.to_string()),
+                ImportSpecifier::Named { imported, local, .. } => named.push(format!("{} as {}", imported.item, local.item)),
+            }
+        }
+        if !named.is_empty() {
+            self.output.push_str(&format!("{{ {} }}, ", named.join(", ")));
+        }
+        if !specifiers.is_empty() {
+            // The last `, ` is the one before `from`.
+            self.output.truncate(self.output.len() - 2);
+            self.output.push_str(" from ");
+        }
+        self.write_literal(&import.source);
+        self.output.push(';');
+    }
+
+    /// `export { a as b } from "c";` without the type exports.
+    fn print_export_specifiers(&mut self, export: &ExportNamedDeclaration<'a>) {
+        let specifiers = export.specifiers.iter()
+            .filter(|specifier| !specifier.is_type)
+            .map(|specifier| if specifier.local.item == specifier.exported.item {
+                specifier.local.item.to_string()
+            } else {
+                format!("{} as {}", specifier.local.item, specifier.exported.item)
+            })
+            .collect::<Vec<String>>();
+        if export.is_type || (specifiers.is_empty() && !export.specifiers.is_empty()) {
+            return;
+        }
+
+        if specifiers.is_empty() {
+            self.output.push_str("export {}");
+        } else {
+            self.output.push_str(&format!("export {{ {} }}", specifiers.join(", ")));
+        }
+        if let Some(ref source) = export.source {
+            self.output.push_str(" from ");
+            self.write_literal(source);
+        }
+        self.output.push(';');
+    }
+}
+
+
+/// Nodes of the program as parsed, by span. A node the folds left equal to the
+/// one parsed at its span is copied from the source, any other is printed.
+#[derive(Default)]
+struct Originals<'a> {
+    statements: HashMap<(usize, usize), Node<'a, Statement<'a>>>,
+    expressions: HashMap<(usize, usize), Node<'a, Expression<'a>>>,
+    /// End of every statement, sorted.
+    statement_ends: Vec<usize>,
+}
+
+impl<'a> Originals<'a> {
+    fn new(program: &'a Program<'a>) -> Self {
+        let mut originals = Originals::default();
+        originals.visit_program(program);
+        originals.statement_ends.sort_unstable();
+
+        originals
+    }
+}
+
+impl<'a> Visit<'a> for Originals<'a> {
+    fn enter(&mut self, node: visit::Node<'a, 'a>) {
+        match node {
+            // The outermost of the nodes sharing a span.
+            visit::Node::Statement(stmt) => {
+                self.statements.entry((stmt.start, stmt.end)).or_insert(stmt);
+                self.statement_ends.push(stmt.end);
+            },
+            visit::Node::Expression(expr) => {
+                self.expressions.entry((expr.start, expr.end)).or_insert(expr);
+            },
+            _ => {},
+        }
+    }
+}
+
+
+// Binding power of expressions, an operand binding weaker than its
+// position allows is parenthesized.
+const SEQUENCE: u8 = 0;
+const ASSIGNMENT: u8 = 1;
+const CONDITIONAL: u8 = 2;
+const UNARY: u8 = 15;
+const POSTFIX: u8 = 16;
+const CALL: u8 = 17;
+const PRIMARY: u8 = 18;
+
+fn binary_precedence(operator: BinaryOperator) -> u8 {
+    match operator {
+        BinaryOperator::Nullish => 3,
+        BinaryOperator::Or => 4,
+        BinaryOperator::And => 5,
+        BinaryOperator::BitOr => 6,
+        BinaryOperator::BitXor => 7,
+        BinaryOperator::BitAnd => 8,
+        BinaryOperator::Equal | BinaryOperator::NotEqual
+        | BinaryOperator::StrictEqual | BinaryOperator::StrictNotEqual => 9,
+        BinaryOperator::Less | BinaryOperator::Greater | BinaryOperator::LessEqual
+        | BinaryOperator::GreaterEqual | BinaryOperator::In | BinaryOperator::Instanceof => 10,
+        BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight | BinaryOperator::UnsignedShiftRight => 11,
+        BinaryOperator::Add | BinaryOperator::Sub => 12,
+        BinaryOperator::Mul | BinaryOperator::Div | BinaryOperator::Rem => 13,
+        BinaryOperator::Exp => 14,
+    }
+}
+
+fn expression_precedence(expr: &Loc<Expression>) -> u8 {
+    match expr.item {
+        Expression::Sequence(_) => SEQUENCE,
+        Expression::Assignment(_) | Expression::Arrow(_) | Expression::Yield(_) | Expression::Spread(_) => ASSIGNMENT,
+        Expression::Conditional(_) => CONDITIONAL,
+        Expression::Binary(ref binary) => binary_precedence(binary.operator),
+        Expression::Unary(_) | Expression::Await(_) => UNARY,
+        Expression::Update(ref update) => if update.prefix { UNARY } else { POSTFIX },
+        Expression::Call(_) | Expression::New(_) | Expression::StaticMember(_) | Expression::ComputedMember(_)
+        | Expression::TaggedTemplate(_) | Expression::Import(_) => CALL,
+        // The types are stripped.
+        Expression::TsAs(ref as_expr) | Expression::TsSatisfies(ref as_expr) => expression_precedence(as_expr.expression),
+        Expression::TsNonNull(inner) => expression_precedence(inner),
+        _ => PRIMARY,
+    }
+}
+
+/// `??` cannot be mixed with `||` and `&&` without parentheses.
+#[inline]
+fn operand_precedence(operator: BinaryOperator, operand: &Loc<Expression>, precedence: u8) -> u8 {
+    match operand.item {
+        Expression::Binary(ref binary) if operator == BinaryOperator::Nullish
+            && matches!(binary.operator, BinaryOperator::And | BinaryOperator::Or) => PRIMARY,
+        _ => precedence,
+    }
+}
+
+/// The callee of `new` is called in `f().a`.
+fn has_call(expr: &Loc<Expression>) -> bool {
+    match expr.item {
+        Expression::Call(_) => true,
+        Expression::StaticMember(ref member) => has_call(member.object),
+        Expression::ComputedMember(ref member) => has_call(member.object),
+        Expression::TaggedTemplate(ref tagged) => has_call(tagged.tag),
+        Expression::TsAs(ref as_expr) | Expression::TsSatisfies(ref as_expr) => has_call(as_expr.expression),
+        Expression::TsNonNull(inner) => has_call(inner),
+        _ => false,
+    }
+}
+
+/// The token an expression starts with, as far as a statement cares.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Leftmost {
+    Brace,
+    Function,
+    Class,
+    Other,
+}
+
+fn leftmost(expr: &Loc<Expression>) -> Leftmost {
+    match expr.item {
+        Expression::Object(_) => Leftmost::Brace,
+        Expression::Function(_) => Leftmost::Function,
+        Expression::Class(_) => Leftmost::Class,
+        Expression::Binary(ref binary) => leftmost(binary.left),
+        Expression::Conditional(ref conditional) => leftmost(conditional.test),
+        Expression::Assignment(ref assignment) => match assignment.left.item {
+            Pattern::Object(_) => Leftmost::Brace,
+            Pattern::Expression(expr) => leftmost(expr),
+            _ => Leftmost::Other,
+        },
+        Expression::Sequence(expressions) => expressions.first_element().map_or(Leftmost::Other, |expr| leftmost(expr)),
+        Expression::Call(ref call) => leftmost(call.callee),
+        Expression::StaticMember(ref member) => leftmost(member.object),
+        Expression::ComputedMember(ref member) => leftmost(member.object),
+        Expression::TaggedTemplate(ref tagged) => leftmost(tagged.tag),
+        Expression::Update(ref update) if !update.prefix => leftmost(update.argument),
+        Expression::TsAs(ref as_expr) | Expression::TsSatisfies(ref as_expr) => leftmost(as_expr.expression),
+        Expression::TsNonNull(inner) => leftmost(inner),
+        _ => Leftmost::Other,
+    }
+}
+
+
+// Collects the outermost JSX nodes of an expression and the TypeScript
+// syntax to strip, the generator takes care of the JSX nested in their children.
+
+#[inline]
+fn remove<'a, T>(node: &Loc<T>, nested: &mut Vec<Loc<Edit<'a>>>) {
+    nested.push(Loc::new(node.start, node.end, Edit::Remove));
+}
+
+/// TypeScript the output has no equivalent for, at `loc`.
+fn unsupported<T>(code: &str, loc: &Loc<T>, message: &str) -> ParseError {
+    ParseError::new(code, Error::UnexpectedToken, (loc.start, loc.end), Vec::new(), Some(message.to_string()))
+}
+
+/// Value of an enum initializer that is a number, `1`, `-1` or `0x10`.
+fn enum_number(code: &str, init: &Loc<Expression>) -> Option<f64> {
+    match init.item {
+        Expression::Literal(Literal::Number) => Some(number_value(&code[init.start..init.end])),
+        Expression::Unary(ref unary) if unary.operator == UnaryOperator::Minus => {
+            enum_number(code, unary.argument).map(|value| -value)
+        },
+        _ => None,
+    }
+}
+
+/// `a` of `private a: A = b`.
+fn parameter_property_name<'a>(param: &Loc<Pattern<'a>>) -> Option<&'a str> {
+    match param.item {
+        Pattern::TsParameterProperty(ref property) => binding_name(property.parameter),
+        _ => None,
+    }
+}
+
+fn binding_name<'a>(pattern: &Loc<Pattern<'a>>) -> Option<&'a str> {
+    match pattern.item {
+        Pattern::Identifier(name) => Some(name),
+        Pattern::Typed(ref typed) => binding_name(typed.pattern),
+        Pattern::Assignment(ref assignment) => binding_name(assignment.left),
+        _ => None,
+    }
+}
+
+/// `super(...);`
+fn is_super_call(stmt: &Loc<Statement>) -> bool {
+    match stmt.item {
+        Statement::Expression(expr) => match expr.item {
+            Expression::Call(ref call) => call.callee.item == Expression::Super,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn collect_expression<'a>(expr: &Loc<Expression<'a>>, nested: &mut Vec<Loc<Edit<'a>>>) {
+    match expr.item {
+        Expression::Element(elem) => {
+            nested.push(Loc::new(expr.start, expr.end, Edit::Element(elem)));
+        },
+        Expression::Fragment(fragment) => {
+            nested.push(Loc::new(expr.start, expr.end, Edit::Fragment(fragment)));
+        },
+        Expression::This | Expression::Super | Expression::Identifier(_) | Expression::Literal(_) => {},
+        Expression::Template(ref template) => {
+            for expr in template.expressions.iter() {
+                collect_expression(expr, nested);
+            }
+        },
+        Expression::TaggedTemplate(ref tagged) => {
+            collect_expression(tagged.tag, nested);
+            if let Some(type_args) = tagged.type_args {
+                remove(type_args, nested);
+            }
+            for expr in tagged.quasi.expressions.iter() {
+                collect_expression(expr, nested);
+            }
+        },
+        Expression::Array(ref array) => {
+            for element in array.elements.iter().flatten() {
+                collect_expression(element, nested);
+            }
+        },
+        Expression::Object(ref object) => {
+            for property in object.properties.iter() {
+                collect_property(property, nested);
+            }
+        },
+        Expression::Function(ref function) => collect_function(function, nested),
+        Expression::Class(ref class) => collect_class(class, nested),
+        Expression::Arrow(ref arrow) => {
+            if let Some(type_params) = arrow.type_params {
+                remove(type_params, nested);
+            }
+            collect_params(arrow.params, nested);
+            if let Some(return_type) = arrow.return_type {
+                remove(return_type, nested);
+            }
+            match arrow.body {
+                ArrowBody::Expression(expr) => collect_expression(expr, nested),
+                ArrowBody::Block(ref block) => {
+                    for stmt in block.body.iter() {
+                        collect_statement(stmt, nested);
+                    }
+                },
+            }
+        },
+        Expression::Parenthesized(expr)
+        | Expression::Spread(expr)
+        | Expression::Await(expr)
+        | Expression::Import(expr) => collect_expression(expr, nested),
+        Expression::MetaProperty(_) => {},
+        Expression::Unary(ref unary) => collect_expression(unary.argument, nested),
+        Expression::Update(ref update) => collect_expression(update.argument, nested),
+        Expression::Binary(ref binary) => {
+            collect_expression(binary.left, nested);
+            collect_expression(binary.right, nested);
+        },
+        Expression::Conditional(ref conditional) => {
+            collect_expression(conditional.test, nested);
+            collect_expression(conditional.consequent, nested);
+            collect_expression(conditional.alternate, nested);
+        },
+        Expression::Assignment(ref assignment) => {
+            collect_pattern(assignment.left, nested);
+            collect_expression(assignment.right, nested);
+        },
+        Expression::Sequence(ref expressions) => {
+            for expr in expressions.iter() {
+                collect_expression(expr, nested);
+            }
+        },
+        Expression::Call(ref call) => {
+            collect_expression(call.callee, nested);
+            if let Some(type_args) = call.type_args {
+                remove(type_args, nested);
+            }
+            for argument in call.arguments.iter() {
+                collect_expression(argument, nested);
+            }
+        },
+        Expression::New(ref new) => {
+            collect_expression(new.callee, nested);
+            if let Some(type_args) = new.type_args {
+                remove(type_args, nested);
+            }
+            for argument in new.arguments.iter() {
+                collect_expression(argument, nested);
+            }
+        },
+        Expression::StaticMember(ref member) => collect_expression(member.object, nested),
+        Expression::ComputedMember(ref member) => {
+            collect_expression(member.object, nested);
+            collect_expression(member.property, nested);
+        },
+        Expression::Yield(ref yield_expr) => {
+            if let Some(argument) = yield_expr.argument {
+                collect_expression(argument, nested);
+            }
+        },
+        // a as T, a satisfies T
+        Expression::TsAs(ref as_expr) | Expression::TsSatisfies(ref as_expr) => {
+            collect_expression(as_expr.expression, nested);
+            nested.push(Loc::new(as_expr.expression.end, expr.end, Edit::Remove));
+        },
+        // a!
+        Expression::TsNonNull(inner) => {
+            collect_expression(inner, nested);
+            nested.push(Loc::new(inner.end, expr.end, Edit::Remove));
+        },
+    }
+}
+
+fn collect_property_key<'a>(key: &PropertyKey<'a>, nested: &mut Vec<Loc<Edit<'a>>>) {
+    if let PropertyKey::Computed(expr) = *key {
+        collect_expression(expr, nested);
+    }
+}
+
+fn collect_property<'a>(property: &Loc<Property<'a>>, nested: &mut Vec<Loc<Edit<'a>>>) {
+    match property.item {
+        Property::Init { ref key, value } => {
+            collect_property_key(key, nested);
+            collect_expression(value, nested);
+        },
+        Property::Shorthand(_) => {},
+        Property::Method { ref key, ref value, .. } => {
+            collect_property_key(key, nested);
+            collect_function(value, nested);
+        },
+        Property::Spread(expr) => collect_expression(expr, nested),
+    }
+}
+
+fn collect_function<'a>(function: &Function<'a>, nested: &mut Vec<Loc<Edit<'a>>>) {
+    if let Some(type_params) = function.type_params {
+        remove(type_params, nested);
+    }
+    collect_params(function.params, nested);
+    if let Some(return_type) = function.return_type {
+        remove(return_type, nested);
+    }
+    for stmt in function.body.body.iter() {
+        collect_statement(stmt, nested);
+    }
+}
+
+/// `function f(this: Window, a)` loses the `this` parameter.
+fn collect_params<'a>(params: NodeList<'a, Pattern<'a>>, nested: &mut Vec<Loc<Edit<'a>>>) {
+    let mut params = params.iter().peekable();
+    while let Some(param) = params.next() {
+        match param.item {
+            Pattern::Typed(ref typed) if is_this_param(typed.pattern) => {
+                let end = params.peek().map_or(param.end, |next| next.start);
+                nested.push(Loc::new(param.start, end, Edit::Remove));
+            },
+            _ => collect_pattern(param, nested),
+        }
+    }
+}
+
+#[inline]
+fn is_this_param(pattern: &Loc<Pattern>) -> bool {
+    match pattern.item {
+        Pattern::Expression(expr) => expr.item == Expression::This,
+        _ => false,
+    }
+}
+
+fn collect_class<'a>(class: &Class<'a>, nested: &mut Vec<Loc<Edit<'a>>>) {
+    for modifier in class.modifiers.iter() {
+        remove(modifier, nested);
+    }
+    if let Some(type_params) = class.type_params {
+        remove(type_params, nested);
+    }
+    if let Some(super_class) = class.super_class {
+        collect_expression(super_class, nested);
+    }
+    if let Some(super_type_args) = class.super_type_args {
+        remove(super_type_args, nested);
+    }
+    if let Some(ref implements) = class.implements {
+        remove(implements, nested);
+    }
+
+    for member in class.body.iter() {
+        match member.item {
+            ClassMember::Method { ref modifiers, kind, ref key, ref optional, ref value, .. } => {
+                for modifier in modifiers.iter() {
+                    remove(modifier, nested);
+                }
+                collect_property_key(key, nested);
+                if let Some(ref optional) = *optional {
+                    remove(optional, nested);
+                }
+                // The assignments follow `super()`, `this` is undefined before it.
+                if kind == MethodKind::Constructor && value.params.iter().any(|param| parameter_property_name(param).is_some()) {
+                    let body = &value.body;
+                    let index = body.body.iter().find(|stmt| is_super_call(stmt)).map_or(body.start + 1, |stmt| stmt.end);
+                    nested.push(Loc::new(index, index, Edit::ParameterProperties(value.params)));
+                }
+                collect_function(value, nested);
+            },
+            // declare a: string; abstract b: number;
+            ClassMember::Property { ref modifiers, .. } if modifiers.iter()
+                .any(|modifier| modifier.item == TsModifier::Declare || modifier.item == TsModifier::Abstract) =>
+            {
+                remove(member, nested);
+            },
+            ClassMember::Property { ref modifiers, ref key, ref annotation, ref value, .. } => {
+                for modifier in modifiers.iter() {
+                    remove(modifier, nested);
+                }
+                collect_property_key(key, nested);
+                if let Some(ref annotation) = *annotation {
+                    remove(annotation, nested);
+                }
+                if let Some(value) = *value {
+                    collect_expression(value, nested);
+                }
+            },
+            ClassMember::TsMethodSignature { .. } | ClassMember::TsIndexSignature(_) => remove(member, nested),
+            ClassMember::StaticBlock(ref block) => {
+                for stmt in block.body.iter() {
+                    collect_statement(stmt, nested);
+                }
+            },
+        }
+    }
+}
+
+fn collect_pattern<'a>(pattern: &Loc<Pattern<'a>>, nested: &mut Vec<Loc<Edit<'a>>>) {
+    match pattern.item {
+        Pattern::Identifier(_) => {},
+        Pattern::Object(ref object) => {
+            for property in object.properties.iter() {
+                match property.item {
+                    PatternProperty::Property { ref key, value } => {
+                        collect_property_key(key, nested);
+                        collect_pattern(value, nested);
+                    },
+                    PatternProperty::Shorthand { ref init, .. } => {
+                        if let Some(init) = *init {
+                            collect_expression(init, nested);
+                        }
+                    },
+                    PatternProperty::Rest(pattern) => collect_pattern(pattern, nested),
+                }
+            }
+        },
+        Pattern::Array(ref array) => {
+            for element in array.elements.iter().flatten() {
+                collect_pattern(element, nested);
+            }
+        },
+        Pattern::Assignment(ref assignment) => {
+            collect_pattern(assignment.left, nested);
+            collect_expression(assignment.right, nested);
+        },
+        Pattern::Rest(pattern) => collect_pattern(pattern, nested),
+        Pattern::Expression(expr) => collect_expression(expr, nested),
+        Pattern::Typed(ref typed) => {
+            collect_pattern(typed.pattern, nested);
+            remove(&typed.annotation, nested);
+        },
+        // `private readonly a` goes up to `a`.
+        Pattern::TsParameterProperty(ref property) => {
+            let mut modifiers = property.modifiers.iter().peekable();
+            while let Some(modifier) = modifiers.next() {
+                let end = modifiers.peek().map_or(property.parameter.start, |next| next.start);
+                nested.push(Loc::new(modifier.start, end, Edit::Remove));
+            }
+            collect_pattern(property.parameter, nested);
+        },
+    }
+}
+
+fn collect_statement<'a>(stmt: &Loc<Statement<'a>>, nested: &mut Vec<Loc<Edit<'a>>>) {
+    match stmt.item {
+        Statement::Empty | Statement::Debugger | Statement::Break(_) | Statement::Continue(_) | Statement::Error => {},
+        Statement::Import(ref import) => {
+            let is_type = import.is_type || (!import.specifiers.is_empty() && import.specifiers.iter()
+                .all(|specifier| matches!(specifier.item, ImportSpecifier::Named { is_type: true, .. })));
+            if is_type {
+                return remove(stmt, nested);
+            }
+
+            // The default import precedes the braces.
+            let mut named = import.specifiers;
+            if let Some(&&Loc { item: ImportSpecifier::Default(_), .. }) = named.first_element() {
+                named.shift_ref();
+            }
+            let named = named.iter().copied().collect::<Vec<_>>();
+            let is_kept = named.iter()
+                .map(|specifier| !matches!(specifier.item, ImportSpecifier::Named { is_type: true, .. }))
+                .collect::<Vec<bool>>();
+            collect_type_specifiers(&named, &is_kept, nested);
+        },
+        Statement::ExportAll(ref export) => {
+            if export.is_type {
+                remove(stmt, nested);
+            }
+        },
+        Statement::Expression(expr) | Statement::Throw(expr) => collect_expression(expr, nested),
+        Statement::Block(ref block) => {
+            for stmt in block.body.iter() {
+                collect_statement(stmt, nested);
+            }
+        },
+        Statement::Variable(ref declaration) => {
+            for declarator in declaration.declarations.iter() {
+                collect_pattern(declarator.id, nested);
+                if let Some(init) = declarator.init {
+                    collect_expression(init, nested);
+                }
+            }
+        },
+        Statement::Function(ref function) => collect_function(function, nested),
+        Statement::Class(ref class) => collect_class(class, nested),
+        Statement::Return(ref argument) => {
+            if let Some(argument) = *argument {
+                collect_expression(argument, nested);
+            }
+        },
+        Statement::If(ref if_stmt) => {
+            collect_expression(if_stmt.test, nested);
+            collect_statement(if_stmt.consequent, nested);
+            if let Some(alternate) = if_stmt.alternate {
+                collect_statement(alternate, nested);
+            }
+        },
+        Statement::Switch(ref switch) => {
+            collect_expression(switch.discriminant, nested);
+            for case in switch.cases.iter() {
+                if let Some(test) = case.test {
+                    collect_expression(test, nested);
+                }
+                for stmt in case.consequent.iter() {
+                    collect_statement(stmt, nested);
+                }
+            }
+        },
+        Statement::While(ref while_stmt) => {
+            collect_expression(while_stmt.test, nested);
+            collect_statement(while_stmt.body, nested);
+        },
+        Statement::DoWhile(ref do_while) => {
+            collect_statement(do_while.body, nested);
+            collect_expression(do_while.test, nested);
+        },
+        Statement::For(ref for_stmt) => {
+            match for_stmt.init {
+                Some(ForInit::Variable(ref declaration)) => {
+                    for declarator in declaration.declarations.iter() {
+                        collect_pattern(declarator.id, nested);
+                        if let Some(init) = declarator.init {
+                            collect_expression(init, nested);
+                        }
+                    }
+                },
+                Some(ForInit::Expression(expr)) => collect_expression(expr, nested),
+                None => {},
+            }
+            if let Some(test) = for_stmt.test {
+                collect_expression(test, nested);
+            }
+            if let Some(update) = for_stmt.update {
+                collect_expression(update, nested);
+            }
+            collect_statement(for_stmt.body, nested);
+        },
+        Statement::ForIn(ref for_in) => {
+            collect_for_head(&for_in.left, nested);
+            collect_expression(for_in.right, nested);
+            collect_statement(for_in.body, nested);
+        },
+        Statement::ForOf(ref for_of) => {
+            collect_for_head(&for_of.left, nested);
+            collect_expression(for_of.right, nested);
+            collect_statement(for_of.body, nested);
+        },
+        Statement::Try(ref try_stmt) => {
+            for stmt in try_stmt.block.body.iter() {
+                collect_statement(stmt, nested);
+            }
+            if let Some(ref handler) = try_stmt.handler {
+                if let Some(param) = handler.param {
+                    collect_pattern(param, nested);
+                }
+                for stmt in handler.body.body.iter() {
+                    collect_statement(stmt, nested);
+                }
+            }
+            if let Some(ref finalizer) = try_stmt.finalizer {
+                for stmt in finalizer.body.iter() {
+                    collect_statement(stmt, nested);
+                }
+            }
+        },
+        Statement::Labeled(ref labeled) => collect_statement(labeled.body, nested),
+        Statement::With(ref with) => {
+            collect_expression(with.object, nested);
+            collect_statement(with.body, nested);
+        },
+        Statement::ExportNamed(ref export) => {
+            let is_kept = export.specifiers.iter()
+                .map(|specifier| !specifier.is_type)
+                .collect::<Vec<bool>>();
+
+            match export.declaration {
+                Some(declaration) if is_type_declaration(declaration) => remove(stmt, nested),
+                Some(declaration) => collect_statement(declaration, nested),
+                None if export.is_type || (!is_kept.is_empty() && !is_kept.contains(&true)) => remove(stmt, nested),
+                None => {
+                    let specifiers = export.specifiers.iter().copied().collect::<Vec<_>>();
+                    collect_type_specifiers(&specifiers, &is_kept, nested);
+                },
+            }
+        },
+        Statement::ExportDefault(ref export) => {
+            match *export {
+                ExportDefaultDeclaration::Function(ref function) => collect_function(function, nested),
+                ExportDefaultDeclaration::Class(ref class) => collect_class(class, nested),
+                ExportDefaultDeclaration::Expression(expr) => collect_expression(expr, nested),
+                ExportDefaultDeclaration::TsDeclareFunction(_)
+                | ExportDefaultDeclaration::TsInterface(_) => remove(stmt, nested),
+            }
+        },
+        Statement::TsEnum(declaration) => nested.push(Loc::new(stmt.start, stmt.end, Edit::Enum(declaration))),
+        Statement::TsModule(_) if !is_type_declaration(stmt) => nested.push(Loc::new(stmt.start, stmt.end, Edit::Namespace)),
+        Statement::TsInterface(_) | Statement::TsTypeAlias(_) | Statement::TsModule(_)
+        | Statement::TsDeclareFunction(_) | Statement::TsDeclare(_) => {
+            remove(stmt, nested);
+        },
+    }
+}
+
+/// TypeScript declarations that leave no code, unlike an `enum` or a
+/// namespace with values.
+fn is_type_declaration(stmt: &Loc<Statement>) -> bool {
+    match stmt.item {
+        Statement::TsInterface(_) | Statement::TsTypeAlias(_) | Statement::TsDeclareFunction(_)
+        | Statement::TsDeclare(_) => true,
+        Statement::TsModule(ref module) => module.body.is_none_or(|body| body.iter().all(|stmt| match stmt.item {
+            Statement::ExportNamed(ref export) => export.declaration.is_some_and(is_type_declaration),
+            _ => is_type_declaration(stmt),
+        })),
+        _ => false,
+    }
+}
+
+/// `{ A, type B, C }` becomes `{ A, C }`, a specifier is removed up to the
+/// next one or, if no kept one follows, from the end of the previous one.
+fn collect_type_specifiers<'a, T>(specifiers: &[&Loc<T>], is_kept: &[bool], nested: &mut Vec<Loc<Edit<'a>>>) {
+    for (index, specifier) in specifiers.iter().enumerate() {
+        if is_kept[index] {
+            continue;
+        }
+
+        match specifiers.get(index + 1) {
+            Some(next) if is_kept[index + 1..].contains(&true) => {
+                nested.push(Loc::new(specifier.start, next.start, Edit::Remove));
+            },
+            _ if index > 0 => nested.push(Loc::new(specifiers[index - 1].end, specifier.end, Edit::Remove)),
+            _ => remove(specifier, nested),
+        }
+    }
+}
+
+fn collect_for_head<'a>(head: &ForHead<'a>, nested: &mut Vec<Loc<Edit<'a>>>) {
+    match *head {
+        ForHead::Variable(ref declaration) => {
+            for declarator in declaration.declarations.iter() {
+                collect_pattern(declarator.id, nested);
+            }
+        },
+        ForHead::Pattern(pattern) => collect_pattern(pattern, nested),
+    }
+}
+
+/// `children` is passed as an array, to `_jsxs`.
+#[inline]
+fn is_static_children(children: &[&Child]) -> bool {
+    children.len() > 1
+}
+
+/// Writes `value` as a double quoted JavaScript string literal.
+pub fn write_string_literal(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{2028}' => output.push_str("\\u2028"),
+            '\u{2029}' => output.push_str("\\u2029"),
+            c if (c as u32) < 0x20 => {
+                output.push_str(&format!("\\u{:04x}", c as u32));
+            },
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+/// Rewrites every JSX element and fragment in `input` for the configured runtime,
+/// `@jsx`, `@jsxFrag`, `@jsxRuntime` and `@jsxImportSource` comments in the file
+/// take precedence over `options`. `folds` run in order over the program parsed
+/// into `arena` before its code is generated. The source map of `output` back to
+/// `input` is built when `options.source_map` is set, its source is named after
+/// `options.filename`.
+pub fn transform_with_folds<'ast>(
+    input: &'ast str,
+    arena: &'ast Arena,
+    output: &mut String,
+    options: &TransformOptions,
+    folds: &mut [&mut dyn Fold<'ast>],
+) -> Result<Option<SourceMap>, ParseError> {
+    let source_map = if options.source_map { Some(SourceMapBuilder::new()) } else { None };
+    let source_map = transform_file(input, arena, output, options, source_map, folds)?;
+    let source_name = options.filename.as_deref().unwrap_or("unknown");
+
+    Ok(source_map.map(|source_map| source_map.build(source_name, input, output)))
+}
+
+fn transform_file<'ast>(
+    input: &'ast str,
+    arena: &'ast Arena,
+    output: &mut String,
+    options: &TransformOptions,
+    source_map: Option<SourceMapBuilder>,
+    folds: &mut [&mut dyn Fold<'ast>],
+) -> Result<Option<SourceMapBuilder>, ParseError> {
+    let mut parser = Parser::with_syntax(input, arena, options.syntax);
+    let mut program = parser.parse()?;
+
+    let config = Config::new(input, options, Pragmas::read(input, &program))?;
+    let mut context = Context::new(config, input);
+    context.source_map = source_map;
+
+    let parsed = program;
+    for fold in folds.iter_mut() {
+        program = fold.fold_program(program);
+    }
+    // Without a change the source is copied as it is.
+    let originals = if program == parsed { None } else { Some(Originals::new(arena.alloc(parsed))) };
+
+    let mut body = String::new();
+    let mut generator = Generator { code: input, output: &mut body, context: &mut context, originals: originals.as_ref() };
+    generator.write_program(&program)?;
+
+    let mut imports = String::new();
+    if context.config.runtime == Runtime::Automatic {
+        context.write_imports(&mut imports);
+    }
+
+    // The imports go after the shebang and the directives, as Babel puts them.
+    let mut header = header_len(input, &program);
+    if !body.starts_with(&input[..header]) {
+        header = 0;
+    }
+    if header > 0 && !imports.is_empty() {
+        let rest = &body[header..];
+        if rest.starts_with("\r\n") {
+            header += 2;
+        } else if rest.starts_with('\n') {
+            header += 1;
+        } else {
+            imports.insert(0, '\n');
+        }
+    }
+
+    let body_start = output.len();
+    output.push_str(&body[..header]);
+    output.push_str(&imports);
+    output.push_str(&body[header..]);
+
+    let mut source_map = context.source_map;
+    if let Some(ref mut source_map) = source_map {
+        source_map.shift(header, imports.len());
+        source_map.shift(0, body_start);
+    }
+
+    Ok(source_map)
+}
+
+/// Length of the `#!` line and the directive prologue, `"use client";` and
+/// the like, that start `program`.
+fn header_len(code: &str, program: &Program) -> usize {
+    let mut len = 0;
+    if code.starts_with("#!") {
+        len = code.find(is_line_terminator).unwrap_or(code.len());
+    }
+
+    for stmt in program.body.iter() {
+        match stmt.item {
+            // `("use strict");` is an expression, not a directive.
+            Statement::Expression(expr) if expr.item == Expression::Literal(Literal::String)
+                && stmt.start == expr.start => len = stmt.end,
+            _ => break,
+        }
+    }
+
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use toolshed::Arena;
+    use crate::list::List;
+
+    use super::{ Runtime, TransformOptions, };
+    use crate::parser::Syntax;
+    use crate::fold::{ Fold, walk_expression, };
+    use crate::ast::{ Loc, Expression, Literal, ElementName, MemberExpression, };
+
+    fn compile(source: &str, options: TransformOptions) -> String {
+        match crate::transform(source, options) {
+            Ok(output) => output.code,
+            Err(diagnostics) => panic!("{}", diagnostics),
+        }
+    }
+
+    fn automatic() -> TransformOptions {
+        TransformOptions { runtime: Runtime::Automatic, ..TransformOptions::default() }
+    }
+
+    #[test]
+    fn classic_runtime() {
+        assert_eq!(
+            compile("const a = <div className=\"x\" {...props}>Hello {name}!</div>;", TransformOptions::default()),
+            "const a = React.createElement(\"div\", { className: \"x\", ...props }, \"Hello \", name, \"!\");",
+        );
+        assert_eq!(
+            compile("const b = <><A.B c={1} d /></>;", TransformOptions::default()),
+            "const b = React.createElement(React.Fragment, null, React.createElement(A.B, { c: 1, d: true }));",
+        );
+    }
+
+    #[test]
+    fn space_between_expressions() {
+        assert_eq!(
+            compile("<b>{first} {last}</b>", TransformOptions::default()),
+            "React.createElement(\"b\", null, first, \" \", last)",
+        );
+        assert_eq!(
+            compile("<b>{first} {last}</b>", automatic()),
+            "import { jsxs as _jsxs } from \"react/jsx-runtime\";\n_jsxs(\"b\", { children: [first, \" \", last] })",
+        );
+    }
+
+    #[test]
+    fn automatic_runtime() {
+        let source = "const a = <div key=\"k\" {...p}>{x}</div>;\nconst b = <ul><li /><li /></ul>;\n";
+        assert_eq!(
+            compile(source, automatic()),
+            "import { jsx as _jsx, jsxs as _jsxs } from \"react/jsx-runtime\";\n\
+             const a = _jsx(\"div\", { ...p, children: x }, \"k\");\n\
+             const b = _jsxs(\"ul\", { children: [_jsx(\"li\", {}), _jsx(\"li\", {})] });\n",
+        );
+    }
+
+    #[test]
+    fn pragmas_from_comments() {
+        assert_eq!(compile("/** @jsx h */\n<a/>", TransformOptions::default()), "/** @jsx h */\nh(\"a\", null)");
+        assert_eq!(
+            compile("<a>{/* @jsxFrag F */}<></></a>", TransformOptions::default()),
+            "React.createElement(\"a\", null, React.createElement(F, null))",
+        );
+        assert_eq!(
+            compile("const doc = \"see /** @jsx h */\"; x = <a/>", TransformOptions::default()),
+            "const doc = \"see /** @jsx h */\"; x = React.createElement(\"a\", null)",
+        );
+        assert_eq!(
+            compile("x = <p>// @jsx h</p>", TransformOptions::default()),
+            "x = React.createElement(\"p\", null, \"// @jsx h\")",
+        );
+    }
+
+    #[test]
+    fn imports_after_directives() {
+        assert_eq!(
+            compile("\"use client\";\nx = <a/>;", automatic()),
+            "\"use client\";\nimport { jsx as _jsx } from \"react/jsx-runtime\";\nx = _jsx(\"a\", {});",
+        );
+        assert_eq!(
+            compile("#!/usr/bin/env node\n// c\n'use strict'; <a/>", automatic()),
+            "#!/usr/bin/env node\n// c\n'use strict';\nimport { jsx as _jsx } from \"react/jsx-runtime\";\n _jsx(\"a\", {})",
+        );
+        assert_eq!(
+            compile("(\"use strict\"); <a/>", automatic()),
+            "import { jsx as _jsx } from \"react/jsx-runtime\";\n(\"use strict\"); _jsx(\"a\", {})",
+        );
+    }
+
+    #[test]
+    fn source_positions() {
+        let options = TransformOptions {
+            development: true,
+            filename: Some("a.jsx".to_string()),
+            ..automatic()
+        };
+        let code = compile("a;\rb;\rx = <A/>", options.clone());
+        assert!(code.ends_with("{ fileName: _jsxFileName, lineNumber: 3, columnNumber: 5 }, this)"), "{}", code);
+        let code = compile("x = \"\u{1f600}\", <A/>", options);
+        assert!(code.ends_with("{ fileName: _jsxFileName, lineNumber: 1, columnNumber: 11 }, this)"), "{}", code);
+    }
+
+    #[test]
+    fn key_after_spread() {
+        assert_eq!(
+            compile("<a {...p} key=\"k\" />", automatic()),
+            "import { createElement as _createElement } from \"react\";\n_createElement(\"a\", { ...p, key: \"k\" })",
+        );
+    }
+
+    #[test]
+    fn spread_children() {
+        assert_eq!(compile("<a>{...b}</a>", TransformOptions::default()), "React.createElement(\"a\", null, ...b)");
+
+        let error = |source| crate::transform(source, automatic()).unwrap_err().to_string();
+        assert_eq!(error("<a>{...b}</a>"), "1:8: Spread children are not supported in React");
+        assert_eq!(error("<>x{...b}</>"), "1:8: Spread children are not supported in React");
+        assert_eq!(error("<a {...p} key=\"k\">{...b}</a>"), "1:23: Spread children are not supported in React");
+    }
+
+    /// `<Old />` becomes `<New />`, `<Gone />` becomes `null`, `<Bad />` `<ui.date-picker />`.
+    struct Rewrite<'ast>(&'ast Arena);
+
+    impl<'ast> Fold<'ast> for Rewrite<'ast> {
+        fn arena(&self) -> &'ast Arena {
+            self.0
+        }
+
+        fn fold_expression(&mut self, expr: Loc<Expression<'ast>>) -> Loc<Expression<'ast>> {
+            match expr.item {
+                Expression::Element(elem) if elem.name == ElementName::Identifier(Loc::new(0, 0, "Gone")) => {
+                    Loc::new(expr.start, expr.end, Expression::Literal(Literal::Null))
+                },
+                _ => walk_expression(self, expr),
+            }
+        }
+
+        fn fold_element_name(&mut self, name: ElementName<'ast>) -> ElementName<'ast> {
+            match name {
+                ElementName::Identifier(ident) if ident.item == "Old" => ElementName::Identifier(Loc { item: "New", ..ident }),
+                ElementName::Identifier(ident) if ident.item == "Bad" => {
+                    let members = [Loc { item: "ui", ..ident }, Loc { item: "date-picker", ..ident }];
+                    ElementName::MemberExpression(MemberExpression { members: List::from_iter(self.0, members) })
+                },
+                name => name,
+            }
+        }
+    }
+
+    fn compile_folded(source: &str, options: TransformOptions) -> Result<String, String> {
+        let arena = Arena::new();
+        let mut rewrite = Rewrite(&arena);
+        crate::transform_with_folds(source, &arena, options, &mut [&mut rewrite])
+            .map(|output| output.code)
+            .map_err(|diagnostics| diagnostics.to_string())
+    }
+
+    #[test]
+    fn folded_names() {
+        assert_eq!(
+            compile_folded("// App\nconst a = <Old x={1}>{b}</Old>;\n\nconst c = <div />;\n", TransformOptions::default()).unwrap(),
+            "// App\nconst a = React.createElement(New, { x: 1 }, b);\n\nconst c = React.createElement(\"div\", null);\n",
+        );
+    }
+
+    #[test]
+    fn folded_expressions() {
+        assert_eq!(
+            compile_folded("function App() {\n  // nothing\n  return <Gone />;\n}\n", TransformOptions::default()).unwrap(),
+            "function App() {\n  // nothing\n  return null;\n}\n",
+        );
+        assert_eq!(
+            compile_folded("x = <a>{cond && <Gone />}</a>", TransformOptions::default()).unwrap(),
+            "x = React.createElement(\"a\", null, cond && null);",
+        );
+        assert_eq!(
+            compile_folded("<Bad />", TransformOptions::default()).unwrap_err(),
+            "1:2: `date-picker` cannot be part of a member expression, it contains a `-`",
+        );
+    }
+
+    fn tsx() -> TransformOptions {
+        TransformOptions { syntax: Syntax::Tsx, ..TransformOptions::default() }
+    }
+
+    #[test]
+    fn typescript_enums() {
+        assert_eq!(
+            compile("export enum A { B, C = 5, D, 'e-f' = \"g\" }", tsx()),
+            "export var A;\n\
+             (function (A) {\n\
+             \x20 A[A[\"B\"] = 0] = \"B\";\n\
+             \x20 A[A[\"C\"] = 5] = \"C\";\n\
+             \x20 A[A[\"D\"] = 6] = \"D\";\n\
+             \x20 A['e-f'] = \"g\";\n\
+             })(A || (A = {}));",
+        );
+        assert_eq!(compile("declare enum A { B }\nnamespace N { type T = 1; }\nx;", tsx()), "x;");
+
+        let error = |source| crate::transform(source, tsx()).unwrap_err().to_string();
+        assert_eq!(error("enum A { B = \"b\", C }"), "1:19: enum member \"C\" needs an initializer");
+        assert_eq!(error("enum A { B = f() }"), "1:14: enum member \"B\" is computed, only numbers and strings are supported");
+        assert_eq!(error("namespace N { export const a = 1; }"), "1:1: TypeScript namespaces are not supported");
+    }
+
+    #[test]
+    fn parameter_properties() {
+        assert_eq!(
+            compile("class A { constructor(private readonly a: string, b?: number) {} }", tsx()),
+            "class A { constructor(a, b) { this.a = a; } }",
+        );
+        assert_eq!(
+            compile("class A extends B {\n  constructor(public a = 1) {\n    super();\n    f();\n  }\n}", tsx()),
+            "class A extends B {\n  constructor(a = 1) {\n    super();\n    this.a = a;\n    f();\n  }\n}",
+        );
+        assert_eq!(
+            compile_folded("class A {\n  constructor(protected a) {\n    <Gone />;\n  }\n}", tsx()).unwrap(),
+            "class A {\n  constructor(a) {\n    this.a = a;\n    null;\n  }\n}",
+        );
+    }
+}
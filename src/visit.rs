@@ -0,0 +1,1166 @@
+use toolshed::Arena;
+use crate::list::List;
+
+use crate::ast::{
+    Loc, NodeList, Program, Statement, BlockStatement, VariableDeclaration, ForInit, ForHead,
+    ExportDefaultDeclaration, Expression, ArrowBody, Property, PropertyKey, Pattern,
+    PatternProperty, Function, Class, ClassMember, ElementExpression, FragmentExpression,
+    ElementName, Attribute, NormalAttributeName, NormalAttributeInitializer, Child, Text,
+    EmptyExpression,
+};
+
+
+/// The node `enter` and `exit` are called with.
+#[derive(Debug, Clone, Copy)]
+pub enum Node<'a, 'ast> {
+    Program(&'a Program<'ast>),
+    Statement(&'a Loc<Statement<'ast>>),
+    Expression(&'a Loc<Expression<'ast>>),
+    Pattern(&'a Loc<Pattern<'ast>>),
+    Function(&'a Function<'ast>),
+    Class(&'a Class<'ast>),
+    ClassMember(&'a Loc<ClassMember<'ast>>),
+    Property(&'a Loc<Property<'ast>>),
+    Element(&'a Loc<ElementExpression<'ast>>),
+    Fragment(&'a Loc<FragmentExpression<'ast>>),
+    ElementName(&'a ElementName<'ast>),
+    Attribute(&'a Attribute<'ast>),
+    AttributeName(&'a NormalAttributeName<'ast>),
+    AttributeValue(&'a NormalAttributeInitializer<'ast>),
+    Text(&'a Loc<Text<'ast>>),
+    ExpressionContainer(&'a Loc<Expression<'ast>>),
+    SpreadChild(&'a Loc<Expression<'ast>>),
+    EmptyExpression(&'a Loc<EmptyExpression>),
+}
+
+
+/// Walks the AST in source order. Every method walks the children of its node
+/// with the matching `walk_*` function by default, an override calls it to
+/// keep walking and skips the subtree otherwise.
+///
+// struct CountElements(usize);
+//
+// impl<'ast> Visit<'ast> for CountElements {
+//     fn visit_element(&mut self, elem: &'ast Loc<ElementExpression<'ast>>) {
+//         self.0 += 1;
+//         walk_element(self, elem);
+//     }
+// }
+pub trait Visit<'ast> {
+    /// Called by every `walk_*` function before the children of `node`.
+    fn enter(&mut self, _node: Node<'ast, 'ast>) {}
+    /// Called by every `walk_*` function after the children of `node`.
+    fn exit(&mut self, _node: Node<'ast, 'ast>) {}
+
+    fn visit_program(&mut self, program: &'ast Program<'ast>) {
+        walk_program(self, program)
+    }
+
+    fn visit_statement(&mut self, stmt: &'ast Loc<Statement<'ast>>) {
+        walk_statement(self, stmt)
+    }
+
+    fn visit_expression(&mut self, expr: &'ast Loc<Expression<'ast>>) {
+        walk_expression(self, expr)
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast Loc<Pattern<'ast>>) {
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_function(&mut self, function: &'ast Function<'ast>) {
+        walk_function(self, function)
+    }
+
+    fn visit_class(&mut self, class: &'ast Class<'ast>) {
+        walk_class(self, class)
+    }
+
+    fn visit_class_member(&mut self, member: &'ast Loc<ClassMember<'ast>>) {
+        walk_class_member(self, member)
+    }
+
+    fn visit_property(&mut self, property: &'ast Loc<Property<'ast>>) {
+        walk_property(self, property)
+    }
+
+    fn visit_element(&mut self, elem: &'ast Loc<ElementExpression<'ast>>) {
+        walk_element(self, elem)
+    }
+
+    fn visit_fragment(&mut self, fragment: &'ast Loc<FragmentExpression<'ast>>) {
+        walk_fragment(self, fragment)
+    }
+
+    fn visit_element_name(&mut self, name: &'ast ElementName<'ast>) {
+        walk_element_name(self, name)
+    }
+
+    /// `name="value"` and `{...spread}`.
+    fn visit_attribute(&mut self, attr: &'ast Attribute<'ast>) {
+        walk_attribute(self, attr)
+    }
+
+    fn visit_attribute_name(&mut self, name: &'ast NormalAttributeName<'ast>) {
+        walk_attribute_name(self, name)
+    }
+
+    fn visit_attribute_value(&mut self, value: &'ast NormalAttributeInitializer<'ast>) {
+        walk_attribute_value(self, value)
+    }
+
+    fn visit_child(&mut self, child: &'ast Child<'ast>) {
+        walk_child(self, child)
+    }
+
+    fn visit_text(&mut self, text: &'ast Loc<Text<'ast>>) {
+        walk_text(self, text)
+    }
+
+    /// `{expr}` as a child, the span is the expression's.
+    fn visit_expression_container(&mut self, expr: &'ast Loc<Expression<'ast>>) {
+        walk_expression_container(self, expr)
+    }
+
+    /// `{...expr}` as a child.
+    fn visit_spread_child(&mut self, expr: &'ast Loc<Expression<'ast>>) {
+        walk_spread_child(self, expr)
+    }
+
+    /// `{}` and `{/* comment */}` as a child.
+    fn visit_empty_expression(&mut self, empty: &'ast Loc<EmptyExpression>) {
+        walk_empty_expression(self, empty)
+    }
+}
+
+
+pub fn walk_program<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, program: &'ast Program<'ast>) {
+    v.enter(Node::Program(program));
+    for stmt in &program.body {
+        v.visit_statement(stmt);
+    }
+    v.exit(Node::Program(program));
+}
+
+pub fn walk_statement<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, stmt: &'ast Loc<Statement<'ast>>) {
+    v.enter(Node::Statement(stmt));
+    match &stmt.item {
+        Statement::Empty | Statement::Debugger | Statement::Error => {},
+        Statement::Break(_) | Statement::Continue(_) => {},
+        Statement::Expression(expr) | Statement::Throw(expr) => v.visit_expression(expr),
+        Statement::Block(block) => walk_block(v, block),
+        Statement::Variable(declaration) => walk_variable_declaration(v, declaration),
+        Statement::Function(function) => v.visit_function(function),
+        Statement::Class(class) => v.visit_class(class),
+        Statement::Return(argument) => {
+            if let Some(argument) = argument {
+                v.visit_expression(argument);
+            }
+        },
+        Statement::If(stmt) => {
+            v.visit_expression(stmt.test);
+            v.visit_statement(stmt.consequent);
+            if let Some(alternate) = &stmt.alternate {
+                v.visit_statement(alternate);
+            }
+        },
+        Statement::Switch(stmt) => {
+            v.visit_expression(stmt.discriminant);
+            for case in &stmt.cases {
+                if let Some(test) = &case.item.test {
+                    v.visit_expression(test);
+                }
+                for stmt in &case.item.consequent {
+                    v.visit_statement(stmt);
+                }
+            }
+        },
+        Statement::While(stmt) => {
+            v.visit_expression(stmt.test);
+            v.visit_statement(stmt.body);
+        },
+        Statement::DoWhile(stmt) => {
+            v.visit_statement(stmt.body);
+            v.visit_expression(stmt.test);
+        },
+        Statement::For(stmt) => {
+            match &stmt.init {
+                Some(ForInit::Variable(declaration)) => walk_variable_declaration(v, &declaration.item),
+                Some(ForInit::Expression(expr)) => v.visit_expression(expr),
+                None => {},
+            }
+            if let Some(test) = &stmt.test {
+                v.visit_expression(test);
+            }
+            if let Some(update) = &stmt.update {
+                v.visit_expression(update);
+            }
+            v.visit_statement(stmt.body);
+        },
+        Statement::ForIn(stmt) => {
+            walk_for_head(v, &stmt.left);
+            v.visit_expression(stmt.right);
+            v.visit_statement(stmt.body);
+        },
+        Statement::ForOf(stmt) => {
+            walk_for_head(v, &stmt.left);
+            v.visit_expression(stmt.right);
+            v.visit_statement(stmt.body);
+        },
+        Statement::Try(stmt) => {
+            walk_block(v, &stmt.block.item);
+            if let Some(handler) = &stmt.handler {
+                if let Some(param) = &handler.item.param {
+                    v.visit_pattern(param);
+                }
+                walk_block(v, &handler.item.body.item);
+            }
+            if let Some(finalizer) = &stmt.finalizer {
+                walk_block(v, &finalizer.item);
+            }
+        },
+        Statement::Labeled(stmt) => v.visit_statement(stmt.body),
+        Statement::With(stmt) => {
+            v.visit_expression(stmt.object);
+            v.visit_statement(stmt.body);
+        },
+        Statement::Import(_) | Statement::ExportAll(_) => {},
+        Statement::ExportNamed(declaration) => {
+            if let Some(declaration) = &declaration.declaration {
+                v.visit_statement(declaration);
+            }
+        },
+        Statement::ExportDefault(declaration) => match declaration {
+            ExportDefaultDeclaration::Function(function) => v.visit_function(function),
+            ExportDefaultDeclaration::Class(class) => v.visit_class(class),
+            ExportDefaultDeclaration::Expression(expr) => v.visit_expression(expr),
+            ExportDefaultDeclaration::TsDeclareFunction(function) => walk_params(v, function.params),
+            ExportDefaultDeclaration::TsInterface(_) => {},
+        },
+        Statement::TsInterface(_) | Statement::TsTypeAlias(_) => {},
+        Statement::TsEnum(declaration) => {
+            for member in &declaration.members {
+                if let Some(init) = &member.item.init {
+                    v.visit_expression(init);
+                }
+            }
+        },
+        Statement::TsModule(module) => {
+            if let Some(body) = &module.body {
+                for stmt in body {
+                    v.visit_statement(stmt);
+                }
+            }
+        },
+        Statement::TsDeclareFunction(function) => walk_params(v, function.params),
+        Statement::TsDeclare(declaration) => v.visit_statement(declaration),
+    }
+    v.exit(Node::Statement(stmt));
+}
+
+fn walk_block<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, block: &'ast BlockStatement<'ast>) {
+    for stmt in &block.body {
+        v.visit_statement(stmt);
+    }
+}
+
+fn walk_variable_declaration<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, declaration: &'ast VariableDeclaration<'ast>) {
+    for declarator in &declaration.declarations {
+        v.visit_pattern(declarator.item.id);
+        if let Some(init) = &declarator.item.init {
+            v.visit_expression(init);
+        }
+    }
+}
+
+fn walk_for_head<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, head: &'ast ForHead<'ast>) {
+    match head {
+        ForHead::Variable(declaration) => walk_variable_declaration(v, &declaration.item),
+        ForHead::Pattern(pattern) => v.visit_pattern(pattern),
+    }
+}
+
+fn walk_params<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, params: NodeList<'ast, Pattern<'ast>>) {
+    for param in params {
+        v.visit_pattern(param);
+    }
+}
+
+/// Computed keys are expressions.
+fn walk_property_key<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, key: &'ast PropertyKey<'ast>) {
+    if let PropertyKey::Computed(expr) = key {
+        v.visit_expression(expr);
+    }
+}
+
+pub fn walk_expression<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, expr: &'ast Loc<Expression<'ast>>) {
+    v.enter(Node::Expression(expr));
+    match &expr.item {
+        Expression::This | Expression::Super | Expression::Identifier(_) => {},
+        Expression::Literal(_) | Expression::MetaProperty(_) => {},
+        Expression::Template(template) => {
+            for expr in &template.expressions {
+                v.visit_expression(expr);
+            }
+        },
+        Expression::TaggedTemplate(tagged) => {
+            v.visit_expression(tagged.tag);
+            for expr in &tagged.quasi.item.expressions {
+                v.visit_expression(expr);
+            }
+        },
+        Expression::Array(array) => {
+            for element in array.elements.iter().flatten() {
+                v.visit_expression(element);
+            }
+        },
+        Expression::Object(object) => {
+            for property in &object.properties {
+                v.visit_property(property);
+            }
+        },
+        Expression::Function(function) => v.visit_function(function),
+        Expression::Class(class) => v.visit_class(class),
+        Expression::Arrow(arrow) => {
+            walk_params(v, arrow.params);
+            match &arrow.body {
+                ArrowBody::Expression(expr) => v.visit_expression(expr),
+                ArrowBody::Block(block) => walk_block(v, &block.item),
+            }
+        },
+        Expression::Parenthesized(expr) | Expression::Spread(expr) | Expression::Await(expr) => {
+            v.visit_expression(expr);
+        },
+        Expression::Import(expr) | Expression::TsNonNull(expr) => v.visit_expression(expr),
+        Expression::Unary(unary) => v.visit_expression(unary.argument),
+        Expression::Update(update) => v.visit_expression(update.argument),
+        Expression::Binary(binary) => {
+            v.visit_expression(binary.left);
+            v.visit_expression(binary.right);
+        },
+        Expression::Conditional(conditional) => {
+            v.visit_expression(conditional.test);
+            v.visit_expression(conditional.consequent);
+            v.visit_expression(conditional.alternate);
+        },
+        Expression::Assignment(assignment) => {
+            v.visit_pattern(assignment.left);
+            v.visit_expression(assignment.right);
+        },
+        Expression::Sequence(expressions) => {
+            for expr in expressions {
+                v.visit_expression(expr);
+            }
+        },
+        Expression::Call(call) => {
+            v.visit_expression(call.callee);
+            for argument in &call.arguments {
+                v.visit_expression(argument);
+            }
+        },
+        Expression::New(new) => {
+            v.visit_expression(new.callee);
+            for argument in &new.arguments {
+                v.visit_expression(argument);
+            }
+        },
+        Expression::StaticMember(member) => v.visit_expression(member.object),
+        Expression::ComputedMember(member) => {
+            v.visit_expression(member.object);
+            v.visit_expression(member.property);
+        },
+        Expression::Yield(expr) => {
+            if let Some(argument) = &expr.argument {
+                v.visit_expression(argument);
+            }
+        },
+        Expression::Element(elem) => v.visit_element(elem),
+        Expression::Fragment(fragment) => v.visit_fragment(fragment),
+        Expression::TsAs(expr) | Expression::TsSatisfies(expr) => v.visit_expression(expr.expression),
+    }
+    v.exit(Node::Expression(expr));
+}
+
+pub fn walk_pattern<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, pattern: &'ast Loc<Pattern<'ast>>) {
+    v.enter(Node::Pattern(pattern));
+    match &pattern.item {
+        Pattern::Identifier(_) => {},
+        Pattern::Object(object) => {
+            for property in &object.properties {
+                match &property.item {
+                    PatternProperty::Property { key, value } => {
+                        walk_property_key(v, key);
+                        v.visit_pattern(value);
+                    },
+                    PatternProperty::Shorthand { init, .. } => {
+                        if let Some(init) = init {
+                            v.visit_expression(init);
+                        }
+                    },
+                    PatternProperty::Rest(pattern) => v.visit_pattern(pattern),
+                }
+            }
+        },
+        Pattern::Array(array) => {
+            for element in array.elements.iter().flatten() {
+                v.visit_pattern(element);
+            }
+        },
+        Pattern::Assignment(assignment) => {
+            v.visit_pattern(assignment.left);
+            v.visit_expression(assignment.right);
+        },
+        Pattern::Rest(pattern) => v.visit_pattern(pattern),
+        Pattern::Expression(expr) => v.visit_expression(expr),
+        Pattern::Typed(typed) => v.visit_pattern(typed.pattern),
+        Pattern::TsParameterProperty(property) => v.visit_pattern(property.parameter),
+    }
+    v.exit(Node::Pattern(pattern));
+}
+
+pub fn walk_function<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, function: &'ast Function<'ast>) {
+    v.enter(Node::Function(function));
+    walk_params(v, function.params);
+    walk_block(v, &function.body.item);
+    v.exit(Node::Function(function));
+}
+
+pub fn walk_class<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, class: &'ast Class<'ast>) {
+    v.enter(Node::Class(class));
+    if let Some(super_class) = &class.super_class {
+        v.visit_expression(super_class);
+    }
+    for member in &class.body {
+        v.visit_class_member(member);
+    }
+    v.exit(Node::Class(class));
+}
+
+pub fn walk_class_member<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, member: &'ast Loc<ClassMember<'ast>>) {
+    v.enter(Node::ClassMember(member));
+    match &member.item {
+        ClassMember::Method { key, value, .. } => {
+            walk_property_key(v, key);
+            v.visit_function(&value.item);
+        },
+        ClassMember::Property { key, value, .. } => {
+            walk_property_key(v, key);
+            if let Some(value) = value {
+                v.visit_expression(value);
+            }
+        },
+        ClassMember::StaticBlock(block) => walk_block(v, block),
+        ClassMember::TsMethodSignature { key, value, .. } => {
+            walk_property_key(v, key);
+            walk_params(v, value.item.params);
+        },
+        ClassMember::TsIndexSignature(signature) => walk_params(v, signature.params),
+    }
+    v.exit(Node::ClassMember(member));
+}
+
+pub fn walk_property<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, property: &'ast Loc<Property<'ast>>) {
+    v.enter(Node::Property(property));
+    match &property.item {
+        Property::Init { key, value } => {
+            walk_property_key(v, key);
+            v.visit_expression(value);
+        },
+        Property::Shorthand(_) => {},
+        Property::Method { key, value, .. } => {
+            walk_property_key(v, key);
+            v.visit_function(&value.item);
+        },
+        Property::Spread(expr) => v.visit_expression(expr),
+    }
+    v.exit(Node::Property(property));
+}
+
+pub fn walk_element<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, elem: &'ast Loc<ElementExpression<'ast>>) {
+    v.enter(Node::Element(elem));
+    v.visit_element_name(&elem.name);
+    for attr in &elem.attrs {
+        v.visit_attribute(attr);
+    }
+    if let Some(children) = &elem.children {
+        for child in children {
+            v.visit_child(child);
+        }
+    }
+    v.exit(Node::Element(elem));
+}
+
+pub fn walk_fragment<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, fragment: &'ast Loc<FragmentExpression<'ast>>) {
+    v.enter(Node::Fragment(fragment));
+    for child in &fragment.children {
+        v.visit_child(child);
+    }
+    v.exit(Node::Fragment(fragment));
+}
+
+pub fn walk_element_name<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, name: &'ast ElementName<'ast>) {
+    v.enter(Node::ElementName(name));
+    v.exit(Node::ElementName(name));
+}
+
+pub fn walk_attribute<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, attr: &'ast Attribute<'ast>) {
+    v.enter(Node::Attribute(attr));
+    match attr {
+        Attribute::Normal(normal) => {
+            v.visit_attribute_name(&normal.name);
+            if let Some(value) = &normal.init {
+                v.visit_attribute_value(value);
+            }
+        },
+        Attribute::Spread(expr) => v.visit_expression(expr),
+    }
+    v.exit(Node::Attribute(attr));
+}
+
+pub fn walk_attribute_name<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, name: &'ast NormalAttributeName<'ast>) {
+    v.enter(Node::AttributeName(name));
+    v.exit(Node::AttributeName(name));
+}
+
+pub fn walk_attribute_value<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, value: &'ast NormalAttributeInitializer<'ast>) {
+    v.enter(Node::AttributeValue(value));
+    match value {
+        NormalAttributeInitializer::LiteralString(_) => {},
+        NormalAttributeInitializer::AssignmentExpression(expr) => v.visit_expression(expr),
+        NormalAttributeInitializer::ElementExpression(elem) => v.visit_element(elem),
+        NormalAttributeInitializer::FragmentExpression(fragment) => v.visit_fragment(fragment),
+    }
+    v.exit(Node::AttributeValue(value));
+}
+
+/// Children are not nodes of their own, `enter` sees the element, text or
+/// container the child is.
+pub fn walk_child<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, child: &'ast Child<'ast>) {
+    match child {
+        Child::Text(text) => v.visit_text(text),
+        Child::Element(elem) => v.visit_element(elem),
+        Child::Fragment(fragment) => v.visit_fragment(fragment),
+        Child::ChildExpression(expr) => v.visit_expression_container(expr),
+        Child::Spread(expr) => v.visit_spread_child(expr),
+        Child::EmptyExpression(empty) => v.visit_empty_expression(empty),
+        Child::Error(_) => {},
+    }
+}
+
+pub fn walk_text<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, text: &'ast Loc<Text<'ast>>) {
+    v.enter(Node::Text(text));
+    v.exit(Node::Text(text));
+}
+
+pub fn walk_expression_container<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, expr: &'ast Loc<Expression<'ast>>) {
+    v.enter(Node::ExpressionContainer(expr));
+    v.visit_expression(expr);
+    v.exit(Node::ExpressionContainer(expr));
+}
+
+pub fn walk_spread_child<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, expr: &'ast Loc<Expression<'ast>>) {
+    v.enter(Node::SpreadChild(expr));
+    v.visit_expression(expr);
+    v.exit(Node::SpreadChild(expr));
+}
+
+pub fn walk_empty_expression<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, empty: &'ast Loc<EmptyExpression>) {
+    v.enter(Node::EmptyExpression(empty));
+    v.exit(Node::EmptyExpression(empty));
+}
+
+
+/// `Visit` over a mutable AST, for rewriting it. Nodes in the arena never
+/// change, the walk visits a copy of every node and allocates it in `arena`
+/// in place of the old one. `enter` and `exit` only look at the node.
+pub trait VisitMut<'ast> {
+    /// The arena of the parse, holds the rewritten nodes.
+    fn arena(&self) -> &'ast Arena;
+
+    /// Called by every `walk_*` function before the children of `node`.
+    fn enter(&mut self, _node: Node<'_, 'ast>) {}
+    /// Called by every `walk_*` function after the children of `node`.
+    fn exit(&mut self, _node: Node<'_, 'ast>) {}
+
+    fn visit_program(&mut self, program: &mut Program<'ast>) {
+        walk_program_mut(self, program)
+    }
+
+    fn visit_statement(&mut self, stmt: &mut Loc<Statement<'ast>>) {
+        walk_statement_mut(self, stmt)
+    }
+
+    fn visit_expression(&mut self, expr: &mut Loc<Expression<'ast>>) {
+        walk_expression_mut(self, expr)
+    }
+
+    fn visit_pattern(&mut self, pattern: &mut Loc<Pattern<'ast>>) {
+        walk_pattern_mut(self, pattern)
+    }
+
+    fn visit_function(&mut self, function: &mut Function<'ast>) {
+        walk_function_mut(self, function)
+    }
+
+    fn visit_class(&mut self, class: &mut Class<'ast>) {
+        walk_class_mut(self, class)
+    }
+
+    fn visit_class_member(&mut self, member: &mut Loc<ClassMember<'ast>>) {
+        walk_class_member_mut(self, member)
+    }
+
+    fn visit_property(&mut self, property: &mut Loc<Property<'ast>>) {
+        walk_property_mut(self, property)
+    }
+
+    fn visit_element(&mut self, elem: &mut Loc<ElementExpression<'ast>>) {
+        walk_element_mut(self, elem)
+    }
+
+    fn visit_fragment(&mut self, fragment: &mut Loc<FragmentExpression<'ast>>) {
+        walk_fragment_mut(self, fragment)
+    }
+
+    fn visit_element_name(&mut self, name: &mut ElementName<'ast>) {
+        walk_element_name_mut(self, name)
+    }
+
+    /// `name="value"` and `{...spread}`.
+    fn visit_attribute(&mut self, attr: &mut Attribute<'ast>) {
+        walk_attribute_mut(self, attr)
+    }
+
+    fn visit_attribute_name(&mut self, name: &mut NormalAttributeName<'ast>) {
+        walk_attribute_name_mut(self, name)
+    }
+
+    fn visit_attribute_value(&mut self, value: &mut NormalAttributeInitializer<'ast>) {
+        walk_attribute_value_mut(self, value)
+    }
+
+    fn visit_child(&mut self, child: &mut Child<'ast>) {
+        walk_child_mut(self, child)
+    }
+
+    fn visit_text(&mut self, text: &mut Loc<Text<'ast>>) {
+        walk_text_mut(self, text)
+    }
+
+    /// `{expr}` as a child, the span is the expression's.
+    fn visit_expression_container(&mut self, expr: &mut Loc<Expression<'ast>>) {
+        walk_expression_container_mut(self, expr)
+    }
+
+    /// `{...expr}` as a child.
+    fn visit_spread_child(&mut self, expr: &mut Loc<Expression<'ast>>) {
+        walk_spread_child_mut(self, expr)
+    }
+
+    /// `{}` and `{/* comment */}` as a child.
+    fn visit_empty_expression(&mut self, empty: &mut Loc<EmptyExpression>) {
+        walk_empty_expression_mut(self, empty)
+    }
+}
+
+
+/// Visits a copy of `node` and puts it in the arena in place of `node`.
+#[inline]
+fn visit_node<'ast, V, T, F>(v: &mut V, node: &mut &'ast Loc<T>, visit: F)
+    where V: VisitMut<'ast> + ?Sized, T: Copy, F: FnOnce(&mut V, &mut Loc<T>)
+{
+    let mut copy = **node;
+    visit(v, &mut copy);
+    *node = v.arena().alloc(copy);
+}
+
+/// Visits a copy of every item and links the copies into a new list.
+fn visit_list<'ast, V, T, F>(v: &mut V, list: &mut List<'ast, T>, mut visit: F)
+    where V: VisitMut<'ast> + ?Sized, T: Copy, F: FnMut(&mut V, &mut T)
+{
+    let arena = v.arena();
+    *list = List::from_iter(arena, list.iter().map(|item| {
+        let mut item = *item;
+        visit(v, &mut item);
+        item
+    }));
+}
+
+/// `visit_list` over nodes, `visit` sees a copy of every node.
+#[inline]
+fn visit_nodes<'ast, V, T, F>(v: &mut V, list: &mut NodeList<'ast, T>, mut visit: F)
+    where V: VisitMut<'ast> + ?Sized, T: Copy, F: FnMut(&mut V, &mut Loc<T>)
+{
+    visit_list(v, list, |v, node| visit_node(v, node, &mut visit));
+}
+
+pub fn walk_program_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, program: &mut Program<'ast>) {
+    v.enter(Node::Program(program));
+    visit_nodes(v, &mut program.body, V::visit_statement);
+    v.exit(Node::Program(program));
+}
+
+pub fn walk_statement_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, stmt: &mut Loc<Statement<'ast>>) {
+    v.enter(Node::Statement(stmt));
+    match &mut stmt.item {
+        Statement::Empty | Statement::Debugger | Statement::Error => {},
+        Statement::Break(_) | Statement::Continue(_) => {},
+        Statement::Expression(expr) | Statement::Throw(expr) => visit_node(v, expr, V::visit_expression),
+        Statement::Block(block) => walk_block_mut(v, block),
+        Statement::Variable(declaration) => walk_variable_declaration_mut(v, declaration),
+        Statement::Function(function) => v.visit_function(function),
+        Statement::Class(class) => v.visit_class(class),
+        Statement::Return(argument) => {
+            if let Some(argument) = argument {
+                visit_node(v, argument, V::visit_expression);
+            }
+        },
+        Statement::If(stmt) => {
+            visit_node(v, &mut stmt.test, V::visit_expression);
+            visit_node(v, &mut stmt.consequent, V::visit_statement);
+            if let Some(alternate) = &mut stmt.alternate {
+                visit_node(v, alternate, V::visit_statement);
+            }
+        },
+        Statement::Switch(stmt) => {
+            visit_node(v, &mut stmt.discriminant, V::visit_expression);
+            visit_nodes(v, &mut stmt.cases, |v, case| {
+                if let Some(test) = &mut case.item.test {
+                    visit_node(v, test, V::visit_expression);
+                }
+                visit_nodes(v, &mut case.item.consequent, V::visit_statement);
+            });
+        },
+        Statement::While(stmt) => {
+            visit_node(v, &mut stmt.test, V::visit_expression);
+            visit_node(v, &mut stmt.body, V::visit_statement);
+        },
+        Statement::DoWhile(stmt) => {
+            visit_node(v, &mut stmt.body, V::visit_statement);
+            visit_node(v, &mut stmt.test, V::visit_expression);
+        },
+        Statement::For(stmt) => {
+            match &mut stmt.init {
+                Some(ForInit::Variable(declaration)) => walk_variable_declaration_mut(v, &mut declaration.item),
+                Some(ForInit::Expression(expr)) => visit_node(v, expr, V::visit_expression),
+                None => {},
+            }
+            if let Some(test) = &mut stmt.test {
+                visit_node(v, test, V::visit_expression);
+            }
+            if let Some(update) = &mut stmt.update {
+                visit_node(v, update, V::visit_expression);
+            }
+            visit_node(v, &mut stmt.body, V::visit_statement);
+        },
+        Statement::ForIn(stmt) => {
+            walk_for_head_mut(v, &mut stmt.left);
+            visit_node(v, &mut stmt.right, V::visit_expression);
+            visit_node(v, &mut stmt.body, V::visit_statement);
+        },
+        Statement::ForOf(stmt) => {
+            walk_for_head_mut(v, &mut stmt.left);
+            visit_node(v, &mut stmt.right, V::visit_expression);
+            visit_node(v, &mut stmt.body, V::visit_statement);
+        },
+        Statement::Try(stmt) => {
+            walk_block_mut(v, &mut stmt.block.item);
+            if let Some(handler) = &mut stmt.handler {
+                if let Some(param) = &mut handler.item.param {
+                    visit_node(v, param, V::visit_pattern);
+                }
+                walk_block_mut(v, &mut handler.item.body.item);
+            }
+            if let Some(finalizer) = &mut stmt.finalizer {
+                walk_block_mut(v, &mut finalizer.item);
+            }
+        },
+        Statement::Labeled(stmt) => visit_node(v, &mut stmt.body, V::visit_statement),
+        Statement::With(stmt) => {
+            visit_node(v, &mut stmt.object, V::visit_expression);
+            visit_node(v, &mut stmt.body, V::visit_statement);
+        },
+        Statement::Import(_) | Statement::ExportAll(_) => {},
+        Statement::ExportNamed(declaration) => {
+            if let Some(declaration) = &mut declaration.declaration {
+                visit_node(v, declaration, V::visit_statement);
+            }
+        },
+        Statement::ExportDefault(declaration) => match declaration {
+            ExportDefaultDeclaration::Function(function) => v.visit_function(function),
+            ExportDefaultDeclaration::Class(class) => v.visit_class(class),
+            ExportDefaultDeclaration::Expression(expr) => visit_node(v, expr, V::visit_expression),
+            ExportDefaultDeclaration::TsDeclareFunction(function) => walk_params_mut(v, &mut function.params),
+            ExportDefaultDeclaration::TsInterface(_) => {},
+        },
+        Statement::TsInterface(_) | Statement::TsTypeAlias(_) => {},
+        Statement::TsEnum(declaration) => {
+            visit_nodes(v, &mut declaration.members, |v, member| {
+                if let Some(init) = &mut member.item.init {
+                    visit_node(v, init, V::visit_expression);
+                }
+            });
+        },
+        Statement::TsModule(module) => {
+            if let Some(body) = &mut module.body {
+                visit_nodes(v, body, V::visit_statement);
+            }
+        },
+        Statement::TsDeclareFunction(function) => walk_params_mut(v, &mut function.params),
+        Statement::TsDeclare(declaration) => visit_node(v, declaration, V::visit_statement),
+    }
+    v.exit(Node::Statement(stmt));
+}
+
+fn walk_block_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, block: &mut BlockStatement<'ast>) {
+    visit_nodes(v, &mut block.body, V::visit_statement);
+}
+
+fn walk_variable_declaration_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, declaration: &mut VariableDeclaration<'ast>) {
+    visit_nodes(v, &mut declaration.declarations, |v, declarator| {
+        visit_node(v, &mut declarator.item.id, V::visit_pattern);
+        if let Some(init) = &mut declarator.item.init {
+            visit_node(v, init, V::visit_expression);
+        }
+    });
+}
+
+fn walk_for_head_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, head: &mut ForHead<'ast>) {
+    match head {
+        ForHead::Variable(declaration) => walk_variable_declaration_mut(v, &mut declaration.item),
+        ForHead::Pattern(pattern) => visit_node(v, pattern, V::visit_pattern),
+    }
+}
+
+fn walk_params_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, params: &mut NodeList<'ast, Pattern<'ast>>) {
+    visit_nodes(v, params, V::visit_pattern);
+}
+
+/// Computed keys are expressions.
+fn walk_property_key_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, key: &mut PropertyKey<'ast>) {
+    if let PropertyKey::Computed(expr) = key {
+        visit_node(v, expr, V::visit_expression);
+    }
+}
+
+pub fn walk_expression_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, expr: &mut Loc<Expression<'ast>>) {
+    v.enter(Node::Expression(expr));
+    match &mut expr.item {
+        Expression::This | Expression::Super | Expression::Identifier(_) => {},
+        Expression::Literal(_) | Expression::MetaProperty(_) => {},
+        Expression::Template(template) => visit_nodes(v, &mut template.expressions, V::visit_expression),
+        Expression::TaggedTemplate(tagged) => {
+            visit_node(v, &mut tagged.tag, V::visit_expression);
+            visit_nodes(v, &mut tagged.quasi.item.expressions, V::visit_expression);
+        },
+        Expression::Array(array) => {
+            visit_list(v, &mut array.elements, |v, element| {
+                if let Some(element) = element {
+                    visit_node(v, element, V::visit_expression);
+                }
+            });
+        },
+        Expression::Object(object) => visit_nodes(v, &mut object.properties, V::visit_property),
+        Expression::Function(function) => v.visit_function(function),
+        Expression::Class(class) => v.visit_class(class),
+        Expression::Arrow(arrow) => {
+            walk_params_mut(v, &mut arrow.params);
+            match &mut arrow.body {
+                ArrowBody::Expression(expr) => visit_node(v, expr, V::visit_expression),
+                ArrowBody::Block(block) => walk_block_mut(v, &mut block.item),
+            }
+        },
+        Expression::Parenthesized(expr) | Expression::Spread(expr) | Expression::Await(expr) => visit_node(v, expr, V::visit_expression),
+        Expression::Import(expr) | Expression::TsNonNull(expr) => visit_node(v, expr, V::visit_expression),
+        Expression::Unary(unary) => visit_node(v, &mut unary.argument, V::visit_expression),
+        Expression::Update(update) => visit_node(v, &mut update.argument, V::visit_expression),
+        Expression::Binary(binary) => {
+            visit_node(v, &mut binary.left, V::visit_expression);
+            visit_node(v, &mut binary.right, V::visit_expression);
+        },
+        Expression::Conditional(conditional) => {
+            visit_node(v, &mut conditional.test, V::visit_expression);
+            visit_node(v, &mut conditional.consequent, V::visit_expression);
+            visit_node(v, &mut conditional.alternate, V::visit_expression);
+        },
+        Expression::Assignment(assignment) => {
+            visit_node(v, &mut assignment.left, V::visit_pattern);
+            visit_node(v, &mut assignment.right, V::visit_expression);
+        },
+        Expression::Sequence(expressions) => visit_nodes(v, expressions, V::visit_expression),
+        Expression::Call(call) => {
+            visit_node(v, &mut call.callee, V::visit_expression);
+            visit_nodes(v, &mut call.arguments, V::visit_expression);
+        },
+        Expression::New(new) => {
+            visit_node(v, &mut new.callee, V::visit_expression);
+            visit_nodes(v, &mut new.arguments, V::visit_expression);
+        },
+        Expression::StaticMember(member) => visit_node(v, &mut member.object, V::visit_expression),
+        Expression::ComputedMember(member) => {
+            visit_node(v, &mut member.object, V::visit_expression);
+            visit_node(v, &mut member.property, V::visit_expression);
+        },
+        Expression::Yield(expr) => {
+            if let Some(argument) = &mut expr.argument {
+                visit_node(v, argument, V::visit_expression);
+            }
+        },
+        Expression::Element(elem) => visit_node(v, elem, V::visit_element),
+        Expression::Fragment(fragment) => visit_node(v, fragment, V::visit_fragment),
+        Expression::TsAs(expr) | Expression::TsSatisfies(expr) => visit_node(v, &mut expr.expression, V::visit_expression),
+    }
+    v.exit(Node::Expression(expr));
+}
+
+pub fn walk_pattern_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, pattern: &mut Loc<Pattern<'ast>>) {
+    v.enter(Node::Pattern(pattern));
+    match &mut pattern.item {
+        Pattern::Identifier(_) => {},
+        Pattern::Object(object) => {
+            visit_nodes(v, &mut object.properties, |v, property| match &mut property.item {
+                PatternProperty::Property { key, value } => {
+                    walk_property_key_mut(v, key);
+                    visit_node(v, value, V::visit_pattern);
+                },
+                PatternProperty::Shorthand { init, .. } => {
+                    if let Some(init) = init {
+                        visit_node(v, init, V::visit_expression);
+                    }
+                },
+                PatternProperty::Rest(pattern) => visit_node(v, pattern, V::visit_pattern),
+            });
+        },
+        Pattern::Array(array) => {
+            visit_list(v, &mut array.elements, |v, element| {
+                if let Some(element) = element {
+                    visit_node(v, element, V::visit_pattern);
+                }
+            });
+        },
+        Pattern::Assignment(assignment) => {
+            visit_node(v, &mut assignment.left, V::visit_pattern);
+            visit_node(v, &mut assignment.right, V::visit_expression);
+        },
+        Pattern::Rest(pattern) => visit_node(v, pattern, V::visit_pattern),
+        Pattern::Expression(expr) => visit_node(v, expr, V::visit_expression),
+        Pattern::Typed(typed) => visit_node(v, &mut typed.pattern, V::visit_pattern),
+        Pattern::TsParameterProperty(property) => visit_node(v, &mut property.parameter, V::visit_pattern),
+    }
+    v.exit(Node::Pattern(pattern));
+}
+
+pub fn walk_function_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, function: &mut Function<'ast>) {
+    v.enter(Node::Function(function));
+    walk_params_mut(v, &mut function.params);
+    walk_block_mut(v, &mut function.body.item);
+    v.exit(Node::Function(function));
+}
+
+pub fn walk_class_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, class: &mut Class<'ast>) {
+    v.enter(Node::Class(class));
+    if let Some(super_class) = &mut class.super_class {
+        visit_node(v, super_class, V::visit_expression);
+    }
+    visit_nodes(v, &mut class.body, V::visit_class_member);
+    v.exit(Node::Class(class));
+}
+
+pub fn walk_class_member_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, member: &mut Loc<ClassMember<'ast>>) {
+    v.enter(Node::ClassMember(member));
+    match &mut member.item {
+        ClassMember::Method { key, value, .. } => {
+            walk_property_key_mut(v, key);
+            v.visit_function(&mut value.item);
+        },
+        ClassMember::Property { key, value, .. } => {
+            walk_property_key_mut(v, key);
+            if let Some(value) = value {
+                visit_node(v, value, V::visit_expression);
+            }
+        },
+        ClassMember::StaticBlock(block) => walk_block_mut(v, block),
+        ClassMember::TsMethodSignature { key, value, .. } => {
+            walk_property_key_mut(v, key);
+            walk_params_mut(v, &mut value.item.params);
+        },
+        ClassMember::TsIndexSignature(signature) => walk_params_mut(v, &mut signature.params),
+    }
+    v.exit(Node::ClassMember(member));
+}
+
+pub fn walk_property_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, property: &mut Loc<Property<'ast>>) {
+    v.enter(Node::Property(property));
+    match &mut property.item {
+        Property::Init { key, value } => {
+            walk_property_key_mut(v, key);
+            visit_node(v, value, V::visit_expression);
+        },
+        Property::Shorthand(_) => {},
+        Property::Method { key, value, .. } => {
+            walk_property_key_mut(v, key);
+            v.visit_function(&mut value.item);
+        },
+        Property::Spread(expr) => visit_node(v, expr, V::visit_expression),
+    }
+    v.exit(Node::Property(property));
+}
+
+pub fn walk_element_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, elem: &mut Loc<ElementExpression<'ast>>) {
+    v.enter(Node::Element(elem));
+    v.visit_element_name(&mut elem.item.name);
+    visit_list(v, &mut elem.item.attrs, V::visit_attribute);
+    if let Some(children) = &mut elem.item.children {
+        visit_list(v, children, V::visit_child);
+    }
+    v.exit(Node::Element(elem));
+}
+
+pub fn walk_fragment_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, fragment: &mut Loc<FragmentExpression<'ast>>) {
+    v.enter(Node::Fragment(fragment));
+    visit_list(v, &mut fragment.item.children, V::visit_child);
+    v.exit(Node::Fragment(fragment));
+}
+
+pub fn walk_element_name_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, name: &mut ElementName<'ast>) {
+    v.enter(Node::ElementName(name));
+    v.exit(Node::ElementName(name));
+}
+
+pub fn walk_attribute_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, attr: &mut Attribute<'ast>) {
+    v.enter(Node::Attribute(attr));
+    match attr {
+        Attribute::Normal(normal) => {
+            v.visit_attribute_name(&mut normal.name);
+            if let Some(value) = &mut normal.init {
+                v.visit_attribute_value(value);
+            }
+        },
+        Attribute::Spread(expr) => visit_node(v, expr, V::visit_expression),
+    }
+    v.exit(Node::Attribute(attr));
+}
+
+pub fn walk_attribute_name_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, name: &mut NormalAttributeName<'ast>) {
+    v.enter(Node::AttributeName(name));
+    v.exit(Node::AttributeName(name));
+}
+
+pub fn walk_attribute_value_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, value: &mut NormalAttributeInitializer<'ast>) {
+    v.enter(Node::AttributeValue(value));
+    match value {
+        NormalAttributeInitializer::LiteralString(_) => {},
+        NormalAttributeInitializer::AssignmentExpression(expr) => visit_node(v, expr, V::visit_expression),
+        NormalAttributeInitializer::ElementExpression(elem) => visit_node(v, elem, V::visit_element),
+        NormalAttributeInitializer::FragmentExpression(fragment) => visit_node(v, fragment, V::visit_fragment),
+    }
+    v.exit(Node::AttributeValue(value));
+}
+
+/// Children are not nodes of their own, `enter` sees the element, text or
+/// container the child is.
+pub fn walk_child_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, child: &mut Child<'ast>) {
+    match child {
+        Child::Text(text) => v.visit_text(text),
+        Child::Element(elem) => visit_node(v, elem, V::visit_element),
+        Child::Fragment(fragment) => visit_node(v, fragment, V::visit_fragment),
+        Child::ChildExpression(expr) => visit_node(v, expr, V::visit_expression_container),
+        Child::Spread(expr) => visit_node(v, expr, V::visit_spread_child),
+        Child::EmptyExpression(empty) => v.visit_empty_expression(empty),
+        Child::Error(_) => {},
+    }
+}
+
+pub fn walk_text_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, text: &mut Loc<Text<'ast>>) {
+    v.enter(Node::Text(text));
+    v.exit(Node::Text(text));
+}
+
+pub fn walk_expression_container_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, expr: &mut Loc<Expression<'ast>>) {
+    v.enter(Node::ExpressionContainer(expr));
+    v.visit_expression(expr);
+    v.exit(Node::ExpressionContainer(expr));
+}
+
+pub fn walk_spread_child_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, expr: &mut Loc<Expression<'ast>>) {
+    v.enter(Node::SpreadChild(expr));
+    v.visit_expression(expr);
+    v.exit(Node::SpreadChild(expr));
+}
+
+pub fn walk_empty_expression_mut<'ast, V: VisitMut<'ast> + ?Sized>(v: &mut V, empty: &mut Loc<EmptyExpression>) {
+    v.enter(Node::EmptyExpression(empty));
+    v.exit(Node::EmptyExpression(empty));
+}
+
+
+#[cfg(test)]
+mod tests {
+    use toolshed::Arena;
+
+    use crate::parser::ParseOptions;
+    use crate::ast::{ Loc, ElementExpression, };
+
+    use super::{ Visit, Node, };
+
+    /// `enter` and `exit` of every node by variant, `+Element` and `-Element`.
+    #[derive(Default)]
+    struct Trace {
+        events: Vec<String>,
+        skip_elements: bool,
+    }
+
+    fn variant(node: Node) -> String {
+        let debug = format!("{:?}", node);
+        debug[..debug.find('(').unwrap_or(debug.len())].to_string()
+    }
+
+    impl<'ast> Visit<'ast> for Trace {
+        fn enter(&mut self, node: Node<'ast, 'ast>) {
+            self.events.push(format!("+{}", variant(node)));
+        }
+
+        fn exit(&mut self, node: Node<'ast, 'ast>) {
+            self.events.push(format!("-{}", variant(node)));
+        }
+
+        fn visit_element(&mut self, elem: &'ast Loc<ElementExpression<'ast>>) {
+            if !self.skip_elements {
+                super::walk_element(self, elem);
+            }
+        }
+    }
+
+    fn trace(code: &str, skip_elements: bool) -> Vec<String> {
+        let arena = Arena::new();
+        let program = crate::parse(code, &arena, ParseOptions::default()).unwrap();
+        let mut trace = Trace { skip_elements, ..Trace::default() };
+        trace.visit_program(&program);
+        trace.events
+    }
+
+    #[test]
+    fn enter_and_exit_in_source_order() {
+        assert_eq!(trace("a = <b c=\"d\">{e}</b>;", false), [
+            "+Program", "+Statement", "+Expression",
+            "+Pattern", "-Pattern",
+            "+Expression", "+Element",
+            "+ElementName", "-ElementName",
+            "+Attribute", "+AttributeName", "-AttributeName", "+AttributeValue", "-AttributeValue", "-Attribute",
+            "+ExpressionContainer", "+Expression", "-Expression", "-ExpressionContainer",
+            "-Element", "-Expression",
+            "-Expression", "-Statement", "-Program",
+        ]);
+    }
+
+    #[test]
+    fn override_skips_subtree() {
+        assert_eq!(trace("f(<b>{e}</b>, x);", true), [
+            "+Program", "+Statement", "+Expression",
+            "+Expression", "-Expression",
+            "+Expression", "-Expression",
+            "+Expression", "-Expression",
+            "-Expression", "-Statement", "-Program",
+        ]);
+    }
+}